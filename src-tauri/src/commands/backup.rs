@@ -1,92 +1,677 @@
+use once_cell::sync::Lazy;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Archive header: "CVBAK" + format version.
+/// 001 is the plain snapshot pack written here; 002 is the encrypted
+/// stream produced by the Python backup service (DATA_DICTIONARY §4).
+pub const ARCHIVE_MAGIC: &[u8; 8] = b"CVBAK001";
+
+/// Footer of CVBAK001 packs: marker followed by the SHA-256 of every
+/// byte before it. Packs written before the footer existed end without it.
+pub const DIGEST_MARKER: &[u8; 8] = b"CVSHA256";
+pub const DIGEST_FOOTER_LEN: u64 = DIGEST_MARKER.len() as u64 + 32;
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+const EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Default progress channel shared by all backups (ADR-008 Rule #4)
+pub const BACKUP_CHANNEL: &str = "backup_progress";
+
+/// Disambiguates task ids of backups started within the same millisecond
+static TASK_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Cancellation flags of in-flight backups, keyed by task_id
+static BACKUP_TASKS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn register_task(task_id: &str) -> Arc<AtomicBool> {
+    let flag = Arc::new(AtomicBool::new(false));
+    BACKUP_TASKS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(task_id.to_string(), flag.clone());
+    flag
+}
+
+fn unregister_task(task_id: &str) {
+    BACKUP_TASKS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(task_id);
+}
+
+/// How long a finished task's events stay available to late subscribers
+const REPLAY_GRACE: Duration = Duration::from_secs(30);
+/// Events kept from the start of a task; later ones only replace `latest`
+const REPLAY_LIMIT: usize = 64;
+
+/// Events already emitted for a task, replayed by `cmd_backup_subscribe`
+#[derive(Default)]
+struct ReplayLog {
+    early: Vec<BackupPayload>,
+    latest: Option<BackupPayload>,
+    next_seq: u64,
+    finished: Option<Instant>,
+}
+
+/// Replay logs keyed by task_id
+static BACKUP_EVENTS: Lazy<Mutex<HashMap<String, ReplayLog>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn replay_logs() -> std::sync::MutexGuard<'static, HashMap<String, ReplayLog>> {
+    let mut logs = BACKUP_EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+    logs.retain(|_, log| log.finished.is_none_or(|t| t.elapsed() < REPLAY_GRACE));
+    logs
+}
+
+/// Start buffering events for `task_id` before its worker can emit any
+fn open_replay(task_id: &str) {
+    replay_logs().insert(task_id.to_string(), ReplayLog::default());
+}
+
+/// Stamp `payload` with its task's next sequence number and keep it for
+/// replay; terminal phases start the grace period
+fn record_event(mut payload: BackupPayload) -> BackupPayload {
+    let mut logs = replay_logs();
+    let log = logs.entry(payload.task_id.clone()).or_default();
+    payload.seq = log.next_seq;
+    log.next_seq += 1;
+    if matches!(payload.phase.as_str(), "done" | "cancelled" | "error") {
+        log.finished = Some(Instant::now());
+    }
+    if log.early.len() < REPLAY_LIMIT {
+        log.early.push(payload.clone());
+    } else {
+        log.latest = Some(payload.clone());
+    }
+    payload
+}
+
+/// Events of `task_id` emitted so far, in order: the first `REPLAY_LIMIT`
+/// and then the most recent one
+fn replay(task_id: &str) -> Option<Vec<BackupPayload>> {
+    let logs = replay_logs();
+    let log = logs.get(task_id)?;
+    Some(log.early.iter().chain(&log.latest).cloned().collect())
+}
+
+/// Tauri event names allow only alphanumerics and `-`, `/`, `:`, `_`
+fn validate_channel(channel: &str) -> Result<(), String> {
+    let valid = !channel.is_empty()
+        && channel
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '/' | ':' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid progress channel name: {:?}", channel))
+    }
+}
+
+/// Map worker I/O failures to stable codes the frontend can branch on
+fn io_error_code(err: &io::Error) -> &'static str {
+    match err.kind() {
+        io::ErrorKind::NotFound => "SOURCE_NOT_FOUND",
+        io::ErrorKind::PermissionDenied => "PERMISSION_DENIED",
+        io::ErrorKind::UnexpectedEof => "SOURCE_CHANGED",
+        _ => "IO_ERROR",
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+fn cancelled_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "backup cancelled")
+}
 
 #[derive(Serialize, Clone, Debug)]
 pub struct BackupPayload {
     pub task_id: String,
+    /// Per-task event number from 0, for deduplicating replayed events
+    pub seq: u64,
     pub phase: String,
     pub progress: f64,
     pub speed: String,
     pub eta: String,
     pub msg: String,
+    /// Machine-readable failure reason, set only when `phase == "error"`
+    pub error_code: Option<String>,
+}
+
+/// One file captured by the snapshot phase
+#[derive(Debug)]
+struct SnapshotEntry {
+    path: PathBuf,
+    /// Path relative to the backup source, `/`-separated
+    name: String,
+    size: u64,
+}
+
+/// Live copy statistics for progress events
+struct Throughput {
+    copied: u64,
+    total: u64,
+    started: Instant,
+}
+
+impl Throughput {
+    fn new(total: u64) -> Self {
+        Self {
+            copied: 0,
+            total,
+            started: Instant::now(),
+        }
+    }
+
+    fn progress(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        self.copied as f64 / self.total as f64 * 100.0
+    }
+
+    fn bytes_per_sec(&self) -> f64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        self.copied as f64 / elapsed
+    }
+
+    fn speed(&self) -> String {
+        format_speed(self.bytes_per_sec())
+    }
+
+    fn eta(&self) -> String {
+        let speed = self.bytes_per_sec();
+        if speed <= 0.0 {
+            return "CALC...".to_string();
+        }
+        let remaining = self.total.saturating_sub(self.copied) as f64;
+        format!("{}s", (remaining / speed).ceil() as u64)
+    }
+}
+
+fn format_speed(bytes_per_sec: f64) -> String {
+    format!("{:.1} MB/s", bytes_per_sec / (1024.0 * 1024.0))
+}
+
+/// Enumerate regular files under `source` (or `source` itself if it is a
+/// file), sorted by name. Symlinks are skipped so loops can't recurse.
+fn snapshot(source: &Path) -> io::Result<Vec<SnapshotEntry>> {
+    let meta = fs::metadata(source)?;
+    if meta.is_file() {
+        let name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        return Ok(vec![SnapshotEntry {
+            path: source.to_path_buf(),
+            name,
+            size: meta.len(),
+        }]);
+    }
+
+    let mut entries = Vec::new();
+    let mut pending = vec![(source.to_path_buf(), String::new())];
+    while let Some((dir, prefix)) = pending.pop() {
+        let mut children = fs::read_dir(&dir)?.collect::<io::Result<Vec<_>>>()?;
+        children.sort_by_key(|c| c.file_name());
+        for child in children {
+            let name = format!("{}{}", prefix, child.file_name().to_string_lossy());
+            let file_type = child.file_type()?;
+            if file_type.is_dir() {
+                pending.push((child.path(), format!("{}/", name)));
+            } else if file_type.is_file() {
+                entries.push(SnapshotEntry {
+                    path: child.path(),
+                    name,
+                    size: child.metadata()?.len(),
+                });
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+/// Writer that feeds everything written through it into a SHA-256
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Write the snapshot into `dest` as a CVBAK001 pack:
+/// `[MAGIC][u32 count]` then per file `[u32 name_len][name][u64 size][bytes]`
+/// (little-endian), closed by `[DIGEST_MARKER][sha256 of all prior bytes]`.
+/// `on_chunk` receives the byte count of each copied chunk and aborts the
+/// copy by returning an error.
+fn write_archive(
+    entries: &[SnapshotEntry],
+    dest: &Path,
+    mut on_chunk: impl FnMut(u64) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut out = HashingWriter {
+        inner: BufWriter::new(File::create(dest)?),
+        hasher: Sha256::new(),
+    };
+    out.write_all(ARCHIVE_MAGIC)?;
+    out.write_all(&(entries.len() as u32).to_le_bytes())?;
+
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    for entry in entries {
+        out.write_all(&(entry.name.len() as u32).to_le_bytes())?;
+        out.write_all(entry.name.as_bytes())?;
+        out.write_all(&entry.size.to_le_bytes())?;
+
+        // Copy exactly the snapshotted size so the header stays truthful
+        let mut input = File::open(&entry.path)?.take(entry.size);
+        let mut remaining = entry.size;
+        while remaining > 0 {
+            let n = input.read(&mut buf)?;
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("{} shrank during backup", entry.name),
+                ));
+            }
+            out.write_all(&buf[..n])?;
+            remaining -= n as u64;
+            on_chunk(n as u64)?;
+        }
+    }
+
+    let HashingWriter { mut inner, hasher } = out;
+    inner.write_all(DIGEST_MARKER)?;
+    inner.write_all(&hasher.finalize())?;
+    inner.into_inner().map_err(|e| e.into_error())?.sync_all()
+}
+
+/// `<parent>/<source name>-<task_id>.cvbak`, next to (never inside) the
+/// source; a filesystem root has no such place
+fn archive_path(source: &Path, task_id: &str) -> Result<PathBuf, String> {
+    let dir = source
+        .parent()
+        .ok_or_else(|| format!("Cannot back up a filesystem root: {}", source.display()))?;
+    let stem = source
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "vault".to_string());
+    Ok(dir.join(format!("{}-{}.cvbak", stem, task_id)))
 }
 
 /// OMEGA PROTOCOL: Hybrid Command-Init → Event-Stream
 ///
 /// Returns TaskID immediately, spawns worker thread for actual backup.
-/// Worker emits `backup_progress` events to single global channel.
+/// Worker emits `backup_progress` events to single global channel, unless
+/// the caller passes a dedicated `channel` (e.g. one per drive when backing
+/// up several in parallel). Payloads carry `task_id` and `seq` either way;
+/// events emitted before the frontend listens are replayed by
+/// `cmd_backup_subscribe`.
+/// `target_dir` is the source to back up (defaults to the app data dir);
+/// the archive is written next to it as `<name>-<task_id>.cvbak`.
 #[tauri::command]
 pub async fn cmd_backup_start(
     app: AppHandle,
-    _target_dir: Option<String>,
+    target_dir: Option<String>,
+    channel: Option<String>,
 ) -> Result<String, String> {
+    let channel = channel.unwrap_or_else(|| BACKUP_CHANNEL.to_string());
+    validate_channel(&channel)?;
+
+    let source = match target_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => app.path().app_data_dir().map_err(|e| e.to_string())?,
+    };
+    if !source.exists() {
+        return Err(format!("Backup source not found: {}", source.display()));
+    }
+
     let task_id = format!(
-        "OMEGA-{}",
+        "OMEGA-{}-{}",
         std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
-            .as_millis()
+            .as_millis(),
+        TASK_SEQ.fetch_add(1, Ordering::Relaxed)
     );
     let app_handle = app.clone();
     let tid = task_id.clone();
+    let dest = archive_path(&source, &task_id)?;
+    let cancelled = register_task(&task_id);
+    open_replay(&task_id);
 
     // Spawn worker thread (Hybrid Flow - return immediately)
     thread::spawn(move || {
-        let emit = |phase: &str, prog: f64, eta: &str, msg: &str| {
-            let payload = BackupPayload {
+        let last_progress = Cell::new(0.0);
+        let send = |payload: BackupPayload| {
+            // SINGLE GLOBAL CHANNEL by default (ADR-008 Rule #4 exception
+            // for caller-requested per-task channels)
+            let _ = app_handle.emit(&channel, record_event(payload));
+        };
+        let emit = |phase: &str, prog: f64, speed: &str, eta: &str, msg: &str| {
+            last_progress.set(prog);
+            send(BackupPayload {
                 task_id: tid.clone(),
+                seq: 0,
                 phase: phase.to_string(),
                 progress: prog,
-                speed: if prog < 100.0 {
-                    "45 MB/s".to_string()
-                } else {
-                    "0 MB/s".to_string()
-                },
+                speed: speed.to_string(),
                 eta: eta.to_string(),
                 msg: msg.to_string(),
-            };
-            // SINGLE GLOBAL CHANNEL (ADR-008 Rule #4)
-            let _ = app_handle.emit("backup_progress", payload);
+                error_code: None,
+            });
+        };
+        // Terminal failure state: keeps the last progress so the UI can show
+        // where it stopped
+        let emit_error = |code: &str, msg: &str| {
+            send(BackupPayload {
+                task_id: tid.clone(),
+                seq: 0,
+                phase: "error".to_string(),
+                progress: last_progress.get(),
+                speed: format_speed(0.0),
+                eta: "0s".to_string(),
+                msg: msg.to_string(),
+                error_code: Some(code.to_string()),
+            });
         };
 
-        // Phase 1: INIT
-        emit("init", 0.0, "CALC...", "Initializing Omega Engine...");
-        thread::sleep(Duration::from_millis(800));
-
-        // Phase 2: SNAPSHOT
-        emit(
-            "snapshot",
-            10.0,
-            "15s",
-            "Taking atomic snapshot (VACUUM INTO)...",
-        );
-        thread::sleep(Duration::from_millis(1000));
-
-        // Phase 3: ENCRYPTING (Loop with progress events)
-        for i in 11..=90 {
-            if i % 5 == 0 {
-                let remaining = 90 - i;
-                let eta = format!("{}-{}s", remaining / 10, remaining / 8);
-                emit(
-                    "encrypting",
-                    i as f64,
-                    &eta,
-                    &format!("Encrypting chunk #{}...", i),
-                );
+        let tmp = dest.with_extension("cvbak.tmp");
+        let check_cancel = || {
+            if cancelled.load(Ordering::SeqCst) {
+                Err(cancelled_error())
+            } else {
+                Ok(())
             }
-            thread::sleep(Duration::from_millis(50));
-        }
+        };
+        // Catch-all: panics are reported like I/O errors so the UI always
+        // receives a terminal phase
+        let result = panic::catch_unwind(AssertUnwindSafe(|| -> io::Result<()> {
+            // Phase 1: INIT
+            emit(
+                "init",
+                0.0,
+                &format_speed(0.0),
+                "CALC...",
+                &format!("Preparing backup of {}...", source.display()),
+            );
+
+            // Phase 2: SNAPSHOT
+            let entries = snapshot(&source)?;
+            check_cancel()?;
+            let total: u64 = entries.iter().map(|e| e.size).sum();
+            emit(
+                "snapshot",
+                0.0,
+                &format_speed(0.0),
+                "CALC...",
+                &format!("Captured {} files ({} bytes)", entries.len(), total),
+            );
+
+            // Phase 3: COPYING (progress = bytes_copied / total_bytes)
+            let mut stats = Throughput::new(total);
+            let mut last_emit = Instant::now();
+            write_archive(&entries, &tmp, |n| {
+                check_cancel()?;
+                stats.copied += n;
+                if last_emit.elapsed() >= EMIT_INTERVAL {
+                    last_emit = Instant::now();
+                    emit(
+                        "copying",
+                        stats.progress(),
+                        &stats.speed(),
+                        &stats.eta(),
+                        &format!("Copied {} of {} bytes...", stats.copied, total),
+                    );
+                }
+                Ok(())
+            })?;
+            check_cancel()?;
+
+            // Phase 4: FINALIZE (atomic rename .tmp -> .cvbak)
+            emit(
+                "finalizing",
+                stats.progress(),
+                &stats.speed(),
+                "0s",
+                "Flushing archive to disk...",
+            );
+            fs::rename(&tmp, &dest)?;
 
-        // Phase 4: FINALIZE
-        emit("finalizing", 95.0, "1-2s", "Verifying Poly1305 MAC...");
-        thread::sleep(Duration::from_millis(800));
+            // Phase 5: DONE
+            emit(
+                "done",
+                100.0,
+                &stats.speed(),
+                "0s",
+                &format!("Backup written to {}", dest.display()),
+            );
+            Ok(())
+        }));
 
-        // Phase 5: DONE
-        emit("done", 100.0, "0s", "Backup secured successfully.");
+        unregister_task(&tid);
+        let (code, msg) = match result {
+            Ok(Ok(())) => return,
+            Ok(Err(e)) => (io_error_code(&e), format!("Backup failed: {}", e)),
+            Err(panic) => (
+                "WORKER_PANIC",
+                format!("Backup worker crashed: {}", panic_message(&*panic)),
+            ),
+        };
+
+        let _ = fs::remove_file(&tmp);
+        if cancelled.load(Ordering::SeqCst) {
+            emit(
+                "cancelled",
+                0.0,
+                &format_speed(0.0),
+                "0s",
+                "Backup cancelled. Partial archive removed.",
+            );
+            return;
+        }
+        emit_error(code, &msg);
     });
 
     // Return TaskID immediately (Command Handshake)
     Ok(task_id)
 }
+
+/// Request cancellation of an in-flight backup.
+///
+/// The worker stops at its next chunk, removes the partial `.tmp` archive
+/// and emits a final `cancelled` phase on the task's progress channel.
+#[tauri::command]
+pub fn cmd_backup_cancel(task_id: String) -> Result<(), String> {
+    let tasks = BACKUP_TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    match tasks.get(&task_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+        None => Err(format!("No running backup with task_id {}", task_id)),
+    }
+}
+
+/// Replay the events a backup has emitted so far.
+///
+/// Call after attaching the progress listener: events from the replay and
+/// the live stream may overlap, so the frontend drops any whose `seq` it has
+/// already seen. Finished tasks stay available for a short grace period.
+#[tauri::command]
+pub fn cmd_backup_subscribe(task_id: String) -> Result<Vec<BackupPayload>, String> {
+    replay(&task_id).ok_or_else(|| format!("No backup events for task_id {}", task_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cvbak-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_archive_contains_every_snapshotted_byte() {
+        let root = scratch_dir("archive");
+        let source = root.join("vault");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("a.db"), vec![7u8; 3000]).unwrap();
+        fs::write(source.join("nested/b.txt"), b"hello").unwrap();
+
+        let entries = snapshot(&source).unwrap();
+        let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["a.db", "nested/b.txt"]);
+
+        let dest = archive_path(&source, "T1").unwrap();
+        assert_eq!(dest, root.join("vault-T1.cvbak"));
+        assert!(archive_path(Path::new("/"), "T1").is_err());
+
+        let mut copied = 0;
+        write_archive(&entries, &dest, |n| {
+            copied += n;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(copied, 3005);
+
+        let bytes = fs::read(&dest).unwrap();
+        assert_eq!(&bytes[..8], ARCHIVE_MAGIC);
+        assert_eq!(u32::from_le_bytes(bytes[8..12].try_into().unwrap()), 2);
+        // header + 2 * (name_len + size) + names + payload + digest footer
+        assert_eq!(bytes.len(), 12 + 2 * 12 + 4 + 12 + 3005 + 40);
+        let (body, footer) = bytes.split_at(bytes.len() - 40);
+        assert_eq!(&footer[..8], DIGEST_MARKER);
+        assert_eq!(footer[8..], Sha256::digest(body)[..]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_cancel_flips_registered_flag() {
+        assert!(cmd_backup_cancel("OMEGA-missing".into()).is_err());
+
+        let flag = register_task("OMEGA-cancel");
+        assert!(cmd_backup_cancel("OMEGA-cancel".into()).is_ok());
+        assert!(flag.load(Ordering::SeqCst));
+
+        unregister_task("OMEGA-cancel");
+        assert!(cmd_backup_cancel("OMEGA-cancel".into()).is_err());
+    }
+
+    #[test]
+    fn test_archive_aborts_when_chunk_callback_fails() {
+        let root = scratch_dir("abort");
+        fs::write(root.join("big.db"), vec![1u8; 10]).unwrap();
+
+        let entries = snapshot(&root.join("big.db")).unwrap();
+        let dest = root.join("big.cvbak.tmp");
+        let err = write_archive(&entries, &dest, |_| Err(cancelled_error())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_channel_names_are_validated() {
+        assert!(validate_channel(BACKUP_CHANNEL).is_ok());
+        assert!(validate_channel("backup_progress:drive-D/1").is_ok());
+        assert!(validate_channel("").is_err());
+        assert!(validate_channel("backup progress").is_err());
+    }
+
+    #[test]
+    fn test_failures_map_to_error_codes() {
+        let missing = snapshot(Path::new("/nonexistent/cvbak-source")).unwrap_err();
+        assert_eq!(io_error_code(&missing), "SOURCE_NOT_FOUND");
+        let eof = io::Error::new(io::ErrorKind::UnexpectedEof, "shrank");
+        assert_eq!(io_error_code(&eof), "SOURCE_CHANGED");
+
+        let panic = panic::catch_unwind(|| panic!("boom {}", 1)).unwrap_err();
+        assert_eq!(panic_message(&*panic), "boom 1");
+    }
+
+    #[test]
+    fn test_throughput_reports_progress_and_eta() {
+        let mut stats = Throughput::new(200);
+        assert_eq!(stats.progress(), 0.0);
+        stats.copied = 50;
+        assert_eq!(stats.progress(), 25.0);
+        assert!(stats.speed().ends_with(" MB/s"));
+        assert!(stats.eta().ends_with('s'));
+
+        assert_eq!(Throughput::new(0).progress(), 100.0);
+    }
+
+    #[test]
+    fn test_subscribe_replays_events_in_order() {
+        let event = |phase: &str| BackupPayload {
+            task_id: "OMEGA-replay".to_string(),
+            seq: 0,
+            phase: phase.to_string(),
+            progress: 0.0,
+            speed: format_speed(0.0),
+            eta: "0s".to_string(),
+            msg: String::new(),
+            error_code: None,
+        };
+        assert!(cmd_backup_subscribe("OMEGA-replay".into()).is_err());
+
+        open_replay("OMEGA-replay");
+        assert_eq!(
+            cmd_backup_subscribe("OMEGA-replay".into()).unwrap().len(),
+            0
+        );
+
+        assert_eq!(record_event(event("init")).seq, 0);
+        for _ in 0..REPLAY_LIMIT {
+            record_event(event("copying"));
+        }
+        let done = record_event(event("done"));
+        assert_eq!(done.seq, REPLAY_LIMIT as u64 + 1);
+
+        // Early events in order, then only the latest of the overflow
+        let replayed = cmd_backup_subscribe("OMEGA-replay".into()).unwrap();
+        assert_eq!(replayed.len(), REPLAY_LIMIT + 1);
+        assert_eq!(replayed[0].phase, "init");
+        assert_eq!(replayed.last().unwrap().phase, "done");
+        assert_eq!(replayed.last().unwrap().seq, done.seq);
+
+        BACKUP_EVENTS.lock().unwrap().remove("OMEGA-replay");
+    }
+}
@@ -2,32 +2,138 @@
 //!
 //! MDS v3.14: Rust (Muscle) controls Python (Brain) via PyO3.
 
+use crate::commands::restore::{self, RestoreResult};
 use crate::python_bridge;
+use serde::Serialize;
 use serde_json::Value;
-use tauri::command;
+use std::time::Instant;
+use tauri::{command, AppHandle};
+
+#[derive(Serialize)]
+pub struct HealthResp {
+    /// The embedded interpreter answered
+    pub ok: bool,
+    pub python_version: String,
+    /// The Python dispatcher is initialized
+    pub backend_loaded: bool,
+}
+
+#[derive(Serialize)]
+pub struct InitResp {
+    /// False if the backend was already loaded and nothing was done
+    pub initialized: bool,
+    pub elapsed_ms: u64,
+    pub python_version: String,
+}
+
+#[derive(Serialize)]
+pub struct QueueStatsResp {
+    pub waiting: u64,
+    pub active: Option<String>,
+    pub served: u64,
+    pub max_waiting: u64,
+    pub max_wait_ms: u64,
+}
+
+/// Tauri debug command reporting the Python dispatch queue.
+///
+/// Dispatches run one at a time in arrival order (`system.*` commands
+/// ahead of the rest); this shows how many are waiting behind the running
+/// one when the UI seems stuck.
+#[command]
+pub fn cmd_dispatch_stats() -> QueueStatsResp {
+    let stats = python_bridge::queue_stats();
+
+    QueueStatsResp {
+        waiting: stats.waiting,
+        active: stats.active,
+        served: stats.served,
+        max_waiting: stats.max_waiting,
+        max_wait_ms: stats.max_wait.as_millis() as u64,
+    }
+}
+
+/// Tauri command to load the Python backend eagerly.
+///
+/// The splash screen calls this to pay the import cost up front behind a
+/// loading indicator instead of on the first dispatch. Calling it again
+/// after success is a no-op; import errors come back as `Err`.
+#[command]
+pub async fn cmd_init_backend() -> Result<InitResp, String> {
+    let started = Instant::now();
+    let initialized =
+        python_bridge::init_python_backend().map_err(|e| format!("Init Failed: {}", e))?;
+
+    Ok(InitResp {
+        initialized,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        python_version: python_bridge::backend_health().python_version,
+    })
+}
 
 /// Tauri command to dispatch requests to Python Core.
 ///
 /// # Arguments
 /// * `cmd` - Command string (e.g., "backup.start")
 /// * `payload` - JSON payload for the command
+/// * `timeout_ms` - Optional limit on how long to wait for Python
 ///
 /// # Returns
 /// * JSON response from Python Dispatcher
 #[command]
-pub async fn cmd_dispatch(cmd: String, payload: Value) -> Result<Value, String> {
+pub async fn cmd_dispatch(
+    cmd: String,
+    payload: Value,
+    timeout_ms: Option<u64>,
+) -> Result<Value, String> {
     // Delegate to Python bridge
-    python_bridge::dispatch_to_python(&cmd, payload)
+    python_bridge::dispatch_to_python_timeout(&cmd, payload, timeout_ms)
 }
 
-/// Tauri command to restore backup from .cvbak file.
+/// Tauri command to cancel a running Python dispatch.
 ///
-/// This is the E2E entry point from DropZone drag-drop.
+/// Only dispatches started under a cancellation token (e.g. restores, by
+/// their task_id) can be cancelled, and only cooperatively: a queued one is
+/// skipped, a running handler stops when it next checks its `cancelled()`
+/// callable. Handlers that never check run to completion.
 #[command]
-pub async fn cmd_restore_from_file(file_path: String) -> Result<Value, String> {
-    let payload = serde_json::json!({
-        "file_path": file_path
-    });
+pub fn cmd_dispatch_cancel(task_id: String) -> Result<(), String> {
+    if python_bridge::cancel_dispatch(&task_id) {
+        Ok(())
+    } else {
+        Err(format!("No running dispatch with task_id {}", task_id))
+    }
+}
 
-    python_bridge::dispatch_to_python("restore.start", payload)
+/// Tauri command to check the Python bridge without side effects.
+///
+/// The frontend polls this on startup for its connection indicator. `ok`
+/// means the interpreter ran a probe and the Python sources were found;
+/// when `backend_loaded` is false the next dispatch (re)initializes the
+/// backend.
+#[command]
+pub fn cmd_python_health() -> HealthResp {
+    let health = python_bridge::backend_health();
+
+    HealthResp {
+        ok: health.ok,
+        python_version: health.python_version,
+        backend_loaded: health.backend_loaded,
+    }
+}
+
+/// Tauri command to restore backup from .cvbak file.
+///
+/// This is the E2E entry point from DropZone drag-drop. Returns a `started`
+/// result with the task_id; progress and the final result arrive as
+/// `restore_progress` events. `dry_run` previews the restore without writing;
+/// `target_dir` overrides where it restores to.
+#[command]
+pub async fn cmd_restore_from_file(
+    app: AppHandle,
+    file_path: String,
+    dry_run: Option<bool>,
+    target_dir: Option<String>,
+) -> Result<RestoreResult, String> {
+    restore::start_restore(app, file_path, dry_run.unwrap_or(false), target_dir)
 }
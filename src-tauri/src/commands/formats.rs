@@ -0,0 +1,56 @@
+//! Formats Command - What the app can ingest, for the frontend.
+//!
+//! The drop zone, file-picker filters and upload validation read this
+//! instead of hardcoding types, so a new format is a Rust-only change.
+
+use crate::drop_filter;
+use serde::Serialize;
+use tauri::command;
+
+/// Capabilities of the extractor/backup layer, for feature toggles
+pub const FEATURES: &[&str] = &[
+    "docx_text",
+    "docx_tables",
+    "docx_encrypted",
+    "pdf_text",
+    "backup",
+    "restore",
+    "recovery_phrase",
+];
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SupportedFormats {
+    /// Lowercase extensions without the dot
+    pub extensions: Vec<String>,
+    pub max_file_size_bytes: u64,
+    pub features: Vec<String>,
+}
+
+/// Tauri command listing accepted file types and limits.
+#[command]
+pub fn cmd_supported_formats() -> SupportedFormats {
+    SupportedFormats {
+        extensions: drop_filter::ALLOWED_EXTENSIONS
+            .iter()
+            .map(|ext| ext.to_string())
+            .collect(),
+        max_file_size_bytes: drop_filter::max_file_size(),
+        features: FEATURES.iter().map(|f| f.to_string()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_match_drop_filter() {
+        let formats = cmd_supported_formats();
+        assert!(formats.extensions.iter().any(|ext| ext == "docx"));
+        assert_eq!(
+            formats.extensions.len(),
+            drop_filter::ALLOWED_EXTENSIONS.len()
+        );
+        assert_eq!(formats.max_file_size_bytes, drop_filter::max_file_size());
+    }
+}
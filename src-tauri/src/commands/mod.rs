@@ -1,4 +0,0 @@
-pub mod backup;
-pub mod dispatch;
-pub mod recovery;
-pub mod restore;
@@ -1,15 +1,63 @@
+use crate::commands::recovery_export::SensitiveRecoveryData;
+use once_cell::sync::Lazy;
 use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long an exported recovery image can be fetched before it is wiped
+pub const RECOVERY_TTL: Duration = Duration::from_secs(60);
 
 #[derive(Serialize)]
 pub struct ExportResp {
-    pub data_uri: String,
+    /// Per-export handle for `cmd_fetch_recovery_image`
+    pub token: String,
     pub ttl_seconds: u64,
 }
 
+struct PendingExport {
+    data_uri: SensitiveRecoveryData,
+    expires_at: Instant,
+}
+
+/// Exported images awaiting fetch, keyed by token. Entries are dropped (and
+/// thereby zeroized) when fetched or by their TTL timer.
+static RECOVERY_EXPORTS: Lazy<Mutex<HashMap<String, PendingExport>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn exports() -> std::sync::MutexGuard<'static, HashMap<String, PendingExport>> {
+    RECOVERY_EXPORTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Store `data_uri` under a fresh random token and schedule its wipe
+fn register_export(data_uri: SensitiveRecoveryData, ttl: Duration) -> String {
+    let token: String = rand::random::<[u8; 16]>()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    exports().insert(
+        token.clone(),
+        PendingExport {
+            data_uri,
+            expires_at: Instant::now() + ttl,
+        },
+    );
+
+    let expired = token.clone();
+    thread::spawn(move || {
+        thread::sleep(ttl);
+        exports().remove(&expired);
+    });
+    token
+}
+
 /// BLIND PROTOCOL: Recovery phrase export
 ///
 /// Frontend NEVER receives plaintext mnemonic.
-/// Returns SVG as data:image URI with TTL for auto-wipe.
+/// Renders the SVG in Rust and returns a token; the image can be fetched
+/// once with `cmd_fetch_recovery_image` before the TTL elapses, then it is
+/// wiped.
 #[tauri::command]
 pub fn cmd_export_recovery_svg(auth: String) -> Result<ExportResp, String> {
     // Security validation
@@ -26,9 +74,46 @@ pub fn cmd_export_recovery_svg(auth: String) -> Result<ExportResp, String> {
     let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"300\" height=\"150\"><rect width=\"100%\" height=\"100%\" fill=\"#1a1a2e\"/><text x=\"50%\" y=\"50%\" text-anchor=\"middle\" fill=\"#4ade80\" font-size=\"14\">Recovery Key Placeholder</text></svg>";
 
     let b64 = general_purpose::STANDARD.encode(svg.as_bytes());
+    let data_uri = SensitiveRecoveryData::new(format!("data:image/svg+xml;base64,{}", b64));
 
     Ok(ExportResp {
-        data_uri: format!("data:image/svg+xml;base64,{}", b64),
-        ttl_seconds: 60,
+        token: register_export(data_uri, RECOVERY_TTL),
+        ttl_seconds: RECOVERY_TTL.as_secs(),
     })
 }
+
+/// Fetch an exported recovery image by token while its TTL is live. The
+/// token is single-use: the stored image is wiped as it is returned.
+#[tauri::command]
+pub fn cmd_fetch_recovery_image(token: String) -> Result<String, String> {
+    match exports().remove(&token) {
+        Some(pending) if Instant::now() < pending.expires_at => {
+            Ok(pending.data_uri.expose().to_string())
+        }
+        Some(_) => Err("Recovery image expired".into()),
+        None => Err("Recovery image expired or unknown".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_is_fetchable_once_until_ttl_wipes_it() {
+        let resp = cmd_export_recovery_svg("passkey".into()).unwrap();
+        assert_eq!(resp.ttl_seconds, 60);
+        let uri = cmd_fetch_recovery_image(resp.token.clone()).unwrap();
+        assert!(uri.starts_with("data:image/svg+xml;base64,"));
+        assert!(cmd_fetch_recovery_image(resp.token.clone()).is_err());
+        assert!(!exports().contains_key(&resp.token));
+
+        let token = register_export(SensitiveRecoveryData::new("secret".into()), Duration::ZERO);
+        assert!(cmd_fetch_recovery_image(token.clone()).is_err());
+        thread::sleep(Duration::from_millis(50));
+        assert!(!exports().contains_key(&token));
+
+        assert!(cmd_fetch_recovery_image("bogus".into()).is_err());
+        assert!(cmd_export_recovery_svg(String::new()).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+use base64::{engine::general_purpose, Engine as _};
+use bip39::{Language, Mnemonic, MnemonicType};
+use image::Luma;
+use qrcode::{Color, QrCode};
+use std::io::Cursor;
+use zeroize::Zeroize;
+
+/// Secret string (mnemonic or rendered image) wiped from memory on drop.
+///
+/// SPEC-007 Iron Rule #3: every value derived from the seed stays in this
+/// wrapper until it leaves Rust.
+pub struct SensitiveRecoveryData(String);
+
+impl SensitiveRecoveryData {
+    pub fn new(data: String) -> Self {
+        Self(data)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Drop for SensitiveRecoveryData {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// 128-bit entropy; 24 words gives 256-bit for high-security vaults
+pub const DEFAULT_WORD_COUNT: u8 = 12;
+
+/// Map a BIP39 language code ("en", "ja", "es", "fr", "it", "ko", "zh-hans",
+/// "zh-hant") to its wordlist; `None` means English.
+fn parse_language(code: Option<&str>) -> Result<Language, String> {
+    match code {
+        None => Ok(Language::English),
+        Some(code) => Language::from_language_code(code)
+            .ok_or_else(|| format!("Unsupported mnemonic language: {}", code)),
+    }
+}
+
+/// BIP39 joins Japanese words with an ideographic space
+fn word_separator(language: Language) -> &'static str {
+    match language {
+        Language::Japanese => "\u{3000}",
+        _ => " ",
+    }
+}
+
+/// Generate a fresh BIP39 recovery phrase in `language` (default English).
+///
+/// `word_count` must be 12, 15, 18, 21 or 24.
+pub fn generate_recovery_phrase(
+    word_count: u8,
+    language: Option<&str>,
+) -> Result<SensitiveRecoveryData, String> {
+    let language = parse_language(language)?;
+    let mnemonic_type = match word_count {
+        12 => MnemonicType::Words12,
+        15 => MnemonicType::Words15,
+        18 => MnemonicType::Words18,
+        21 => MnemonicType::Words21,
+        24 => MnemonicType::Words24,
+        _ => {
+            return Err(format!(
+                "Unsupported word count {}: expected 12, 15, 18, 21 or 24",
+                word_count
+            ))
+        }
+    };
+    let mnemonic = Mnemonic::new(mnemonic_type, language);
+    Ok(SensitiveRecoveryData::new(
+        mnemonic
+            .phrase()
+            .split(' ')
+            .collect::<Vec<_>>()
+            .join(word_separator(language)),
+    ))
+}
+
+/// Check a user-typed recovery phrase against the BIP39 wordlist of
+/// `language` (default English) and its checksum, so restore can flag typos
+/// before attempting decryption.
+///
+/// Case and extra whitespace (including ideographic spaces) are ignored.
+/// The input is zeroized afterward.
+#[tauri::command]
+pub fn verify_recovery_phrase(
+    mut phrase: String,
+    language: Option<String>,
+) -> Result<bool, String> {
+    let language = match parse_language(language.as_deref()) {
+        Ok(language) => language,
+        Err(e) => {
+            phrase.zeroize();
+            return Err(e);
+        }
+    };
+    if phrase.trim().is_empty() {
+        phrase.zeroize();
+        return Err("Recovery phrase required".into());
+    }
+
+    // Built in one buffer sized so it never reallocates (lowercasing at
+    // most grows text by half), leaving no unzeroized copies behind
+    let mut normalized = String::with_capacity(phrase.len() * 2);
+    for word in phrase.split_whitespace() {
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.extend(word.chars().flat_map(char::to_lowercase));
+    }
+    let normalized = SensitiveRecoveryData::new(normalized);
+    phrase.zeroize();
+
+    // tiny-bip39 expects single ASCII spaces for every language
+    Ok(Mnemonic::validate(normalized.expose(), language).is_ok())
+}
+
+/// Output encoding of the recovery QR code
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecoveryImageFormat {
+    /// 200x200 raster (legacy)
+    Png,
+    /// Vector paths; stays crisp when the dialog scales it on high-DPI screens
+    Svg,
+}
+
+/// Modules of light border around the code, as required by the QR spec
+const QUIET_ZONE: usize = 4;
+
+fn render_png(code: &QrCode) -> Result<Vec<u8>, String> {
+    // qrcode 0.14 renders into image 0.25
+    let image = code.render::<Luma<u8>>().min_dimensions(200, 200).build();
+
+    let mut cursor = Cursor::new(Vec::new());
+    let written = image.write_to(&mut cursor, image::ImageFormat::Png);
+    let mut png = cursor.into_inner();
+    if let Err(e) = written {
+        png.zeroize();
+        return Err(e.to_string());
+    }
+    Ok(png)
+}
+
+/// One `<rect>` per dark module on a viewBox of module units
+fn render_svg(code: &QrCode) -> Vec<u8> {
+    let width = code.width();
+    let size = width + 2 * QUIET_ZONE;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {size} {size}\" \
+         shape-rendering=\"crispEdges\"><rect width=\"{size}\" height=\"{size}\" fill=\"#fff\"/>"
+    );
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color == Color::Dark {
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\"/>",
+                i % width + QUIET_ZONE,
+                i / width + QUIET_ZONE
+            ));
+        }
+    }
+    svg.push_str("</svg>");
+    svg.into_bytes()
+}
+
+/// Render `phrase` as a QR code data URI (`image/png` or `image/svg+xml`).
+///
+/// Language-agnostic: the QR encodes the phrase's UTF-8 bytes as typed.
+///
+/// The QR version is chosen from the payload length, so 24-word phrases get
+/// a denser code automatically.
+///
+/// `phrase` is zeroized on every path; the returned URI is wrapped so it is
+/// wiped when dropped.
+pub fn export_recovery_image(
+    mut phrase: String,
+    format: RecoveryImageFormat,
+) -> Result<SensitiveRecoveryData, String> {
+    // 1. Generate Code
+    let code = match QrCode::new(phrase.as_bytes()) {
+        Ok(code) => code,
+        Err(e) => {
+            phrase.zeroize();
+            return Err(e.to_string());
+        }
+    };
+    phrase.zeroize();
+
+    // 2. Render (the QR modules themselves encode the secret)
+    let (mime, mut rendered) = match format {
+        RecoveryImageFormat::Png => ("image/png", render_png(&code)?),
+        RecoveryImageFormat::Svg => ("image/svg+xml", render_svg(&code)),
+    };
+
+    // 3. Base64 Encode
+    let mut base64_string = general_purpose::STANDARD.encode(&rendered);
+    rendered.zeroize();
+    let data_uri = format!("data:{};base64,{}", mime, base64_string);
+    base64_string.zeroize();
+
+    Ok(SensitiveRecoveryData::new(data_uri))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_render_and_verify_data_uri() {
+        let phrase = generate_recovery_phrase(DEFAULT_WORD_COUNT, None).unwrap();
+        assert_eq!(phrase.expose().split_whitespace().count(), 12);
+        assert!(Mnemonic::validate(phrase.expose(), Language::English).is_ok());
+
+        let uri =
+            export_recovery_image(phrase.expose().to_string(), RecoveryImageFormat::Png).unwrap();
+        let b64 = uri
+            .expose()
+            .strip_prefix("data:image/png;base64,")
+            .expect("PNG data URI prefix");
+        let png = general_purpose::STANDARD.decode(b64).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
+
+    #[test]
+    fn test_24_word_phrase_round_trips() {
+        let phrase = generate_recovery_phrase(24, None).unwrap();
+        assert_eq!(phrase.expose().split_whitespace().count(), 24);
+
+        let restored = Mnemonic::from_phrase(phrase.expose(), Language::English).unwrap();
+        assert_eq!(restored.entropy().len(), 32);
+        assert!(
+            export_recovery_image(phrase.expose().to_string(), RecoveryImageFormat::Svg).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_invalid_word_count_is_rejected() {
+        for count in [0, 11, 13, 25] {
+            assert!(generate_recovery_phrase(count, None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_verify_accepts_valid_and_rejects_typos() {
+        // BIP39 test vector: all-zero entropy
+        let valid = format!("{} about", "abandon ".repeat(11).trim_end());
+        assert_eq!(verify_recovery_phrase(valid.clone(), None), Ok(true));
+        assert_eq!(
+            verify_recovery_phrase(format!("  {}  ", valid.to_uppercase()), None),
+            Ok(true)
+        );
+
+        // Same words, wrong checksum word
+        let bad_checksum = "abandon ".repeat(12).trim_end().to_string();
+        assert_eq!(verify_recovery_phrase(bad_checksum, None), Ok(false));
+        assert_eq!(
+            verify_recovery_phrase(valid.replace("about", "abuot"), None),
+            Ok(false)
+        );
+        assert!(verify_recovery_phrase("   ".into(), None).is_err());
+    }
+
+    #[test]
+    fn test_svg_export_draws_one_rect_per_dark_module() {
+        let phrase = generate_recovery_phrase(DEFAULT_WORD_COUNT, None).unwrap();
+        let code = QrCode::new(phrase.expose().as_bytes()).unwrap();
+        let dark = code
+            .to_colors()
+            .iter()
+            .filter(|c| **c == Color::Dark)
+            .count();
+
+        let uri =
+            export_recovery_image(phrase.expose().to_string(), RecoveryImageFormat::Svg).unwrap();
+        let b64 = uri
+            .expose()
+            .strip_prefix("data:image/svg+xml;base64,")
+            .expect("SVG data URI prefix");
+        let svg = String::from_utf8(general_purpose::STANDARD.decode(b64).unwrap()).unwrap();
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>"));
+        // background + dark modules
+        assert_eq!(svg.matches("<rect").count(), 1 + dark);
+    }
+
+    #[test]
+    fn test_japanese_phrase_round_trips_with_ideographic_spaces() {
+        let phrase = generate_recovery_phrase(DEFAULT_WORD_COUNT, Some("ja")).unwrap();
+        assert_eq!(phrase.expose().split('\u{3000}').count(), 12);
+        assert!(!phrase.expose().contains(' '));
+
+        let ja = Some("ja".to_string());
+        assert_eq!(
+            verify_recovery_phrase(phrase.expose().to_string(), ja.clone()),
+            Ok(true)
+        );
+        // Wrong wordlist
+        assert_eq!(
+            verify_recovery_phrase(phrase.expose().to_string(), None),
+            Ok(false)
+        );
+
+        assert!(
+            export_recovery_image(phrase.expose().to_string(), RecoveryImageFormat::Svg).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_language_codes() {
+        assert!(generate_recovery_phrase(12, Some("es")).is_ok());
+        assert!(generate_recovery_phrase(12, Some("zh-hans")).is_ok());
+        assert!(generate_recovery_phrase(12, Some("xx")).is_err());
+        assert!(verify_recovery_phrase("abandon".into(), Some("xx".into())).is_err());
+    }
+}
@@ -1,50 +1,459 @@
 //! Restore Command - Wires Frontend to Python via PyO3 Bridge
 //!
 //! Phase 3 E2E Integration: DropZone/FilePicker → Rust → Python
+//!
+//! Same hybrid flow as backup: the command returns a task_id immediately and
+//! a worker streams `restore_progress` events, forwarding the progress the
+//! Python handler reports through the bridge callback.
 
+use crate::commands::backup::{ARCHIVE_MAGIC, DIGEST_FOOTER_LEN, DIGEST_MARKER};
 use crate::python_bridge;
-use serde_json::json;
-use tauri::command;
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use tauri::{command, AppHandle, Emitter};
 
-/// Restore backup from .cvbak file
+/// Progress channel for all restores; payloads carry `task_id`
+pub const RESTORE_CHANNEL: &str = "restore_progress";
+
+/// Encrypted backup stream written by the Python backup service
+/// (DATA_DICTIONARY §4)
+pub const ENCRYPTED_MAGIC: &[u8; 8] = b"CVBAK002";
+
+/// Disambiguates task ids of restores started within the same millisecond
+static TASK_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Typed reply of the restore commands and of a finished restore
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RestoreResult {
+    /// started | success | cancelled | error
+    pub status: String,
+    pub restored_items: u32,
+    pub message: String,
+    pub task_id: Option<String>,
+    /// Set when nothing was written and `items`/`conflicts` are a preview
+    pub dry_run: bool,
+    /// Items the restore would write (dry runs only)
+    pub items: Vec<String>,
+    /// Items that already exist at the restore target (dry runs only)
+    pub conflicts: Vec<String>,
+}
+
+impl RestoreResult {
+    /// Handshake reply: the worker has been spawned for `task_id`
+    fn started(task_id: String, dry_run: bool) -> Self {
+        Self {
+            status: "started".to_string(),
+            restored_items: 0,
+            message: "Restore started".to_string(),
+            task_id: Some(task_id),
+            dry_run,
+            items: Vec::new(),
+            conflicts: Vec::new(),
+        }
+    }
+
+    /// Map the Python dispatcher's JSON reply; missing keys fall back to
+    /// neutral values. `task_id` is ours, not Python's, so the frontend can
+    /// keep correlating by the id it got from the handshake.
+    fn from_python(task_id: &str, reply: &Value) -> Self {
+        let names = |key: &str| -> Vec<String> {
+            reply[key]
+                .as_array()
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Self {
+            status: reply["status"].as_str().unwrap_or("error").to_string(),
+            restored_items: reply["restored_items"]
+                .as_u64()
+                .map_or(0, |n| n.min(u32::MAX as u64) as u32),
+            message: reply["message"].as_str().unwrap_or_default().to_string(),
+            task_id: Some(task_id.to_string()),
+            dry_run: reply["dry_run"].as_bool().unwrap_or(false),
+            items: names("items"),
+            conflicts: names("conflicts"),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RestorePayload {
+    pub task_id: String,
+    /// init | validating | <phase reported by Python> | done | cancelled | error
+    pub phase: String,
+    pub progress: f64,
+    pub msg: String,
+    /// Python's final reply, set only when `phase == "done"`
+    pub result: Option<RestoreResult>,
+    /// Hex SHA-256 computed during validation, set only when `phase == "done"`
+    pub sha256: Option<String>,
+}
+
+/// Turn a Python progress report (`{"phase", "percent", "message"}`) into
+/// an event payload; missing keys fall back to neutral values
+fn progress_payload(task_id: &str, update: &Value) -> RestorePayload {
+    RestorePayload {
+        task_id: task_id.to_string(),
+        phase: update["phase"].as_str().unwrap_or("restoring").to_string(),
+        progress: update["percent"].as_f64().unwrap_or(0.0).clamp(0.0, 100.0),
+        msg: update["message"].as_str().unwrap_or_default().to_string(),
+        result: None,
+        sha256: None,
+    }
+}
+
+/// Hash `file` from its current position up to `len` bytes
+fn sha256_of(file: &mut File, len: u64) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut file.take(len), &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn read_err(e: io::Error) -> String {
+    format!("Cannot read backup file: {}", e)
+}
+
+/// Open `path` and read its header, positioned just after it
+fn read_header(path: &str) -> Result<(File, Vec<u8>), String> {
+    let mut file = File::open(path).map_err(read_err)?;
+    let mut header = Vec::with_capacity(ARCHIVE_MAGIC.len());
+    (&mut file)
+        .take(ARCHIVE_MAGIC.len() as u64)
+        .read_to_end(&mut header)
+        .map_err(read_err)?;
+    Ok((file, header))
+}
+
+/// Check that `path` is a readable, non-empty file starting with a known
+/// `.cvbak` header, so renamed files fail fast instead of deep in Python.
+/// Only reads the header; it runs before the handshake.
+fn validate_cvbak(path: &str) -> Result<(), String> {
+    let (_, header) = read_header(path)?;
+
+    if header.is_empty() {
+        return Err("Backup file is empty".into());
+    }
+    if header != ARCHIVE_MAGIC && header != ENCRYPTED_MAGIC {
+        return Err("Not a valid .cvbak file".into());
+    }
+    Ok(())
+}
+
+/// Hash the archive at `path` on the worker thread.
 ///
-/// This is the E2E connection point:
-/// Frontend (DropZone/FilePicker) → This Command → PyO3 Bridge → Python Dispatcher
-#[command]
-pub fn cmd_restore_backup(path: String) -> Result<String, String> {
+/// Returns the hex SHA-256 of the archive contents. CVBAK001 packs ending
+/// in a digest footer must match it; the footer itself is not hashed.
+fn verify_cvbak(path: &str) -> Result<String, String> {
+    let (mut file, header) = read_header(path)?;
+    let len = file.metadata().map_err(read_err)?.len();
+    let mut stored = None;
+    if header == ARCHIVE_MAGIC && len >= header.len() as u64 + DIGEST_FOOTER_LEN {
+        let mut footer = [0u8; DIGEST_FOOTER_LEN as usize];
+        file.seek(SeekFrom::End(-(DIGEST_FOOTER_LEN as i64)))
+            .and_then(|_| file.read_exact(&mut footer))
+            .map_err(read_err)?;
+        if footer.starts_with(DIGEST_MARKER) {
+            stored = Some(footer[DIGEST_MARKER.len()..].to_vec());
+        }
+    }
+
+    let covered = if stored.is_some() {
+        len - DIGEST_FOOTER_LEN
+    } else {
+        len
+    };
+    file.rewind().map_err(read_err)?;
+    let digest = sha256_of(&mut file, covered).map_err(read_err)?;
+    if stored.is_some_and(|s| s != digest) {
+        return Err("Integrity check failed".into());
+    }
+    Ok(to_hex(&digest))
+}
+
+/// Check the header of `file_path`, then verify and restore it on a
+/// worker thread.
+///
+/// Returns a `started` result carrying the task_id; the worker emits
+/// `restore_progress` events (`init`, `validating`, then Python's phases)
+/// ending in a `done`, `cancelled` or `error` phase. `cmd_dispatch_cancel` with the task_id stops the restore at the
+/// Python handler's next cancellation check. With `dry_run`, Python only
+/// lists the items and conflicts in the `done` result; `target_dir`, when
+/// given, is where Python restores to and checks for conflicts.
+pub fn start_restore(
+    app: AppHandle,
+    file_path: String,
+    dry_run: bool,
+    target_dir: Option<String>,
+) -> Result<RestoreResult, String> {
     // Validate file extension
-    if !path.to_lowercase().ends_with(".cvbak") {
+    if !file_path.to_lowercase().ends_with(".cvbak") {
         return Err("Invalid file format. Expected .cvbak".into());
     }
+    validate_cvbak(&file_path)?;
 
-    println!("🔌 [RUST] cmd_restore_backup called with: {}", path);
+    let task_id = format!(
+        "RESTORE-{}-{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis(),
+        TASK_SEQ.fetch_add(1, Ordering::Relaxed)
+    );
+    let tid = task_id.clone();
+    // Registered before the handshake so a cancel can never miss the task
+    let token = python_bridge::CancelToken::register(&task_id);
 
-    // Create payload for Python Dispatcher
-    let payload = json!({
-        "path": path
-    });
+    thread::spawn(move || {
+        let emit = |payload: RestorePayload| {
+            let _ = app.emit(RESTORE_CHANNEL, payload);
+        };
+        let status = |phase: &str, msg: String| {
+            emit(RestorePayload {
+                task_id: tid.clone(),
+                phase: phase.to_string(),
+                progress: 0.0,
+                msg,
+                result: None,
+                sha256: None,
+            })
+        };
 
-    // Call Python via PyO3 Bridge
-    match python_bridge::dispatch_to_python("restore.start", payload) {
-        Ok(result) => {
-            println!("✅ [RUST] Python returned: {:?}", result);
-
-            // Check Python response status
-            if result["status"] == "success" {
-                Ok(format!(
-                    "Restore initiated: {}",
-                    result["message"].as_str().unwrap_or("OK")
-                ))
-            } else {
-                Err(format!(
-                    "Python error: {}",
-                    result["message"].as_str().unwrap_or("Unknown error")
-                ))
+        status("init", format!("Opening {}...", file_path));
+
+        // Hashing a large archive takes a while, so it runs here rather
+        // than before the handshake
+        status("validating", "Verifying backup integrity...".to_string());
+        let sha256 = match verify_cvbak(&file_path) {
+            Ok(digest) => digest,
+            Err(e) => {
+                status("error", e);
+                return;
             }
+        };
+        log::info!("🔐 [RUST] {} sha256={}", file_path, sha256);
+
+        let finish = |phase: &str, progress: f64, msg: String, result: Option<RestoreResult>| {
+            emit(RestorePayload {
+                task_id: tid.clone(),
+                phase: phase.to_string(),
+                progress,
+                msg,
+                result,
+                sha256: (phase == "done").then(|| sha256.clone()),
+            })
+        };
+
+        // Python reports progress through the bridge callback
+        let progress_app = app.clone();
+        let progress_tid = tid.clone();
+        let on_progress: python_bridge::ProgressFn = Arc::new(move |update| {
+            let _ = progress_app.emit(RESTORE_CHANNEL, progress_payload(&progress_tid, &update));
+        });
+
+        let mut payload = json!({
+            "file_path": file_path,
+            "dry_run": dry_run
+        });
+        if let Some(target_dir) = target_dir {
+            payload["target_dir"] = json!(target_dir);
         }
-        Err(e) => {
-            println!("❌ [RUST] Python bridge error: {}", e);
-            Err(format!("Bridge error: {}", e))
+        let reply = python_bridge::dispatch_to_python_cancellable(
+            "restore.start",
+            payload,
+            &token,
+            Some(on_progress),
+        );
+        match reply {
+            Ok(reply) if reply["status"] == "success" => {
+                let result = RestoreResult::from_python(&tid, &reply);
+                let msg = if result.message.is_empty() {
+                    "OK".to_string()
+                } else {
+                    result.message.clone()
+                };
+                finish("done", 100.0, msg, Some(result));
+            }
+            _ if token.is_cancelled() => {
+                finish("cancelled", 0.0, "Restore cancelled".to_string(), None);
+            }
+            Ok(result) => {
+                let msg = result["message"].as_str().unwrap_or("Unknown error");
+                finish("error", 0.0, format!("Python error: {}", msg), None);
+            }
+            Err(e) => {
+                log::error!("❌ [RUST] Python bridge error: {}", e);
+                finish("error", 0.0, format!("Bridge error: {}", e), None);
+            }
         }
+    });
+
+    Ok(RestoreResult::started(task_id, dry_run))
+}
+
+/// Restore backup from .cvbak file
+///
+/// This is the E2E connection point:
+/// Frontend (DropZone/FilePicker) → This Command → PyO3 Bridge → Python Dispatcher
+#[command]
+pub fn cmd_restore_backup(
+    app: AppHandle,
+    path: String,
+    dry_run: Option<bool>,
+    target_dir: Option<String>,
+) -> Result<RestoreResult, String> {
+    log::info!("🔌 [RUST] cmd_restore_backup called with: {}", path);
+    start_restore(app, path, dry_run.unwrap_or(false), target_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cvbak_header_is_checked_before_dispatch() {
+        let dir = std::env::temp_dir().join(format!("cvbak-magic-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, bytes: &[u8]| {
+            let path = dir.join(name);
+            std::fs::write(&path, bytes).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+
+        assert!(validate_cvbak(&write("plain.cvbak", b"CVBAK001\0\0\0\0")).is_ok());
+        assert!(validate_cvbak(&write("sealed.cvbak", b"CVBAK002salt...")).is_ok());
+        assert_eq!(
+            validate_cvbak(&write("renamed.cvbak", b"PK\x03\x04 not a backup")),
+            Err("Not a valid .cvbak file".to_string())
+        );
+        assert_eq!(
+            validate_cvbak(&write("short.cvbak", b"CVBAK")),
+            Err("Not a valid .cvbak file".to_string())
+        );
+        assert_eq!(
+            validate_cvbak(&write("empty.cvbak", b"")),
+            Err("Backup file is empty".to_string())
+        );
+        assert!(validate_cvbak(&dir.join("missing.cvbak").to_string_lossy()).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_digest_footer_is_verified() {
+        let dir = std::env::temp_dir().join(format!("cvbak-digest-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, bytes: &[u8]| {
+            let path = dir.join(name);
+            std::fs::write(&path, bytes).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+
+        let body = b"CVBAK001\x01\0\0\0payload".to_vec();
+        let digest = Sha256::digest(&body);
+        let mut sealed = body.clone();
+        sealed.extend_from_slice(DIGEST_MARKER);
+        sealed.extend_from_slice(&digest);
+
+        // The reported hash covers the archive contents, not the footer
+        assert_eq!(
+            verify_cvbak(&write("sealed.cvbak", &sealed)),
+            Ok(to_hex(&digest))
+        );
+        // Packs without a footer still validate and report their own hash
+        assert_eq!(
+            verify_cvbak(&write("legacy.cvbak", &body)),
+            Ok(to_hex(&digest))
+        );
+
+        let mut tampered = sealed.clone();
+        tampered[12] ^= 0xff;
+        // The header check before the handshake does not hash
+        assert_eq!(validate_cvbak(&write("unchecked.cvbak", &tampered)), Ok(()));
+        assert_eq!(
+            verify_cvbak(&write("tampered.cvbak", &tampered)),
+            Err("Integrity check failed".to_string())
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_restore_result_from_python_reply() {
+        let result = RestoreResult::from_python(
+            "RESTORE-1-0",
+            &json!({
+                "status": "success",
+                "task_id": "restore_001",
+                "message": "Restored",
+                "restored_items": 12,
+                "file_path": "/tmp/a.cvbak"
+            }),
+        );
+        assert_eq!(
+            result,
+            RestoreResult {
+                status: "success".to_string(),
+                restored_items: 12,
+                message: "Restored".to_string(),
+                task_id: Some("RESTORE-1-0".to_string()),
+                dry_run: false,
+                items: Vec::new(),
+                conflicts: Vec::new(),
+            }
+        );
+
+        let preview = RestoreResult::from_python(
+            "RESTORE-1-0",
+            &json!({
+                "status": "success",
+                "dry_run": true,
+                "items": ["notes.db", "config.json"],
+                "conflicts": ["config.json"],
+                "restored_items": 0
+            }),
+        );
+        assert!(preview.dry_run);
+        assert_eq!(preview.items, vec!["notes.db", "config.json"]);
+        assert_eq!(preview.conflicts, vec!["config.json"]);
+
+        let sparse = RestoreResult::from_python("RESTORE-1-0", &json!({"restored_items": -3}));
+        assert_eq!(sparse.status, "error");
+        assert_eq!(sparse.restored_items, 0);
+        assert_eq!(sparse.message, "");
+
+        let started =
+            serde_json::to_value(RestoreResult::started("RESTORE-2-1".into(), true)).unwrap();
+        assert_eq!(started["status"], "started");
+        assert_eq!(started["dry_run"], true);
+        assert_eq!(started["task_id"], "RESTORE-2-1");
+    }
+
+    #[test]
+    fn test_progress_payload_from_python_report() {
+        let payload = progress_payload(
+            "RESTORE-1-0",
+            &json!({"phase": "decrypting", "percent": 42.5, "message": "Decrypting..."}),
+        );
+        assert_eq!(payload.phase, "decrypting");
+        assert_eq!(payload.progress, 42.5);
+        assert_eq!(payload.msg, "Decrypting...");
+
+        let fallback = progress_payload("RESTORE-1-0", &json!({"percent": 250}));
+        assert_eq!(fallback.phase, "restoring");
+        assert_eq!(fallback.progress, 100.0);
     }
 }
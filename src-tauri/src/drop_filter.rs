@@ -0,0 +1,163 @@
+//! Allow-list for files dropped onto the main window.
+//!
+//! The drop handler emits accepted paths on `file-uploaded` and the rest on
+//! `file-rejected`, so the frontend never has to know which types we index.
+//!
+//! Files over [`max_file_size`] (default [`MAX_FILE_SIZE_BYTES`]) are
+//! rejected too, with reason `too_large` and their size. The same limits
+//! are reported to the frontend by `cmd_supported_formats`.
+//!
+//! Directories are not expanded: they are emitted on `folder-dropped` and
+//! the frontend decides whether to import them. Mixed drops are split
+//! per path, so files dropped alongside a folder are still uploaded.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Extensions (lowercase, without dot) the app can ingest or restore
+pub const ALLOWED_EXTENSIONS: &[&str] = &[
+    "docx", "xlsx", "xls", "pdf", "txt", "md", "png", "jpg", "jpeg", "tif", "tiff", "cvbak",
+];
+
+/// Default for the largest file accepted for ingest or restore
+pub const MAX_FILE_SIZE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Limit in effect, see [`set_max_file_size`]
+static MAX_FILE_SIZE: AtomicU64 = AtomicU64::new(MAX_FILE_SIZE_BYTES);
+
+/// Largest file accepted for ingest or restore
+pub fn max_file_size() -> u64 {
+    MAX_FILE_SIZE.load(Ordering::Relaxed)
+}
+
+/// Change the size limit applied to later drops
+pub fn set_max_file_size(bytes: u64) {
+    MAX_FILE_SIZE.store(bytes, Ordering::Relaxed);
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RejectedFile {
+    pub path: PathBuf,
+    /// no_extension | unsupported_type | too_large
+    pub reason: String,
+    /// Human-readable explanation for toasts
+    pub message: String,
+    /// File size, set when `reason == "too_large"`
+    pub size: Option<u64>,
+}
+
+/// Why `path` is rejected, as `(reason, message, size)`
+fn rejection_reason(path: &Path, size: Option<u64>) -> Option<(&'static str, String, Option<u64>)> {
+    match path.extension().and_then(|e| e.to_str()) {
+        None => Some(("no_extension", "File has no extension".to_string(), None)),
+        Some(ext) if !ALLOWED_EXTENSIONS.contains(&ext.to_lowercase().as_str()) => Some((
+            "unsupported_type",
+            format!("Unsupported file type: .{}", ext),
+            None,
+        )),
+        _ => size.filter(|&size| size > max_file_size()).map(|size| {
+            (
+                "too_large",
+                format!("File too large: {} bytes", size),
+                Some(size),
+            )
+        }),
+    }
+}
+
+/// Dropped paths sorted by how the handler should emit them
+#[derive(Debug, Default, PartialEq)]
+pub struct DropPartition {
+    /// Files on the allow-list (`file-uploaded`)
+    pub accepted: Vec<PathBuf>,
+    /// Files outside the allow-list (`file-rejected`)
+    pub rejected: Vec<RejectedFile>,
+    /// Directories (`folder-dropped`)
+    pub folders: Vec<PathBuf>,
+}
+
+/// Split dropped paths into accepted files, rejections with a reason, and
+/// folders
+pub fn partition_drop(paths: &[PathBuf]) -> DropPartition {
+    let mut drop = DropPartition::default();
+    for path in paths {
+        let metadata = std::fs::metadata(path).ok();
+        if metadata.as_ref().is_some_and(|m| m.is_dir()) {
+            drop.folders.push(path.clone());
+            continue;
+        }
+        match rejection_reason(path, metadata.map(|m| m.len())) {
+            None => drop.accepted.push(path.clone()),
+            Some((reason, message, size)) => drop.rejected.push(RejectedFile {
+                path: path.clone(),
+                reason: reason.to_string(),
+                message,
+                size,
+            }),
+        }
+    }
+    drop
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_drop_by_extension() {
+        let paths: Vec<PathBuf> = ["a.DOCX", "vault.cvbak", "run.exe", "README"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let drop = partition_drop(&paths);
+
+        assert_eq!(
+            drop.accepted,
+            [PathBuf::from("a.DOCX"), PathBuf::from("vault.cvbak")]
+        );
+        assert_eq!(drop.rejected.len(), 2);
+        assert_eq!(drop.rejected[0].reason, "unsupported_type");
+        assert_eq!(drop.rejected[0].message, "Unsupported file type: .exe");
+        assert_eq!(drop.rejected[1].reason, "no_extension");
+        assert_eq!(drop.rejected[1].message, "File has no extension");
+        assert!(drop.folders.is_empty());
+    }
+
+    #[test]
+    fn test_folders_are_split_out_of_mixed_drops() {
+        // A directory named like an allowed file must not pass as one
+        let dir = std::env::temp_dir().join(format!("drop-{}.docx", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let drop = partition_drop(&[dir.clone(), PathBuf::from("notes.md")]);
+        assert_eq!(drop.folders, std::slice::from_ref(&dir));
+        assert_eq!(drop.accepted, [PathBuf::from("notes.md")]);
+        assert!(drop.rejected.is_empty());
+
+        std::fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_oversized_files_are_rejected() {
+        let path = std::env::temp_dir().join(format!("drop-{}-big.pdf", std::process::id()));
+        // Sparse, so no disk space is used
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_len(MAX_FILE_SIZE_BYTES + 1).unwrap();
+
+        let drop = partition_drop(std::slice::from_ref(&path));
+        assert!(drop.accepted.is_empty());
+        assert_eq!(drop.rejected[0].reason, "too_large");
+        assert_eq!(drop.rejected[0].size, Some(MAX_FILE_SIZE_BYTES + 1));
+        assert_eq!(
+            drop.rejected[0].message,
+            format!("File too large: {} bytes", MAX_FILE_SIZE_BYTES + 1)
+        );
+
+        file.set_len(MAX_FILE_SIZE_BYTES).unwrap();
+        let drop = partition_drop(std::slice::from_ref(&path));
+        assert_eq!(drop.accepted, std::slice::from_ref(&path));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -3,10 +3,13 @@ use tauri::Emitter;
 pub mod commands {
     pub mod backup;
     pub mod dispatch;
+    pub mod formats;
     pub mod recovery;
+    pub mod recovery_export;
     pub mod restore;
 }
 
+pub mod drop_filter;
 pub mod python_bridge;
 
 #[cfg(test)]
@@ -14,25 +17,53 @@ mod tests;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // RUST_LOG overrides the default level (e.g. RUST_LOG=warn)
+    let _ = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .try_init();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .setup(|app| {
             use tauri::Manager;
+            if let Ok(resource_dir) = app.path().resource_dir() {
+                python_bridge::set_resource_dir(resource_dir);
+            }
+            // Drop size limit override, in bytes (reported by cmd_supported_formats)
+            if let Some(limit) = std::env::var("CONVERT_MAX_DROP_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+            {
+                drop_filter::set_max_file_size(limit);
+            }
             let window = app.get_webview_window("main").unwrap();
             let window_clone = window.clone();
 
             window.on_window_event(move |event| match event {
+                // Hover events let the frontend toggle its drop-zone highlight
                 tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Enter { paths, .. }) => {
-                    println!("🔍 [RUST DEBUG] File Hover detected: {:?}", paths);
+                    log::debug!("🔍 [RUST DEBUG] File Hover detected: {:?}", paths);
+                    let _ = window_clone.emit("file-hover-enter", paths);
                 }
                 tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
-                    println!("✅ [RUST DEBUG] File Dropped: {:?}", paths);
-                    let _ = window_clone.emit("file-uploaded", &paths);
+                    log::info!("✅ [RUST DEBUG] File Dropped: {:?}", paths);
+                    // No Leave follows a drop
+                    let _ = window_clone.emit("file-hover-leave", ());
+                    let drop = drop_filter::partition_drop(paths);
+                    if !drop.accepted.is_empty() {
+                        let _ = window_clone.emit("file-uploaded", &drop.accepted);
+                    }
+                    if !drop.rejected.is_empty() {
+                        let _ = window_clone.emit("file-rejected", &drop.rejected);
+                    }
+                    if !drop.folders.is_empty() {
+                        let _ = window_clone.emit("folder-dropped", &drop.folders);
+                    }
                 }
                 tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Leave { .. }) => {
-                    println!("❌ [RUST DEBUG] File Drop Cancelled/Left");
+                    log::debug!("❌ [RUST DEBUG] File Drop Cancelled/Left");
+                    let _ = window_clone.emit("file-hover-leave", ());
                 }
                 _ => {}
             });
@@ -40,10 +71,19 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::backup::cmd_backup_start,
+            commands::backup::cmd_backup_cancel,
+            commands::backup::cmd_backup_subscribe,
             commands::recovery::cmd_export_recovery_svg,
+            commands::recovery::cmd_fetch_recovery_image,
+            commands::recovery_export::verify_recovery_phrase,
             commands::restore::cmd_restore_backup,
             commands::dispatch::cmd_dispatch,
-            commands::dispatch::cmd_restore_from_file
+            commands::dispatch::cmd_init_backend,
+            commands::dispatch::cmd_python_health,
+            commands::dispatch::cmd_dispatch_stats,
+            commands::dispatch::cmd_dispatch_cancel,
+            commands::dispatch::cmd_restore_from_file,
+            commands::formats::cmd_supported_formats
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
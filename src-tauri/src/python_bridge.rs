@@ -7,144 +7,1315 @@
 //! - Dynamic Path Resolution (Gap 2 Fix)
 //! - Persistent Session State data via OnceCell (Gap 3 Fix)
 
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
+use pyo3::exceptions::{PyOSError, PyRuntimeError, PyValueError};
+use pyo3::ffi;
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{
+    PyBool, PyBytes, PyCFunction, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple,
+};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::env;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::os::raw::c_long;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant};
 
 // GLOBAL STATE: Persist Python Dispatcher instance
 // Mutex ensures thread safety across Tauri command calls
 static PYTHON_DISPATCHER: Lazy<Mutex<Option<PyObject>>> = Lazy::new(|| Mutex::new(None));
 
-/// Helper: Resolve Python Core source path dynamically
-fn get_python_src_path() -> PathBuf {
-    let current_dir = env::current_dir().unwrap_or_default();
+/// Env var that surfaces full Python tracebacks in release builds
+const DEBUG_TRACEBACK_ENV: &str = "CONVERT_DEBUG_TRACEBACKS";
+
+/// Tracebacks longer than this keep only their tail (innermost frames)
+const MAX_TRACEBACK_CHARS: usize = 4000;
+
+/// Env var forcing the JSON-string path for payloads and results
+const BRIDGE_JSON_ENV: &str = "CONVERT_BRIDGE_JSON";
+
+/// Python module and class instantiated as the dispatcher
+struct BackendConfig {
+    module: String,
+    class: String,
+}
+
+const DEFAULT_BACKEND_MODULE: &str = "core.dispatcher";
+const DEFAULT_BACKEND_CLASS: &str = "Dispatcher";
+
+/// Backend chosen via `configure_backend`, fixed once the bridge initializes
+static BACKEND: OnceCell<BackendConfig> = OnceCell::new();
+
+/// Select the Python module/class the bridge instantiates
+///
+/// Defaults to `core.dispatcher.Dispatcher`. Must be called before the
+/// first dispatch, e.g. so integration tests can inject a stub backend.
+pub fn configure_backend(module: String, class: String) -> Result<(), String> {
+    BACKEND
+        .set(BackendConfig { module, class })
+        .map_err(|_| "Backend already configured or initialized".to_string())
+}
+
+/// Configured backend, falling back to (and fixing) the defaults
+fn backend() -> &'static BackendConfig {
+    BACKEND.get_or_init(|| BackendConfig {
+        module: DEFAULT_BACKEND_MODULE.to_string(),
+        class: DEFAULT_BACKEND_CLASS.to_string(),
+    })
+}
 
-    // 1. DEV Mode: Look for adjacent src-core directory
-    // E:\DEV\Convert\src-tauri\..\src
-    // Note: Project structure is src/core/dispatcher.py, but sys.path needs the root of the module
-    // If src/core/dispatcher.py exists, we need to add 'src' to sys.path so 'import core.dispatcher' works?
-    // OR if we import 'dispatcher' directly, we need to add 'src/core' to sys.path.
-    // Based on previous code: sys_path.insert(0, path_str) where path points to 'src/core'
+/// Retry policy for loading the backend
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InitRetry {
+    /// Total tries, including the first (at least one is always made)
+    pub attempts: u32,
+    /// Wait before the first retry, doubled before each further one
+    pub base_delay: Duration,
+}
+
+const DEFAULT_INIT_ATTEMPTS: u32 = 3;
+const DEFAULT_INIT_DELAY: Duration = Duration::from_millis(200);
 
-    // Attempt 1: Check for standard Monorepo Dev structure
-    // We are in src-tauri. We need to go up one level, then into src/core
-    // But wait, the previous code used: project_root.join("src") which implies E:\DEV\Convert\src
-    // And import was "core.dispatcher".
+/// Policy chosen via `configure_init_retry`, fixed once the bridge initializes
+static INIT_RETRY: OnceCell<InitRetry> = OnceCell::new();
+
+/// Set how often a transient backend load failure is retried
+///
+/// Defaults to 3 attempts starting at a 200 ms delay. Like
+/// `configure_backend`, must be called before the first dispatch.
+pub fn configure_init_retry(attempts: u32, base_delay: Duration) -> Result<(), String> {
+    INIT_RETRY
+        .set(InitRetry {
+            attempts,
+            base_delay,
+        })
+        .map_err(|_| "Init retry already configured or initialized".to_string())
+}
 
-    let root_src = current_dir.parent().unwrap().join("src");
-    if root_src.exists() {
-        println!("🐍 [PyO3] DEV Mode detected. Path: {:?}", root_src);
-        return root_src;
+/// Configured retry policy, falling back to (and fixing) the defaults
+fn init_retry() -> InitRetry {
+    *INIT_RETRY.get_or_init(|| InitRetry {
+        attempts: DEFAULT_INIT_ATTEMPTS,
+        base_delay: DEFAULT_INIT_DELAY,
+    })
+}
+
+/// Tauri resource directory, recorded at app setup (PROD bundles)
+static RESOURCE_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// Bundled Python sources live under `<resource_dir>/python`
+/// (see `bundle.resources` in tauri.conf.json)
+const BUNDLED_PYTHON_DIR: &str = "python";
+
+/// Record the app's resource directory (`AppHandle::path().resource_dir()`)
+///
+/// Must be called before the first dispatch for PROD builds to find the
+/// bundled Python sources; later calls are ignored.
+pub fn set_resource_dir(dir: PathBuf) {
+    let _ = RESOURCE_DIR.set(dir);
+}
+
+/// A directory is a Python root if the backend module imports from it
+fn is_python_root(dir: &Path) -> bool {
+    let module_path = dir.join(backend().module.replace('.', "/"));
+    module_path.with_extension("py").is_file() || module_path.join("__init__.py").is_file()
+}
+
+/// Helper: Resolve Python Core source path dynamically
+///
+/// The returned directory goes on `sys.path` so the backend module imports.
+fn get_python_src_path() -> Result<PathBuf, String> {
+    let mut searched = Vec::new();
+
+    // 1. PROD Mode: sources bundled into the Tauri resource directory
+    if let Some(resource_dir) = RESOURCE_DIR.get() {
+        let bundled = resource_dir.join(BUNDLED_PYTHON_DIR);
+        if is_python_root(&bundled) {
+            log::info!("🐍 [PyO3] PROD Mode detected. Path: {:?}", bundled);
+            return Ok(bundled);
+        }
+        searched.push(bundled);
     }
 
-    // 2. PROD Mode: Fallback to local 'resources' or bundled folder
-    println!("⚠️ [PyO3] DEV path not found. Falling back to PROD logic.");
-    // For now, return current dir to prevent crash, real prod logic needs sidecar resource
-    current_dir
+    // 2. DEV Mode: running from src-tauri, sources in the adjacent ../src
+    let current_dir =
+        env::current_dir().map_err(|e| format!("Cannot read current directory: {}", e))?;
+    if let Some(root_src) = current_dir.parent().map(|parent| parent.join("src")) {
+        if is_python_root(&root_src) {
+            log::info!("🐍 [PyO3] DEV Mode detected. Path: {:?}", root_src);
+            return Ok(root_src);
+        }
+        searched.push(root_src);
+    }
+
+    Err(format!(
+        "Python sources for {} not found; searched {:?}",
+        backend().module,
+        searched
+    ))
+}
+
+/// Lock the dispatcher slot
+///
+/// Lock order is always dispatcher mutex first, then the GIL; taking them
+/// the other way round could deadlock against init/dispatch/reset.
+///
+/// A thread panicking while holding the lock poisons it. Rather than
+/// failing every later dispatch, the guard is recovered, the instance
+/// (possibly left mid-call) dropped so the next dispatch re-initializes,
+/// and the poison cleared.
+fn lock_dispatcher() -> MutexGuard<'static, Option<PyObject>> {
+    PYTHON_DISPATCHER.lock().unwrap_or_else(|poisoned| {
+        log::warn!("⚠️ [PyO3] Dispatcher lock poisoned by a panic; re-initializing backend");
+        let mut guard = poisoned.into_inner();
+        *guard = None;
+        PYTHON_DISPATCHER.clear_poison();
+        guard
+    })
 }
 
 /// Initialize Python environment and cache Dispatcher instance
-pub fn init_python_backend() -> PyResult<()> {
-    let mut dispatcher_guard = PYTHON_DISPATCHER.lock().unwrap();
+///
+/// Returns whether this call loaded the backend (false if already cached).
+pub fn init_python_backend() -> PyResult<bool> {
+    let mut dispatcher_guard = lock_dispatcher();
+    init_locked(&mut dispatcher_guard)
+}
 
+/// Initialize into an already locked dispatcher slot (no-op if cached)
+///
+/// Transient failures (see `is_transient`) are retried per
+/// `configure_init_retry`, sleeping with the GIL released in between.
+fn init_locked(dispatcher_guard: &mut Option<PyObject>) -> PyResult<bool> {
     if dispatcher_guard.is_some() {
-        return Ok(());
+        return Ok(false);
     }
 
-    Python::with_gil(|py| {
-        // 1. Setup Path
-        let sys = py.import_bound("sys")?;
-        let path = sys.getattr("path")?;
-
-        let src_path = get_python_src_path();
-        path.call_method1("insert", (0, src_path.to_str().unwrap()))?;
-
-        println!("🐍 [PyO3] PYTHONPATH injected: {:?}", src_path);
-
-        // 2. Import Module
-        // We assume 'core.dispatcher' based on 'src' being the root in sys.path
-        // If sys.path points to 'src', then 'import core.dispatcher' is valid?
-        // Let's verify: E:\DEV\Convert\src\core\dispatcher.py
-        // If sys.path = E:\DEV\Convert\src
-        // Then 'import core.dispatcher' works.
-        let module = PyModule::import_bound(py, "core.dispatcher").map_err(|e| {
-            println!("❌ [PyO3] Import Failed: {}", e);
-            e
+    let retry = init_retry();
+    let attempts = retry.attempts.max(1);
+    let mut delay = retry.base_delay;
+    let mut attempt = 1;
+    loop {
+        log::debug!(
+            "🐍 [PyO3] Loading backend (attempt {}/{})",
+            attempt,
+            attempts
+        );
+        let error = match Python::with_gil(load_dispatcher) {
+            Ok(instance) => {
+                *dispatcher_guard = Some(instance);
+                log::info!("🐍 [PyO3] Dispatcher Singleton Initialized.");
+                return Ok(true);
+            }
+            Err(e) => e,
+        };
+
+        let transient = Python::with_gil(|py| is_transient(py, &error));
+        if !transient || attempt >= attempts {
+            log::error!(
+                "❌ [PyO3] Init Failed (attempt {}/{}): {}",
+                attempt,
+                attempts,
+                error
+            );
+            return Err(error);
+        }
+        log::warn!(
+            "⚠️ [PyO3] Init attempt {}/{} failed, retrying in {:?}: {}",
+            attempt,
+            attempts,
+            delay,
+            error
+        );
+        thread::sleep(delay);
+        delay = delay.saturating_mul(2);
+        attempt += 1;
+    }
+}
+
+/// Put the sources on `sys.path`, import the backend and instantiate it
+fn load_dispatcher(py: Python<'_>) -> PyResult<PyObject> {
+    // 1. Setup Path
+    let sys = py.import_bound("sys")?;
+    let path = sys.getattr("path")?;
+
+    // A stub backend may already be importable without our sources
+    let src_path = get_python_src_path();
+    if let Ok(src_path) = &src_path {
+        // A retried attempt finds the entry already in place
+        if !path.contains(src_path)? {
+            path.call_method1("insert", (0, src_path))?;
+            log::debug!("🐍 [PyO3] PYTHONPATH injected: {:?}", src_path);
+        }
+    }
+
+    // 2. Import Module (with 'src' on sys.path, 'core.dispatcher' imports)
+    let backend = backend();
+    let module =
+        PyModule::import_bound(py, backend.module.as_str()).map_err(|e| match src_path {
+            Err(path_error) => PyRuntimeError::new_err(path_error),
+            Ok(_) => e,
         })?;
 
-        // 3. Create Instance
-        let class = module.getattr("Dispatcher")?;
-        let instance = class.call0()?;
+    // 3. Create Instance
+    let class = module.getattr(backend.class.as_str())?;
+    Ok(class.call0()?.unbind())
+}
+
+/// Whether an init failure may succeed when simply tried again
+///
+/// Only `OSError`s (filesystem hiccups, contended locks, timeouts) are
+/// retried. `ModuleNotFoundError`, `SyntaxError`, a missing class and
+/// anything else fail the same way every time.
+fn is_transient(py: Python<'_>, err: &PyErr) -> bool {
+    err.is_instance_of::<PyOSError>(py)
+}
 
-        // 4. Cache it
-        *dispatcher_guard = Some(instance.unbind());
-        println!("🐍 [PyO3] Dispatcher Singleton Initialized.");
+/// Drop the cached Dispatcher so the next dispatch re-initializes
+///
+/// In-flight dispatches hold the dispatcher lock for the whole call, so a
+/// reset waits for them to finish and no dispatch can start mid-reset.
+/// The backend's package is evicted from `sys.modules` so the next init
+/// re-imports fresh sources (hot reload during development).
+pub fn reset_python_backend() -> PyResult<()> {
+    let mut dispatcher_guard = lock_dispatcher();
+    let Some(dispatcher) = dispatcher_guard.take() else {
+        return Ok(());
+    };
 
+    Python::with_gil(|py| {
+        drop(dispatcher);
+
+        let package = backend().module.split('.').next().unwrap_or_default();
+        let prefix = format!("{}.", package);
+        let modules = py.import_bound("sys")?.getattr("modules")?;
+        let stale: Vec<String> = modules
+            .call_method0("keys")?
+            .iter()?
+            .filter_map(|name| name.ok()?.extract::<String>().ok())
+            .filter(|name| name == package || name.starts_with(&prefix))
+            .collect();
+        for name in stale {
+            modules.del_item(name)?;
+        }
+
+        log::info!("🐍 [PyO3] Dispatcher Singleton Reset.");
         Ok(())
     })
 }
 
+/// Bridge status for health checks
+pub struct BackendHealth {
+    /// Whether the interpreter ran a probe and the Python sources were found
+    pub ok: bool,
+    /// Embedded interpreter version, e.g. "3.12.4"
+    pub python_version: String,
+    /// Whether the Dispatcher singleton is initialized
+    pub backend_loaded: bool,
+}
+
+/// Report bridge status without dispatching or initializing anything
+///
+/// Never waits on an in-flight dispatch: a held lock means a dispatch is
+/// running, so the backend counts as loaded.
+pub fn backend_health() -> BackendHealth {
+    let backend_loaded = match PYTHON_DISPATCHER.try_lock() {
+        Ok(guard) => guard.is_some(),
+        Err(TryLockError::WouldBlock) => true,
+        Err(TryLockError::Poisoned(_)) => false,
+    };
+
+    // Running code, not just taking the GIL, shows the interpreter works
+    let probe = Python::with_gil(|py| {
+        py.import_bound("platform")
+            .and_then(|platform| platform.call_method0("python_version"))
+            .and_then(|version| version.extract::<String>())
+    });
+    let ok = probe.is_ok() && get_python_src_path().is_ok();
+
+    BackendHealth {
+        ok,
+        python_version: probe.unwrap_or_default(),
+        backend_loaded,
+    }
+}
+
+/// Receives the progress updates a handler reports during one dispatch
+pub type ProgressFn = Arc<dyn Fn(Value) + Send + Sync>;
+
 /// Dispatch command to Python (Stateful)
 pub fn dispatch_to_python(cmd: &str, payload: Value) -> Result<Value, String> {
-    // Ensure initialized
-    if PYTHON_DISPATCHER.lock().unwrap().is_none() {
-        init_python_backend().map_err(|e| format!("Init Failed: {}", e))?;
+    dispatch_locked(cmd, payload, None, None, None, None)
+}
+
+/// Dispatch command to Python with a raw byte buffer
+///
+/// `data` reaches Python as a `bytes` object in the envelope's `data` slot
+/// instead of being base64-encoded into the JSON payload; `meta` is sent as
+/// the usual payload.
+pub fn dispatch_bytes_to_python(cmd: &str, data: &[u8], meta: Value) -> Result<Value, String> {
+    dispatch_locked(cmd, meta, None, None, Some(data), None)
+}
+
+/// Dispatch command to Python, forwarding progress reports to `on_progress`
+///
+/// The envelope gains a `progress` callable; handlers call it with a dict
+/// (by convention `{"phase", "percent", "message"}`), which is converted
+/// like a result and passed on. Runs on the calling thread, so long
+/// operations should be dispatched from a worker.
+pub fn dispatch_to_python_with_progress(
+    cmd: &str,
+    payload: Value,
+    on_progress: ProgressFn,
+) -> Result<Value, String> {
+    dispatch_locked(cmd, payload, None, Some(&on_progress), None, None)
+}
+
+/// Dispatch command to Python under a cancellation token
+///
+/// The envelope gains a `cancelled` callable returning whether
+/// `cancel_dispatch` was called for the token's task. Cancellation is
+/// cooperative: a dispatch still queued is skipped with
+/// `Err("Python call cancelled")`, but a running handler only stops if it
+/// checks `cancelled()` itself; others run to completion.
+pub fn dispatch_to_python_cancellable(
+    cmd: &str,
+    payload: Value,
+    token: &CancelToken,
+    on_progress: Option<ProgressFn>,
+) -> Result<Value, String> {
+    dispatch_locked(cmd, payload, None, on_progress.as_ref(), None, Some(token))
+}
+
+/// Cancellation flags of cancellable dispatches, keyed by task_id
+static CANCEL_TOKENS: Lazy<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Cooperative cancellation flag of one dispatch, registered by task_id
+///
+/// Dropping the token unregisters it, so `cancel_dispatch` only reaches
+/// dispatches that are still queued or running.
+pub struct CancelToken {
+    task_id: String,
+    flag: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Register a fresh token for `task_id`, replacing any earlier one
+    pub fn register(task_id: &str) -> Self {
+        let flag = Arc::new(AtomicBool::new(false));
+        CANCEL_TOKENS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(task_id.to_string(), Arc::clone(&flag));
+
+        CancelToken {
+            task_id: task_id.to_string(),
+            flag,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for CancelToken {
+    fn drop(&mut self) {
+        let mut tokens = CANCEL_TOKENS.lock().unwrap_or_else(|e| e.into_inner());
+        // A later token registered under the same id stays
+        if tokens
+            .get(&self.task_id)
+            .is_some_and(|flag| Arc::ptr_eq(flag, &self.flag))
+        {
+            tokens.remove(&self.task_id);
+        }
+    }
+}
+
+/// Request cancellation of the dispatch registered under `task_id`
+///
+/// Returns false if no such dispatch is queued or running.
+pub fn cancel_dispatch(task_id: &str) -> bool {
+    let tokens = CANCEL_TOKENS.lock().unwrap_or_else(|e| e.into_inner());
+    match tokens.get(task_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            log::info!("🐍 [PyO3] Cancellation requested for {}", task_id);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Dispatch command to Python, giving up after `timeout_ms`
+///
+/// CPython cannot preempt a running handler, so this only bounds how long
+/// the caller waits: on timeout it returns `Err("Python call timed out")`
+/// right away while the call keeps running on its own thread. Cancellation
+/// is best effort - a dispatch still queued behind the lock is skipped, and
+/// a running handler gets a `TimeoutError` raised at its next bytecode
+/// (handlers blocked inside C code are not interrupted). `None` waits
+/// forever, like `dispatch_to_python`.
+pub fn dispatch_to_python_timeout(
+    cmd: &str,
+    payload: Value,
+    timeout_ms: Option<u64>,
+) -> Result<Value, String> {
+    let Some(timeout_ms) = timeout_ms else {
+        return dispatch_to_python(cmd, payload);
+    };
+
+    let cmd = cmd.to_string();
+    run_with_timeout(Duration::from_millis(timeout_ms), move |state| {
+        dispatch_locked(&cmd, payload, Some(state), None, None, None)
+    })
+}
+
+/// FIFO admission for dispatches, with a fast lane for short commands
+///
+/// Handlers hold the GIL (and the dispatcher lock) for their whole run, so
+/// dispatches can only run one at a time; `std::sync::Mutex` makes no
+/// ordering promise, and a burst of calls could keep overtaking one that
+/// arrived first. Tickets are served strictly in arrival order instead.
+///
+/// Commands of a [`PRIORITY_SERVICES`] service draw from a second lane that
+/// is served before the regular one, so a `system.ping` waits for at most
+/// the dispatch already running, not for every restore queued before it.
+/// A running dispatch is never preempted.
+struct DispatchQueue {
+    state: Mutex<QueueState>,
+    turn: Condvar,
+}
+
+/// Services whose commands are short and skip ahead of queued dispatches
+const PRIORITY_SERVICES: &[&str] = &["system"];
+
+/// Tickets of one lane, served in arrival order
+#[derive(Default)]
+struct Lane {
+    next_ticket: u64,
+    now_serving: u64,
+}
+
+impl Lane {
+    /// Tickets handed out and not yet finished
+    fn in_flight(&self) -> u64 {
+        self.next_ticket - self.now_serving
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    regular: Lane,
+    priority: Lane,
+    /// Command holding the turn
+    active: Option<String>,
+    served: u64,
+    max_waiting: u64,
+    max_wait: Duration,
+}
+
+impl QueueState {
+    /// Dispatches waiting for their turn (not counting the running one)
+    fn waiting(&self) -> u64 {
+        (self.regular.in_flight() + self.priority.in_flight())
+            .saturating_sub(u64::from(self.active.is_some()))
+    }
+}
+
+static DISPATCH_QUEUE: Lazy<DispatchQueue> = Lazy::new(|| DispatchQueue {
+    state: Mutex::new(QueueState::default()),
+    turn: Condvar::new(),
+});
+
+/// Snapshot of the dispatch queue, for debugging stalls
+pub struct QueueStats {
+    /// Dispatches waiting for their turn (not counting the running one)
+    pub waiting: u64,
+    /// Command currently running, if any
+    pub active: Option<String>,
+    /// Dispatches completed since startup
+    pub served: u64,
+    /// Highest `waiting` seen
+    pub max_waiting: u64,
+    /// Longest time a dispatch waited for its turn
+    pub max_wait: Duration,
+}
+
+impl DispatchQueue {
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        // The state is only counters, which stay consistent across a panic
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
     }
 
+    /// Block until every dispatch queued earlier in the same lane has
+    /// finished (and, for the regular lane, the priority lane is empty)
+    fn wait_turn(&'static self, cmd: &str) -> QueueTurn {
+        let service = cmd.split('.').next().unwrap_or_default();
+        let priority = PRIORITY_SERVICES.contains(&service);
+
+        let mut state = self.lock();
+        let lane = if priority {
+            &mut state.priority
+        } else {
+            &mut state.regular
+        };
+        let ticket = lane.next_ticket;
+        lane.next_ticket += 1;
+        state.max_waiting = state.max_waiting.max(state.waiting());
+
+        let queued_at = Instant::now();
+        loop {
+            let (own, other_busy) = if priority {
+                (&state.priority, false)
+            } else {
+                (&state.regular, state.priority.in_flight() > 0)
+            };
+            if own.now_serving == ticket && !other_busy && state.active.is_none() {
+                break;
+            }
+            state = self.turn.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        state.active = Some(cmd.to_string());
+        state.max_wait = state.max_wait.max(queued_at.elapsed());
+
+        QueueTurn {
+            queue: self,
+            priority,
+        }
+    }
+}
+
+/// A dispatch's turn; dropping it (also on panic) admits the next ticket
+struct QueueTurn {
+    queue: &'static DispatchQueue,
+    priority: bool,
+}
+
+impl Drop for QueueTurn {
+    fn drop(&mut self) {
+        let mut state = self.queue.lock();
+        if self.priority {
+            state.priority.now_serving += 1;
+        } else {
+            state.regular.now_serving += 1;
+        }
+        state.served += 1;
+        state.active = None;
+        self.queue.turn.notify_all();
+    }
+}
+
+/// Report the dispatch queue without waiting on it
+pub fn queue_stats() -> QueueStats {
+    let state = DISPATCH_QUEUE.lock();
+
+    QueueStats {
+        waiting: state.waiting(),
+        active: state.active.clone(),
+        served: state.served,
+        max_waiting: state.max_waiting,
+        max_wait: state.max_wait,
+    }
+}
+
+/// Dispatch under the dispatcher lock, optionally reporting to a timed caller
+fn dispatch_locked(
+    cmd: &str,
+    payload: Value,
+    state: Option<&CallState>,
+    progress: Option<&ProgressFn>,
+    data: Option<&[u8]>,
+    cancel: Option<&CancelToken>,
+) -> Result<Value, String> {
+    let _turn = DISPATCH_QUEUE.wait_turn(cmd);
+    // Held for the whole call so a reset cannot swap the instance mid-dispatch
+    let mut guard = lock_dispatcher();
+
+    // The caller may have timed out while we waited for the lock
+    // (or been cancelled by task_id)
+    if state.is_some_and(CallState::is_cancelled) || cancel.is_some_and(CancelToken::is_cancelled) {
+        return Err("Python call cancelled".to_string());
+    }
+
+    // Ensure initialized
+    init_locked(&mut guard).map_err(|e| format!("Init Failed: {}", e))?;
+
     Python::with_gil(|py| {
-        let guard = PYTHON_DISPATCHER.lock().unwrap();
-        let py_instance = guard.as_ref().expect("Dispatcher should be initialized");
-        let dispatcher = py_instance.bind(py);
-
-        // Create envelope
-        let envelope = PyDict::new_bound(py);
-        envelope.set_item("cmd", cmd).unwrap();
-
-        // Pass payload as JSON string to handle complex types reliably
-        let payload_str = serde_json::to_string(&payload).unwrap();
-
-        // IMPORTANT: The Python Dispatcher expects a DICT payload, NOT a string.
-        // We must convert JSON string -> Python Dict here to match the Interface.
-        let json_module = PyModule::import_bound(py, "json").unwrap();
-        let payload_dict = json_module.call_method1("loads", (payload_str,)).unwrap();
-
-        envelope.set_item("payload", payload_dict).unwrap();
-
-        // Call handle
-        let result = dispatcher
-            .call_method1("handle", (envelope,))
-            .map_err(|e| format!("Python Execution Error: {}", e))?;
-
-        // Convert result back to Rust Value
-        let result_str = json_module
-            .call_method1("dumps", (result,))
-            .unwrap()
-            .extract::<String>()
-            .unwrap();
+        let py_instance = guard
+            .as_ref()
+            .ok_or_else(|| "Dispatcher not initialized".to_string())?;
+
+        if let Some(state) = state {
+            state.enter(py);
+        }
+        let cancelled = cancel.map(|token| &token.flag);
+        let result = call_dispatcher(
+            py_instance.bind(py),
+            cmd,
+            &payload,
+            progress,
+            data,
+            cancelled,
+        );
+        if let Some(state) = state {
+            state.leave();
+        }
+        result
+    })
+}
+
+/// Cancellation handle shared between a timed caller and its worker thread
+#[derive(Default)]
+struct CallState {
+    cancelled: AtomicBool,
+    /// `threading.get_ident()` of the worker while it runs Python (0 = none)
+    python_thread: AtomicU64,
+}
+
+impl CallState {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Record that this thread is about to run Python code (GIL held)
+    fn enter(&self, py: Python<'_>) {
+        let ident = py
+            .import_bound("threading")
+            .and_then(|threading| threading.call_method0("get_ident"))
+            .and_then(|ident| ident.extract::<u64>());
+        if let Ok(ident) = ident {
+            self.python_thread.store(ident, Ordering::SeqCst);
+        }
+    }
+
+    /// Record that the Python code finished (GIL held)
+    fn leave(&self) {
+        self.python_thread.store(0, Ordering::SeqCst);
+    }
+
+    /// Skip the call if it has not started, else raise `TimeoutError` in it
+    fn cancel(self: &Arc<Self>) {
+        self.cancelled.store(true, Ordering::SeqCst);
+
+        // Waiting for the GIL must not block the caller that timed out
+        let state = Arc::clone(self);
+        thread::spawn(move || {
+            Python::with_gil(|_py| {
+                // Checked under the GIL, so the worker cannot leave in between
+                let ident = state.python_thread.load(Ordering::SeqCst);
+                if ident != 0 {
+                    // SAFETY: GIL is held and the exception type is a static
+                    // builtin; an unknown thread id is a no-op.
+                    unsafe {
+                        ffi::PyThreadState_SetAsyncExc(ident as c_long, ffi::PyExc_TimeoutError);
+                    }
+                }
+            })
+        });
+    }
+}
+
+/// Run `work` on its own thread, waiting at most `timeout` for the result
+fn run_with_timeout<F>(timeout: Duration, work: F) -> Result<Value, String>
+where
+    F: FnOnce(&CallState) -> Result<Value, String> + Send + 'static,
+{
+    let state = Arc::new(CallState::default());
+    let (sender, receiver) = mpsc::channel();
+
+    let worker_state = Arc::clone(&state);
+    thread::Builder::new()
+        .name("python-dispatch".to_string())
+        .spawn(move || {
+            // The caller may be gone already; the result is then dropped
+            let _ = sender.send(work(&worker_state));
+        })
+        .map_err(|e| format!("Failed to start dispatch thread: {}", e))?;
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            state.cancel();
+            Err("Python call timed out".to_string())
+        }
+        Err(RecvTimeoutError::Disconnected) => Err("Python dispatch thread panicked".to_string()),
+    }
+}
+
+/// Whether bridge errors should carry full Python tracebacks
+///
+/// On in debug builds; release builds only show the exception message
+/// unless `CONVERT_DEBUG_TRACEBACKS=1` is set, to avoid leaking internals.
+fn debug_tracebacks() -> bool {
+    cfg!(debug_assertions) || env::var(DEBUG_TRACEBACK_ENV).is_ok_and(|v| v == "1")
+}
+
+/// Format a Python exception, with its traceback when debugging
+fn python_error(py: Python<'_>, err: &PyErr) -> String {
+    let message = err.to_string();
+    if !debug_tracebacks() {
+        return message;
+    }
+
+    let Some(traceback) = err.traceback_bound(py).and_then(|tb| tb.format().ok()) else {
+        return message;
+    };
+
+    let traceback = match traceback.char_indices().rev().nth(MAX_TRACEBACK_CHARS) {
+        Some((cut, _)) => format!("...{}", &traceback[cut..]),
+        None => traceback,
+    };
+    format!("{}\n{}", message, traceback.trim_end())
+}
+
+/// Send one envelope to a Dispatcher instance and convert its reply
+///
+/// Every step maps failures into the error channel instead of panicking,
+/// so a handler returning non-JSON data cannot take down the app.
+fn call_dispatcher(
+    dispatcher: &Bound<'_, PyAny>,
+    cmd: &str,
+    payload: &Value,
+    progress: Option<&ProgressFn>,
+    data: Option<&[u8]>,
+    cancelled: Option<&Arc<AtomicBool>>,
+) -> Result<Value, String> {
+    let py = dispatcher.py();
+
+    // Create envelope
+    let envelope = PyDict::new_bound(py);
+    envelope
+        .set_item("cmd", cmd)
+        .map_err(|e| format!("Envelope Error (cmd): {}", e))?;
+
+    // IMPORTANT: The Python Dispatcher expects a DICT payload, NOT a string.
+    let payload_obj = if json_bridge() {
+        json_to_python(py, payload)?
+    } else {
+        value_to_py(py, payload).map_err(|e| format!("Payload Conversion Error: {}", e))?
+    };
+
+    envelope
+        .set_item("payload", payload_obj)
+        .map_err(|e| format!("Envelope Error (payload): {}", e))?;
+
+    if let Some(data) = data {
+        envelope
+            .set_item("data", PyBytes::new_bound(py, data))
+            .map_err(|e| format!("Envelope Error (data): {}", e))?;
+    }
 
-        let val: Value = serde_json::from_str(&result_str).unwrap();
-        Ok(val)
+    if let Some(progress) = progress {
+        let on_progress = Arc::clone(progress);
+        let callback = PyCFunction::new_closure_bound(
+            py,
+            Some(c"progress"),
+            None,
+            move |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<()> {
+                let update = args.get_item(0)?;
+                let value = py_to_value(&update).map_err(PyValueError::new_err)?;
+                on_progress(value);
+                Ok(())
+            },
+        )
+        .map_err(|e| format!("Envelope Error (progress): {}", e))?;
+        envelope
+            .set_item("progress", callback)
+            .map_err(|e| format!("Envelope Error (progress): {}", e))?;
+    }
+
+    if let Some(cancelled) = cancelled {
+        let flag = Arc::clone(cancelled);
+        let callback = PyCFunction::new_closure_bound(
+            py,
+            Some(c"cancelled"),
+            None,
+            move |_args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> bool {
+                flag.load(Ordering::SeqCst)
+            },
+        )
+        .map_err(|e| format!("Envelope Error (cancelled): {}", e))?;
+        envelope
+            .set_item("cancelled", callback)
+            .map_err(|e| format!("Envelope Error (cancelled): {}", e))?;
+    }
+
+    // Call handle
+    let result = dispatcher
+        .call_method1("handle", (envelope,))
+        .map_err(|e| format!("Python Execution Error: {}", python_error(py, &e)))?;
+
+    // Convert result back to Rust Value; anything without a direct JSON
+    // shape (tuples-as-keys, custom objects, ...) goes through json.dumps
+    if !json_bridge() {
+        if let Ok(value) = py_to_value(&result) {
+            return Ok(value);
+        }
+    }
+    python_to_json(py, &result)
+}
+
+/// Whether payloads/results go through JSON strings instead of native
+/// conversion (`CONVERT_BRIDGE_JSON=1`)
+fn json_bridge() -> bool {
+    env::var(BRIDGE_JSON_ENV).is_ok_and(|v| v == "1")
+}
+
+/// JSON fallback: serialize in Rust, `json.loads` in Python
+fn json_to_python(py: Python<'_>, value: &Value) -> Result<PyObject, String> {
+    let value_str =
+        serde_json::to_string(value).map_err(|e| format!("Payload Serialization Error: {}", e))?;
+    let json_module = PyModule::import_bound(py, "json")
+        .map_err(|e| format!("Python Import Error (json): {}", e))?;
+    json_module
+        .call_method1("loads", (value_str,))
+        .map(Bound::unbind)
+        .map_err(|e| format!("Payload Decode Error: {}", e))
+}
+
+/// JSON fallback: `json.dumps` in Python, parse in Rust
+fn python_to_json(py: Python<'_>, obj: &Bound<'_, PyAny>) -> Result<Value, String> {
+    let json_module = PyModule::import_bound(py, "json")
+        .map_err(|e| format!("Python Import Error (json): {}", e))?;
+
+    // NaN/Infinity are not valid JSON
+    let dumps_kwargs = PyDict::new_bound(py);
+    dumps_kwargs
+        .set_item("allow_nan", false)
+        .map_err(|e| format!("Result Serialization Error: {}", e))?;
+    let result_str = json_module
+        .call_method("dumps", (obj,), Some(&dumps_kwargs))
+        .map_err(|e| format!("Result Serialization Error: {}", e))?
+        .extract::<String>()
+        .map_err(|e| format!("Result Serialization Error: {}", e))?;
+
+    serde_json::from_str(&result_str).map_err(|e| format!("Result Parse Error: {}", e))
+}
+
+/// Build Python objects straight from a JSON value tree
+fn value_to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_py(py),
+            (None, Some(u)) => u.into_py(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(items) => {
+            let list = PyList::empty_bound(py);
+            for item in items {
+                list.append(value_to_py(py, item)?)?;
+            }
+            list.into_py(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new_bound(py);
+            for (key, item) in map {
+                dict.set_item(key, value_to_py(py, item)?)?;
+            }
+            dict.into_py(py)
+        }
     })
 }
 
+/// Convert Python objects that map 1:1 onto JSON into a value tree
+///
+/// Fails on anything else (sets, non-string keys, NaN, big ints, custom
+/// objects) so the caller can fall back to `json.dumps`.
+fn py_to_value(obj: &Bound<'_, PyAny>) -> Result<Value, String> {
+    if obj.is_none() {
+        return Ok(Value::Null);
+    }
+    // bool before int: Python's bool is an int subclass
+    if let Ok(b) = obj.downcast::<PyBool>() {
+        return Ok(Value::Bool(b.is_true()));
+    }
+    if obj.is_instance_of::<PyLong>() {
+        if let Ok(i) = obj.extract::<i64>() {
+            return Ok(i.into());
+        }
+        return obj
+            .extract::<u64>()
+            .map(Value::from)
+            .map_err(|_| "integer out of range".to_string());
+    }
+    if let Ok(f) = obj.downcast::<PyFloat>() {
+        return serde_json::Number::from_f64(f.value())
+            .map(Value::Number)
+            .ok_or_else(|| "non-finite float".to_string());
+    }
+    if let Ok(s) = obj.downcast::<PyString>() {
+        return s
+            .to_str()
+            .map(|s| Value::String(s.to_string()))
+            .map_err(|e| e.to_string());
+    }
+    if let Ok(list) = obj.downcast::<PyList>() {
+        return list.iter().map(|item| py_to_value(&item)).collect();
+    }
+    if let Ok(dict) = obj.downcast::<PyDict>() {
+        let mut map = serde_json::Map::with_capacity(dict.len());
+        for (key, item) in dict.iter() {
+            let key = key
+                .downcast::<PyString>()
+                .map_err(|_| "non-string key".to_string())?
+                .to_str()
+                .map_err(|e| e.to_string())?
+                .to_string();
+            map.insert(key, py_to_value(&item)?);
+        }
+        return Ok(Value::Object(map));
+    }
+
+    Err(format!("unsupported type {}", obj.get_type()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Serializes the tests that initialize, reset or poison the global
+    /// dispatcher; the harness would otherwise run them in parallel
+    static SINGLETON_TESTS: Mutex<()> = Mutex::new(());
+
+    fn lock_singleton() -> MutexGuard<'static, ()> {
+        SINGLETON_TESTS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Serializes the tests that hold turns of the global dispatch queue
+    static QUEUE_TESTS: Mutex<()> = Mutex::new(());
+
+    fn lock_queue() -> MutexGuard<'static, ()> {
+        QUEUE_TESTS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
     #[test]
     fn test_path_resolution() {
-        let path = get_python_src_path();
-        assert!(path.exists());
+        let path = get_python_src_path().unwrap();
+        assert!(is_python_root(&path));
+    }
+
+    #[test]
+    fn test_non_serializable_result_is_error() {
+        Python::with_gil(|py| {
+            let module = PyModule::from_code_bound(
+                py,
+                "class Dispatcher:\n    def handle(self, envelope):\n        return {'tags': {1, 2}, 'ratio': float('nan')}\n",
+                "stub_dispatcher.py",
+                "stub_dispatcher",
+            )
+            .unwrap();
+            let dispatcher = module.getattr("Dispatcher").unwrap().call0().unwrap();
+
+            let result = call_dispatcher(
+                &dispatcher,
+                "echo",
+                &serde_json::json!({}),
+                None,
+                None,
+                None,
+            );
+            let error = result.unwrap_err();
+            assert!(error.starts_with("Result Serialization Error"), "{}", error);
+        });
+    }
+
+    #[test]
+    fn test_native_value_round_trip() {
+        let value = serde_json::json!({
+            "name": "report.docx",
+            "flags": [true, false, null],
+            "size": 18446744073709551615u64,
+            "offset": -42,
+            "score": 0.5,
+            "nested": {"tags": ["a", "b"]}
+        });
+
+        Python::with_gil(|py| {
+            let obj = value_to_py(py, &value).unwrap();
+            assert_eq!(py_to_value(obj.bind(py)).unwrap(), value);
+
+            let tuple: PyObject = (1, 2).into_py(py);
+            assert!(py_to_value(tuple.bind(py)).is_err());
+        });
+    }
+
+    #[test]
+    fn test_progress_reports_reach_callback() {
+        Python::with_gil(|py| {
+            let module = PyModule::from_code_bound(
+                py,
+                "class Dispatcher:\n    def handle(self, envelope):\n        for pct in (10, 60):\n            envelope['progress']({'phase': 'restoring', 'percent': pct})\n        return {'status': 'success'}\n",
+                "progress_dispatcher.py",
+                "progress_dispatcher",
+            )
+            .unwrap();
+            let dispatcher = module.getattr("Dispatcher").unwrap().call0().unwrap();
+
+            let seen = Arc::new(Mutex::new(Vec::new()));
+            let sink = Arc::clone(&seen);
+            let progress: ProgressFn = Arc::new(move |update| sink.lock().unwrap().push(update));
+            let result = call_dispatcher(
+                &dispatcher,
+                "restore.start",
+                &serde_json::json!({}),
+                Some(&progress),
+                None,
+                None,
+            );
+
+            assert_eq!(result.unwrap()["status"], "success");
+            let seen = seen.lock().unwrap();
+            assert_eq!(seen.len(), 2);
+            assert_eq!(seen[1]["percent"], 60);
+        });
+    }
+
+    #[test]
+    fn test_bytes_reach_handler_unencoded() {
+        Python::with_gil(|py| {
+            let module = PyModule::from_code_bound(
+                py,
+                "class Dispatcher:\n    def handle(self, envelope):\n        data = envelope['data']\n        return {'type': type(data).__name__, 'len': len(data), 'last': data[-1], 'name': envelope['payload']['name']}\n",
+                "bytes_dispatcher.py",
+                "bytes_dispatcher",
+            )
+            .unwrap();
+            let dispatcher = module.getattr("Dispatcher").unwrap().call0().unwrap();
+
+            let data = [0u8, 159, 146, 150, 255];
+            let result = call_dispatcher(
+                &dispatcher,
+                "import.file",
+                &serde_json::json!({"name": "blob.bin"}),
+                None,
+                Some(&data),
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(result["type"], "bytes");
+            assert_eq!(result["len"], 5);
+            assert_eq!(result["last"], 255);
+            assert_eq!(result["name"], "blob.bin");
+        });
+    }
+
+    #[test]
+    fn test_cancel_token_reaches_handler() {
+        Python::with_gil(|py| {
+            let module = PyModule::from_code_bound(
+                py,
+                "class Dispatcher:\n    def handle(self, envelope):\n        return {'cancelled': envelope['cancelled']()}\n",
+                "cancel_dispatcher.py",
+                "cancel_dispatcher",
+            )
+            .unwrap();
+            let dispatcher = module.getattr("Dispatcher").unwrap().call0().unwrap();
+            let call = |token: &CancelToken| {
+                call_dispatcher(
+                    &dispatcher,
+                    "restore.start",
+                    &serde_json::json!({}),
+                    None,
+                    None,
+                    Some(&token.flag),
+                )
+                .unwrap()
+            };
+
+            let token = CancelToken::register("RESTORE-cancel-0");
+            assert_eq!(call(&token)["cancelled"], false);
+            assert!(cancel_dispatch("RESTORE-cancel-0"));
+            assert_eq!(call(&token)["cancelled"], true);
+        });
+
+        // Dropped tokens are unregistered
+        assert!(!cancel_dispatch("RESTORE-cancel-0"));
+        assert!(!cancel_dispatch("RESTORE-missing"));
+    }
+
+    #[test]
+    fn test_execution_error_includes_traceback() {
+        Python::with_gil(|py| {
+            let module = PyModule::from_code_bound(
+                py,
+                "class Dispatcher:\n    def handle(self, envelope):\n        return self.fail()\n    def fail(self):\n        raise ValueError('boom')\n",
+                "failing_dispatcher.py",
+                "failing_dispatcher",
+            )
+            .unwrap();
+            let dispatcher = module.getattr("Dispatcher").unwrap().call0().unwrap();
+
+            let error = call_dispatcher(
+                &dispatcher,
+                "echo",
+                &serde_json::json!({}),
+                None,
+                None,
+                None,
+            )
+            .unwrap_err();
+            assert!(error.contains("ValueError: boom"), "{}", error);
+            // Tests run as debug builds, so the traceback is included
+            assert!(error.contains("in fail"), "{}", error);
+        });
+    }
+
+    #[test]
+    fn test_timeout_interrupts_runaway_handler() {
+        let (done_sender, done_receiver) = mpsc::channel();
+
+        let result = run_with_timeout(Duration::from_millis(100), move |state| {
+            let outcome = Python::with_gil(|py| {
+                state.enter(py);
+                let outcome = py.run_bound("while True:\n    pass\n", None, None, None);
+                state.leave();
+                outcome.map_err(|e| e.to_string())
+            });
+            let _ = done_sender.send(outcome);
+            Ok(Value::Null)
+        });
+        assert_eq!(result, Err("Python call timed out".to_string()));
+
+        // The runaway loop is interrupted with TimeoutError
+        let outcome = done_receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert!(outcome.unwrap_err().contains("TimeoutError"));
     }
 
     #[test]
     fn test_singleton_initialization() {
+        let _singleton = lock_singleton();
         assert!(init_python_backend().is_ok());
         // Second call should return immediately
+        assert_eq!(init_python_backend().ok(), Some(false));
+        // The backend is fixed once initialized
+        assert!(configure_backend("stub".into(), "Stub".into()).is_err());
+    }
+
+    #[test]
+    fn test_only_os_errors_are_transient() {
+        Python::with_gil(|py| {
+            assert!(is_transient(py, &PyOSError::new_err("I/O hiccup")));
+            assert!(is_transient(
+                py,
+                &pyo3::exceptions::PyTimeoutError::new_err("slow disk")
+            ));
+            assert!(!is_transient(
+                py,
+                &pyo3::exceptions::PyModuleNotFoundError::new_err("No module named 'core'")
+            ));
+            assert!(!is_transient(
+                py,
+                &PyRuntimeError::new_err("sources not found")
+            ));
+        });
+        // The policy is fixed once read
+        let retry = init_retry();
+        assert!(configure_init_retry(5, Duration::from_millis(10)).is_err());
+        assert_eq!(init_retry(), retry);
+    }
+
+    #[test]
+    fn test_reset_allows_reinitialization() {
+        let _singleton = lock_singleton();
+        assert!(init_python_backend().is_ok());
+        assert!(reset_python_backend().is_ok());
+        // Resetting an empty slot is a no-op
+        assert!(reset_python_backend().is_ok());
         assert!(init_python_backend().is_ok());
+        assert!(lock_dispatcher().is_some());
+
+        let health = backend_health();
+        assert!(health.ok);
+        assert!(health.backend_loaded);
+        assert!(health.python_version.starts_with("3."));
+    }
+
+    #[test]
+    fn test_poisoned_lock_recovers() {
+        let _singleton = lock_singleton();
+        let panicked = thread::spawn(|| {
+            let _guard = PYTHON_DISPATCHER.lock();
+            panic!("handler panicked while holding the dispatcher");
+        })
+        .join();
+        assert!(panicked.is_err());
+
+        // Unknown services are reported by the dispatcher, not the bridge
+        let result = dispatch_to_python("unknown.ping", serde_json::json!({}));
+        assert_eq!(result.unwrap()["status"], "error");
+        assert!(!PYTHON_DISPATCHER.is_poisoned());
+    }
+
+    #[test]
+    fn test_dispatch_queue_is_fifo() {
+        let _queue = lock_queue();
+        let first = DISPATCH_QUEUE.wait_turn("test.first");
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let mut workers = Vec::new();
+        for i in 0..3 {
+            let before = queue_stats().waiting;
+            let order = Arc::clone(&order);
+            workers.push(thread::spawn(move || {
+                let _turn = DISPATCH_QUEUE.wait_turn("test.waiter");
+                order.lock().unwrap().push(i);
+            }));
+            // Queue the workers one after another
+            while queue_stats().waiting <= before {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+        let stats = queue_stats();
+        assert!(stats.waiting >= 3);
+        assert!(stats.max_waiting >= 3);
+
+        drop(first);
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+        assert!(queue_stats().served >= 4);
+    }
+
+    #[test]
+    fn test_priority_lane_skips_queued_dispatches() {
+        let _queue = lock_queue();
+        let first = DISPATCH_QUEUE.wait_turn("test.first");
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let mut workers = Vec::new();
+        for cmd in ["restore.start", "system.ping"] {
+            let before = queue_stats().waiting;
+            let order = Arc::clone(&order);
+            workers.push(thread::spawn(move || {
+                let _turn = DISPATCH_QUEUE.wait_turn(cmd);
+                order.lock().unwrap().push(cmd);
+            }));
+            while queue_stats().waiting <= before {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        // The running dispatch is not preempted; the ping then goes first
+        assert_eq!(queue_stats().active.as_deref(), Some("test.first"));
+        drop(first);
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), vec!["system.ping", "restore.start"]);
     }
 }
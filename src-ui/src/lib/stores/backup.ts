@@ -13,6 +13,7 @@ interface BackupState {
     message: string;
     taskId: string | null;
     error: string | null;
+    errorCode: string | null;
 }
 
 const initialState: BackupState = {
@@ -23,9 +24,12 @@ const initialState: BackupState = {
     eta: '',
     message: '',
     taskId: null,
-    error: null
+    error: null,
+    errorCode: null
 };
 
+const TERMINAL_PHASES = ['done', 'cancelled', 'error'];
+
 function createBackupStore() {
     const { subscribe, set, update } = writable<BackupState>(initialState);
     let unlisten: UnlistenFn | null = null;
@@ -34,7 +38,7 @@ function createBackupStore() {
         subscribe,
 
         // Start backup - Hybrid Flow
-        start: async (targetDir?: string) => {
+        start: async (targetDir?: string, channel: string = 'backup_progress') => {
             // Reset state
             set({
                 ...initialState,
@@ -46,19 +50,19 @@ function createBackupStore() {
             try {
                 // COMMAND: Get TaskID immediately (no blocking)
                 const taskId = await invoke<string>('cmd_backup_start', {
-                    targetDir: targetDir || null
+                    targetDir: targetDir || null,
+                    channel
                 });
 
                 update(s => ({ ...s, taskId }));
 
-                // EVENT: Listen to progress stream
-                if (unlisten) unlisten();
-
-                unlisten = await listen<BackupPayload>('backup_progress', (event) => {
-                    const payload = event.payload;
-
+                // Events may arrive twice (replay and live stream); apply
+                // each seq once, in order
+                let lastSeq = -1;
+                const apply = (payload: BackupPayload) => {
                     // Filter: Only process our task
-                    if (payload.task_id !== get({ subscribe }).taskId) return;
+                    if (payload.task_id !== taskId || payload.seq <= lastSeq) return;
+                    lastSeq = payload.seq;
 
                     update(s => ({
                         ...s,
@@ -68,17 +72,27 @@ function createBackupStore() {
                         eta: payload.eta,
                         message: payload.msg,
                         error: payload.phase === 'error' ? payload.msg : null, // msg contains error when phase is 'error'
-                        isProcessing: payload.phase !== 'done' && payload.phase !== 'error'
+                        errorCode: payload.error_code,
+                        isProcessing: !TERMINAL_PHASES.includes(payload.phase)
                     }));
 
                     // Cleanup listener when done
-                    if (payload.phase === 'done' || payload.phase === 'error') {
+                    if (TERMINAL_PHASES.includes(payload.phase)) {
                         if (unlisten) {
                             unlisten();
                             unlisten = null;
                         }
                     }
-                });
+                };
+
+                // EVENT: Listen to progress stream
+                if (unlisten) unlisten();
+
+                unlisten = await listen<BackupPayload>(channel, (event) => apply(event.payload));
+
+                // Replay whatever the worker emitted before we were listening
+                const missed = await invoke<BackupPayload[]>('cmd_backup_subscribe', { taskId });
+                missed.forEach(apply);
 
             } catch (err) {
                 set({
@@ -90,6 +104,13 @@ function createBackupStore() {
             }
         },
 
+        // Cancel in-flight backup; worker emits final 'cancelled' phase
+        cancel: async () => {
+            const { taskId, isProcessing } = get({ subscribe });
+            if (!taskId || !isProcessing) return;
+            await invoke('cmd_backup_cancel', { taskId });
+        },
+
         reset: () => {
             if (unlisten) {
                 unlisten();
@@ -33,10 +33,13 @@ function createRecoveryStore() {
             try {
                 // BLIND PROTOCOL: Only receive SVG, never mnemonic
                 const response = await invoke<ExportResp>('cmd_export_recovery_svg', { auth });
+                const dataUri = await invoke<string>('cmd_fetch_recovery_image', {
+                    token: response.token
+                });
 
                 set({
                     isLoading: false,
-                    dataUri: response.data_uri,
+                    dataUri,
                     ttlSeconds: response.ttl_seconds,
                     remainingSeconds: response.ttl_seconds,
                     error: null
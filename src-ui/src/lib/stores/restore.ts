@@ -0,0 +1,54 @@
+// Restore Flow - Hybrid Command-Event Pattern (same as backup, ADR-008)
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { RestorePayload, RestoreResult } from '../types/ipc';
+
+// Start a restore and resolve with Python's final reply once the worker
+// emits 'done' (null on 'cancelled', reject on 'error'). onProgress sees
+// every event of this task. With dryRun the result only lists items and
+// conflicts; nothing is written. targetDir overrides the restore target.
+export async function restoreFile(
+    filePath: string,
+    onProgress?: (payload: RestorePayload) => void,
+    dryRun = false,
+    targetDir?: string
+): Promise<RestoreResult | null> {
+    const pending: RestorePayload[] = [];
+    let taskId: string | null = null;
+    let settle: ((payload: RestorePayload) => void) | null = null;
+
+    // Listen before invoking so early events are not missed
+    const unlisten = await listen<RestorePayload>('restore_progress', (event) => {
+        if (taskId === null) {
+            pending.push(event.payload);
+        } else if (event.payload.task_id === taskId) {
+            settle?.(event.payload);
+        }
+    });
+
+    try {
+        return await new Promise((resolve, reject) => {
+            settle = (payload) => {
+                onProgress?.(payload);
+                if (payload.phase === 'done') resolve(payload.result);
+                if (payload.phase === 'cancelled') resolve(null);
+                if (payload.phase === 'error') reject(payload.msg);
+            };
+
+            invoke<RestoreResult>('cmd_restore_from_file', { filePath, dryRun, targetDir })
+                .then(({ task_id: id }) => {
+                    taskId = id;
+                    pending.filter((p) => p.task_id === id).forEach((p) => settle?.(p));
+                })
+                .catch(reject);
+        });
+    } finally {
+        unlisten();
+    }
+}
+
+// Ask the worker of taskId (from the first progress event) to stop; the
+// Python handler checks at its next step and the task ends in 'cancelled'
+export async function cancelRestore(taskId: string): Promise<void> {
+    await invoke('cmd_dispatch_cancel', { taskId });
+}
@@ -3,17 +3,40 @@
 // Backup Progress Event (from Rust worker thread)
 export interface BackupPayload {
     task_id: string;
-    phase: 'init' | 'snapshot' | 'encrypting' | 'finalizing' | 'done' | 'error';
+    seq: number;            // Per-task event number, for deduplicating replays
+    phase: 'init' | 'snapshot' | 'copying' | 'encrypting' | 'finalizing' | 'done' | 'cancelled' | 'error';
     progress: number;       // 0.0 - 100.0
     speed: string;          // "45 MB/s"
     eta: string;            // "10-15s" range
     msg: string;            // Error message when phase === 'error'
+    error_code: string | null; // e.g. "PERMISSION_DENIED" when phase === 'error'
+}
+
+// Restore Progress Event (from Rust worker, phases reported by Python)
+export interface RestorePayload {
+    task_id: string;
+    phase: string;          // 'init' | 'validating' | Python phase | 'done' | 'cancelled' | 'error'
+    progress: number;       // 0.0 - 100.0
+    msg: string;
+    result: RestoreResult | null; // Python reply when phase === 'done'
+    sha256: string | null;  // Archive hash when phase === 'done'
+}
+
+// Restore command reply ('started' handshake) and final result
+export interface RestoreResult {
+    status: 'started' | 'success' | 'cancelled' | 'error';
+    restored_items: number;
+    message: string;
+    task_id: string | null;
+    dry_run: boolean;       // Preview only: nothing was written
+    items: string[];        // Items a dry run would restore
+    conflicts: string[];    // Items that already exist at the target
 }
 
 // Recovery SVG Response (Blind Protocol)
 export interface ExportResp {
-    data_uri: string;       // "data:image/svg+xml;base64,..."
-    ttl_seconds: number;    // 60
+    token: string;          // Pass once to cmd_fetch_recovery_image
+    ttl_seconds: number;    // 60 - image is wiped in Rust afterwards
 }
 
 // Command Result Types
@@ -3,7 +3,21 @@ Command Dispatcher - Routes commands to appropriate services.
 
 Implements routing logic for Hybrid SSOT architecture.
 """
-from typing import Dict, Any
+from pathlib import Path
+from typing import Dict, Any, Callable
+
+
+# Progress reporter injected by the Rust bridge as envelope["progress"]
+ProgressCallback = Callable[[Dict[str, Any]], None]
+
+
+def _no_progress(update: Dict[str, Any]) -> None:
+    """Default reporter when the caller does not stream progress."""
+
+
+def _never_cancelled() -> bool:
+    """Default cancellation check when the caller cannot cancel."""
+    return False
 
 
 class Dispatcher:
@@ -25,7 +39,12 @@ class Dispatcher:
         Route command to appropriate handler.
         
         Args:
-            envelope: Command envelope with 'cmd' and 'payload' keys
+            envelope: Command envelope with 'cmd' and 'payload' keys, an
+                optional 'progress' callable for streaming updates and an
+                optional 'data' bytes buffer, handed to the handler as
+                payload["data"]; cancellable dispatches add a 'cancelled'
+                callable, handed over as payload["cancelled"]. Cancellation
+                is cooperative: only handlers that check it can stop early
             
         Returns:
             Result dictionary with 'status' and additional data
@@ -57,12 +76,20 @@ class Dispatcher:
                 return self._error(f"Unknown service: {service_name}")
             
             # Delegate to service handler
-            return self._services[service_name](action, envelope["payload"])
+            payload = envelope["payload"]
+            if envelope.get("data") is not None:
+                payload = {**payload, "data": envelope["data"]}
+            if envelope.get("cancelled") is not None:
+                payload = {**payload, "cancelled": envelope["cancelled"]}
+            progress = envelope.get("progress") or _no_progress
+            return self._services[service_name](action, payload, progress)
             
         except Exception as e:
             return self._error(f"Dispatcher error: {str(e)}")
     
-    def _handle_backup(self, action: str, payload: Dict[str, Any]) -> Dict[str, Any]:
+    def _handle_backup(
+        self, action: str, payload: Dict[str, Any], progress: ProgressCallback
+    ) -> Dict[str, Any]:
         """Handle backup service commands."""
         if action == "start":
             # Validate payload
@@ -78,7 +105,9 @@ class Dispatcher:
         
         return self._error(f"Unknown backup action: {action}")
     
-    def _handle_restore(self, action: str, payload: Dict[str, Any]) -> Dict[str, Any]:
+    def _handle_restore(
+        self, action: str, payload: Dict[str, Any], progress: ProgressCallback
+    ) -> Dict[str, Any]:
         """Handle restore service commands."""
         if action == "start":
             # Validate payload (FIXED: match Rust parameter name 'file_path')
@@ -87,18 +116,54 @@ class Dispatcher:
             
             path = payload["file_path"]  # FIXED: use correct key
             print(f"🐍 [PYTHON] Restore command received! Path: {path}")
+            progress({"phase": "validating", "percent": 10, "message": f"Validating {path}"})
+            
+            if payload.get("dry_run"):
+                return self._preview_restore(path, payload.get("target_dir"))
+            
+            # Last point to stop before anything is written
+            cancelled = payload.get("cancelled") or _never_cancelled
+            if cancelled():
+                return {
+                    "status": "cancelled",
+                    "message": "Restore cancelled",
+                    "file_path": path,
+                }
             
             # TODO: Implement actual restore logic in Sprint 6
             # For now, return success to confirm E2E connection works
+            progress({"phase": "restoring", "percent": 90, "message": "Restoring vault..."})
             return {
                 "status": "success",
                 "task_id": "restore_001",
                 "message": f"Restore initiated for {path}",
-                "file_path": path
+                "file_path": path,
+                "dry_run": False
             }
         
         return self._error(f"Unknown restore action: {action}")
     
+    def _preview_restore(self, path: str, target_dir: str | None) -> Dict[str, Any]:
+        """List what a restore would write, and which of it already exists, without writing."""
+        from core.services.backup import BackupError, list_pack_entries
+
+        try:
+            items = list_pack_entries(path)
+        except BackupError as e:
+            return self._error(f"Restore preview failed: {e}")
+
+        conflicts = [name for name in items if target_dir and (Path(target_dir) / name).exists()]
+        return {
+            "status": "success",
+            "task_id": "restore_001",
+            "message": f"Dry run: {len(items)} items would be restored, {len(conflicts)} conflicts",
+            "file_path": path,
+            "dry_run": True,
+            "items": items,
+            "conflicts": conflicts,
+            "restored_items": 0
+        }
+    
     def _error(self, message: str) -> Dict[str, Any]:
         """Create error response."""
         return {
@@ -0,0 +1,103 @@
+//! Extraction throughput of the pure-Rust core
+//!
+//! Fixtures are built in memory with docx-rs, so `cargo bench` needs no
+//! files on disk. Throughput is reported per input byte.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use docx_extractor::{extract, ExtractOptions};
+
+fn paragraph(text: &str) -> docx_rs::Paragraph {
+    docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text))
+}
+
+fn pack(docx: docx_rs::Docx) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    docx.build()
+        .pack(&mut std::io::Cursor::new(&mut buffer))
+        .expect("fixture packs");
+    buffer
+}
+
+fn prose(paragraphs: usize) -> Vec<u8> {
+    let docx = (0..paragraphs).fold(docx_rs::Docx::new(), |docx, i| {
+        docx.add_paragraph(paragraph(&format!(
+            "Paragraph {} of the benchmark fixture, long enough to resemble body text \
+             with a few clauses, numbers like {} and some punctuation.",
+            i,
+            i * 7
+        )))
+    });
+    pack(docx)
+}
+
+fn tables(count: usize, rows: usize, cols: usize) -> Vec<u8> {
+    let table = |t: usize| {
+        docx_rs::Table::new(
+            (0..rows)
+                .map(|r| {
+                    docx_rs::TableRow::new(
+                        (0..cols)
+                            .map(|c| {
+                                docx_rs::TableCell::new()
+                                    .add_paragraph(paragraph(&format!("T{} R{} C{}", t, r, c)))
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        )
+    };
+    let docx = (0..count).fold(docx_rs::Docx::new(), |docx, t| {
+        docx.add_paragraph(paragraph(&format!("Table {}", t)))
+            .add_table(table(t))
+    });
+    pack(docx)
+}
+
+fn lists(items: usize) -> Vec<u8> {
+    let level = |ilvl: usize, format: &str, text: &str| {
+        docx_rs::Level::new(
+            ilvl,
+            docx_rs::Start::new(1),
+            docx_rs::NumberFormat::new(format),
+            docx_rs::LevelText::new(text),
+            docx_rs::LevelJc::new("left"),
+        )
+    };
+    let docx = docx_rs::Docx::new()
+        .add_abstract_numbering(
+            docx_rs::AbstractNumbering::new(1)
+                .add_level(level(0, "decimal", "%1."))
+                .add_level(level(1, "lowerLetter", "%2)"))
+                .add_level(level(2, "lowerRoman", "%3.")),
+        )
+        .add_numbering(docx_rs::Numbering::new(1, 1));
+    let docx = (0..items).fold(docx, |docx, i| {
+        docx.add_paragraph(paragraph(&format!("List item {}", i)).numbering(
+            docx_rs::NumberingId::new(1),
+            docx_rs::IndentLevel::new(i % 3),
+        ))
+    });
+    pack(docx)
+}
+
+fn bench_extract(c: &mut Criterion) {
+    let fixtures = [
+        ("small", prose(20)),
+        ("large", prose(5_000)),
+        ("table_heavy", tables(50, 20, 6)),
+        ("list_heavy", lists(3_000)),
+    ];
+
+    let mut group = c.benchmark_group("extract");
+    for (name, buffer) in &fixtures {
+        group.throughput(Throughput::Bytes(buffer.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), buffer, |b, buffer| {
+            b.iter(|| extract(buffer, ExtractOptions::default()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_extract);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+//! Expose the resolved docx-rs version as `DOCX_RS_VERSION`
+//!
+//! Cargo only provides the crate's own version, so the dependency's is read
+//! from Cargo.lock.
+
+use std::{env, fs, path::Path};
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let lock_path = Path::new(&manifest_dir).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+
+    let lock = fs::read_to_string(&lock_path).unwrap_or_default();
+    let version = lock
+        .split("[[package]]")
+        .find(|package| package.contains("name = \"docx-rs\""))
+        .and_then(|package| {
+            package
+                .lines()
+                .find_map(|line| line.strip_prefix("version = "))
+                .map(|v| v.trim_matches('"').to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=DOCX_RS_VERSION={}", version);
+}
@@ -0,0 +1,202 @@
+//! Symbol-font glyphs and typographic punctuation
+//!
+//! Word stores characters inserted from symbol fonts as `w:sym` elements:
+//! a font name plus a hex character code, usually in the U+F0xx
+//! private-use range that the font maps onto its own glyphs. These helpers
+//! resolve the common ones to Unicode and fold typographic punctuation to
+//! ASCII for consumers that mangle it.
+
+/// Stands in for symbols without a known Unicode equivalent
+pub(crate) const UNKNOWN_SYMBOL: char = '\u{FFFD}';
+
+/// Greek letters of the Symbol font at `A`..=`Z` and `a`..=`z`
+const SYMBOL_UPPER: &str = "ΑΒΧΔΕΦΓΗΙϑΚΛΜΝΟΠΘΡΣΤΥςΩΞΨΖ";
+const SYMBOL_LOWER: &str = "αβχδεφγηιϕκλμνοπθρστυϖωξψζ";
+
+/// Symbol font codes that are neither letters nor plain ASCII
+const SYMBOL_CHARS: [(u8, char); 57] = [
+    (0x22, '∀'),
+    (0x24, '∃'),
+    (0x27, '∋'),
+    (0x2A, '∗'),
+    (0x2D, '−'),
+    (0x40, '≅'),
+    (0x5C, '∴'),
+    (0x5E, '⊥'),
+    (0x7E, '∼'),
+    (0xA1, 'ϒ'),
+    (0xA2, '′'),
+    (0xA3, '≤'),
+    (0xA4, '⁄'),
+    (0xA5, '∞'),
+    (0xA6, 'ƒ'),
+    (0xA7, '♣'),
+    (0xA8, '♦'),
+    (0xA9, '♥'),
+    (0xAA, '♠'),
+    (0xAB, '↔'),
+    (0xAC, '←'),
+    (0xAD, '↑'),
+    (0xAE, '→'),
+    (0xAF, '↓'),
+    (0xB0, '°'),
+    (0xB1, '±'),
+    (0xB2, '″'),
+    (0xB3, '≥'),
+    (0xB4, '×'),
+    (0xB5, '∝'),
+    (0xB6, '∂'),
+    (0xB7, '•'),
+    (0xB8, '÷'),
+    (0xB9, '≠'),
+    (0xBA, '≡'),
+    (0xBB, '≈'),
+    (0xBC, '…'),
+    (0xC5, '⊕'),
+    (0xC6, '∅'),
+    (0xC7, '∩'),
+    (0xC8, '∪'),
+    (0xCC, '⊂'),
+    (0xCE, '∈'),
+    (0xCF, '∉'),
+    (0xD0, '∠'),
+    (0xD1, '∇'),
+    (0xD2, '®'),
+    (0xD3, '©'),
+    (0xD4, '™'),
+    (0xD5, '∏'),
+    (0xD6, '√'),
+    (0xD7, '⋅'),
+    (0xD8, '¬'),
+    (0xDB, '⇔'),
+    (0xDE, '⇒'),
+    (0xE5, '∑'),
+    (0xF2, '∫'),
+];
+
+/// Wingdings codes commonly used for bullets and check boxes
+const WINGDINGS_CHARS: [(u8, char); 16] = [
+    (0x28, '☎'),
+    (0x2A, '✉'),
+    (0x4A, '☺'),
+    (0x4C, '☹'),
+    (0x6C, '●'),
+    (0x6E, '■'),
+    (0x71, '❑'),
+    (0x75, '◆'),
+    (0x76, '❖'),
+    (0xA7, '▪'),
+    (0xA8, '◻'),
+    (0xD8, '➢'),
+    (0xFB, '✗'),
+    (0xFC, '✓'),
+    (0xFD, '☒'),
+    (0xFE, '☑'),
+];
+
+/// Unicode equivalent of a `w:sym` glyph, or [`UNKNOWN_SYMBOL`]
+///
+/// `code` is the `w:char` attribute in hex. Codes outside the private-use
+/// area are ordinary characters whatever the font.
+pub(crate) fn resolve_sym(font: &str, code: &str) -> char {
+    let Ok(code) = u32::from_str_radix(code, 16) else {
+        return UNKNOWN_SYMBOL;
+    };
+    if !(0xE000..=0xF8FF).contains(&code) {
+        return char::from_u32(code).unwrap_or(UNKNOWN_SYMBOL);
+    }
+    // Symbol fonts map U+F020..U+F0FF onto their 8-bit code page
+    let Some(byte) = code.checked_sub(0xF000).and_then(|b| u8::try_from(b).ok()) else {
+        return UNKNOWN_SYMBOL;
+    };
+
+    let glyph = match font.to_ascii_lowercase().as_str() {
+        "symbol" => symbol_char(byte),
+        "wingdings" => lookup(&WINGDINGS_CHARS, byte),
+        _ => None,
+    };
+    glyph.unwrap_or(UNKNOWN_SYMBOL)
+}
+
+fn lookup(table: &[(u8, char)], byte: u8) -> Option<char> {
+    table.iter().find(|(b, _)| *b == byte).map(|(_, c)| *c)
+}
+
+fn symbol_char(byte: u8) -> Option<char> {
+    match byte {
+        b'A'..=b'Z' => SYMBOL_UPPER.chars().nth((byte - b'A') as usize),
+        b'a'..=b'z' => SYMBOL_LOWER.chars().nth((byte - b'a') as usize),
+        _ => lookup(&SYMBOL_CHARS, byte)
+            .or_else(|| (byte.is_ascii_graphic() || byte == b' ').then_some(byte as char)),
+    }
+}
+
+/// ASCII approximation of smart quotes, dashes, ellipses and special spaces
+fn fold_char(c: char) -> Option<&'static str> {
+    Some(match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => "'",
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' => "\"",
+        '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2212}' => "-",
+        '\u{2014}' | '\u{2015}' => "--",
+        '\u{2026}' => "...",
+        '\u{00A0}' | '\u{2007}' | '\u{2009}' | '\u{202F}' => " ",
+        _ => return None,
+    })
+}
+
+/// [`fold_char`] applied to `text`, also returning where each byte offset
+/// of `text` (including its end) lands in the folded string
+pub(crate) fn fold_ascii_mapped(text: &str) -> (String, Vec<usize>) {
+    let mut folded = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+
+    for c in text.chars() {
+        offsets.extend(std::iter::repeat_n(folded.len(), c.len_utf8()));
+        match fold_char(c) {
+            Some(ascii) => folded.push_str(ascii),
+            None => folded.push(c),
+        }
+    }
+    offsets.push(folded.len());
+
+    (folded, offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_sym() {
+        assert_eq!(resolve_sym("Symbol", "F061"), 'α');
+        assert_eq!(resolve_sym("Symbol", "F057"), 'Ω');
+        assert_eq!(resolve_sym("Symbol", "F0B7"), '•');
+        assert_eq!(resolve_sym("Symbol", "F0B1"), '±');
+        assert_eq!(resolve_sym("Symbol", "F033"), '3');
+        assert_eq!(resolve_sym("Wingdings", "F0FC"), '✓');
+        assert_eq!(resolve_sym("wingdings", "F0A7"), '▪');
+        // Plain code points need no font table
+        assert_eq!(resolve_sym("Arial", "00E9"), 'é');
+
+        assert_eq!(resolve_sym("Webdings", "F021"), UNKNOWN_SYMBOL);
+        assert_eq!(resolve_sym("Wingdings", "F021"), UNKNOWN_SYMBOL);
+        assert_eq!(resolve_sym("Symbol", "zz"), UNKNOWN_SYMBOL);
+    }
+
+    #[test]
+    fn test_fold_ascii_mapped() {
+        let text = "“Quote” — it’s…\u{00A0}done";
+        let (folded, offsets) = fold_ascii_mapped(text);
+        assert_eq!(folded, "\"Quote\" -- it's... done");
+        assert_eq!(offsets.len(), text.len() + 1);
+        assert_eq!(offsets[text.find('Q').unwrap()], 1);
+        assert_eq!(
+            offsets[text.find("it").unwrap()],
+            folded.find("it").unwrap()
+        );
+        assert_eq!(offsets[text.len()], folded.len());
+
+        let (plain, _) = fold_ascii_mapped("plain ascii");
+        assert_eq!(plain, "plain ascii");
+    }
+}
@@ -2,52 +2,315 @@
 //! Task 6.4 - Sprint 6 Background Services
 //!
 //! Uses docx-rs for high-performance DOCX text extraction.
-//! Exposes to Python via PyO3 bindings.
+//! Exposes to Python via PyO3 bindings; the core (`extract` with
+//! `ExtractOptions`, returning `ExtractionData`) is plain Rust and can be
+//! called without Python.
 
+use pyo3::exceptions::{PyAttributeError, PyRuntimeError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyDict, PyList};
+use pyo3::IntoPyObjectExt;
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::Read;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+mod encryption;
+mod glyphs;
+mod ooxml;
 
 /// Text segment with metadata
 #[pyclass]
-#[derive(Clone)]
-struct TextSegment {
+#[derive(Clone, Default, Serialize)]
+pub struct TextSegment {
+    #[pyo3(get)]
+    pub text: String,
+    /// Estimated 1-based page, counted from explicit page breaks, rendered
+    /// page-break hints and section breaks. DOCX stores no real pagination,
+    /// so this is a best-effort value; None for headers/footers.
+    #[pyo3(get)]
+    pub page: Option<i32>,
+    #[pyo3(get)]
+    pub section: Option<String>,
+    #[pyo3(get)]
+    pub confidence: f64,
+    /// (display text, resolved URL) for each hyperlink in the segment
+    #[pyo3(get)]
+    pub links: Vec<(String, String)>,
+    /// List nesting level (`ilvl`) for numbered/bulleted paragraphs
+    #[pyo3(get)]
+    pub list_level: Option<i32>,
+    /// Body child index (as in `paragraph_{idx}`) this segment is attached to,
+    /// e.g. where a footnote marker appeared
+    #[pyo3(get)]
+    pub anchor_paragraph: Option<i32>,
+    /// `"inserted"` / `"deleted"` for tracked-change text, else None
+    #[pyo3(get)]
+    pub change_type: Option<String>,
+    /// Outline level 1-9 for paragraphs styled as headings
+    #[pyo3(get)]
+    pub heading_level: Option<u8>,
+    /// Relationship id of the image an `image_alt` segment describes
+    #[pyo3(get)]
+    pub media_id: Option<String>,
+    /// Author of a `comment` segment
+    #[pyo3(get)]
+    pub comment_author: Option<String>,
+    /// Body child index of the paragraph the comment range starts in
+    #[pyo3(get)]
+    pub comment_ref_paragraph: Option<usize>,
+    /// Id of the parent comment for threaded replies
+    #[pyo3(get)]
+    pub reply_to: Option<String>,
+    /// Segment contains underlined text (only set with `emphasis_markers`)
+    #[pyo3(get)]
+    pub underline: bool,
+    /// Paragraph style name (e.g. "Quote"); None for the default style
+    #[pyo3(get)]
+    pub style: Option<String>,
+    /// Base direction, `"ltr"` or `"rtl"`: the paragraph's `w:bidi`
+    /// property, else the first strongly directional character; None when
+    /// the text has none
     #[pyo3(get)]
-    text: String,
+    pub direction: Option<String>,
+    /// Field instruction (`"TOC"`, `"PAGEREF"`, ...) of a `field` segment,
+    /// whose text is the field's cached result
     #[pyo3(get)]
-    page: Option<i32>,
+    pub field_type: Option<String>,
+    /// Formatting runs within `text` (only set with `detailed_runs`)
     #[pyo3(get)]
-    section: Option<String>,
+    pub runs: Vec<RunSpan>,
+    /// Body child index the segment was extracted from (paragraphs,
+    /// tables and what is drawn in them); None for headers, footers,
+    /// notes and comments
     #[pyo3(get)]
-    confidence: f64,
+    pub source_index: Option<usize>,
+    /// Characters in all segments before this one
+    #[pyo3(get)]
+    pub char_offset: usize,
+    /// Language tag (`"en-US"`, ...): the `w:lang` of most of the
+    /// segment's own runs, else the document default
+    #[pyo3(get)]
+    pub language: Option<String>,
+    /// Sections a header/footer segment's text appears in (identical
+    /// headers/footers are emitted once); None for other segments
+    #[pyo3(get)]
+    pub repeat_count: Option<u32>,
+    /// `w:tag` (else `w:alias`) of the content control the text was
+    /// entered in, e.g. a form field's name
+    #[pyo3(get)]
+    pub sdt_tag: Option<String>,
 }
 
 #[pymethods]
 impl TextSegment {
     #[new]
-    #[pyo3(signature = (text, page=None, section=None, confidence=1.0))]
-    fn new(text: String, page: Option<i32>, section: Option<String>, confidence: f64) -> Self {
+    #[pyo3(signature = (text, page=None, section=None, confidence=1.0, links=Vec::new(), list_level=None, anchor_paragraph=None, change_type=None, heading_level=None, media_id=None, comment_author=None, comment_ref_paragraph=None, reply_to=None, underline=false, style=None, direction=None, field_type=None, runs=Vec::new(), source_index=None, char_offset=0, language=None, repeat_count=None, sdt_tag=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        text: String,
+        page: Option<i32>,
+        section: Option<String>,
+        confidence: f64,
+        links: Vec<(String, String)>,
+        list_level: Option<i32>,
+        anchor_paragraph: Option<i32>,
+        change_type: Option<String>,
+        heading_level: Option<u8>,
+        media_id: Option<String>,
+        comment_author: Option<String>,
+        comment_ref_paragraph: Option<usize>,
+        reply_to: Option<String>,
+        underline: bool,
+        style: Option<String>,
+        direction: Option<String>,
+        field_type: Option<String>,
+        runs: Vec<RunSpan>,
+        source_index: Option<usize>,
+        char_offset: usize,
+        language: Option<String>,
+        repeat_count: Option<u32>,
+        sdt_tag: Option<String>,
+    ) -> Self {
         TextSegment {
             text,
             page,
             section,
             confidence,
+            links,
+            list_level,
+            anchor_paragraph,
+            change_type,
+            heading_level,
+            media_id,
+            comment_author,
+            comment_ref_paragraph,
+            reply_to,
+            underline,
+            style,
+            direction,
+            field_type,
+            runs,
+            source_index,
+            char_offset,
+            language,
+            repeat_count,
+            sdt_tag,
+        }
+    }
+}
+
+impl TextSegment {
+    /// Full-confidence segment with no page or links attached
+    fn with_section(text: String, section: String) -> Self {
+        TextSegment {
+            text,
+            section: Some(section),
+            confidence: 1.0,
+            ..Default::default()
+        }
+    }
+}
+
+/// Formatting of a byte range of a segment's text
+#[pyclass]
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct RunSpan {
+    /// Byte offsets into `TextSegment.text`
+    #[pyo3(get)]
+    pub start: usize,
+    #[pyo3(get)]
+    pub end: usize,
+    #[pyo3(get)]
+    pub bold: bool,
+    #[pyo3(get)]
+    pub italic: bool,
+    /// Hex RGB (e.g. "FF0000"); None for automatic color
+    #[pyo3(get)]
+    pub color: Option<String>,
+}
+
+#[pymethods]
+impl RunSpan {
+    #[new]
+    #[pyo3(signature = (start, end, bold=false, italic=false, color=None))]
+    fn new(start: usize, end: usize, bold: bool, italic: bool, color: Option<String>) -> Self {
+        RunSpan {
+            start,
+            end,
+            bold,
+            italic,
+            color,
+        }
+    }
+}
+
+/// Move run spans after an insertion of `len` bytes at byte `at`
+///
+/// Text inserted at a span's start goes before it, not into it.
+fn shift_runs(runs: &mut [RunSpan], at: usize, len: usize) {
+    for run in runs {
+        if at <= run.start {
+            run.start += len;
+        }
+        if at < run.end || at <= run.start {
+            run.end += len;
+        }
+    }
+}
+
+/// Machine-readable failure reasons of an [`ExtractionError`]
+///
+/// Python sees the `as_str` form in `ExtractionError.code`; the full list
+/// is `ExtractionError.CODES`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    FileNotFound,
+    ReadError,
+    EmptyFile,
+    LegacyDocFormat,
+    Encrypted,
+    SizeLimitExceeded,
+    NotADocx,
+    ParserPanic,
+    Corrupted,
+    Cancelled,
+    Timeout,
+    Truncated,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 12] = [
+        ErrorCode::FileNotFound,
+        ErrorCode::ReadError,
+        ErrorCode::EmptyFile,
+        ErrorCode::LegacyDocFormat,
+        ErrorCode::Encrypted,
+        ErrorCode::SizeLimitExceeded,
+        ErrorCode::NotADocx,
+        ErrorCode::ParserPanic,
+        ErrorCode::Corrupted,
+        ErrorCode::Cancelled,
+        ErrorCode::Timeout,
+        ErrorCode::Truncated,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::FileNotFound => "FILE_NOT_FOUND",
+            ErrorCode::ReadError => "READ_ERROR",
+            ErrorCode::EmptyFile => "EMPTY_FILE",
+            ErrorCode::LegacyDocFormat => "LEGACY_DOC_FORMAT",
+            ErrorCode::Encrypted => "ENCRYPTED",
+            ErrorCode::SizeLimitExceeded => "SIZE_LIMIT_EXCEEDED",
+            ErrorCode::NotADocx => "NOT_A_DOCX",
+            ErrorCode::ParserPanic => "PARSER_PANIC",
+            ErrorCode::Corrupted => "CORRUPTED",
+            ErrorCode::Cancelled => "CANCELLED",
+            ErrorCode::Timeout => "TIMEOUT",
+            ErrorCode::Truncated => "TRUNCATED",
         }
     }
+
+    /// Inverse of `as_str`
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+
+    /// Limits the caller can raise, see [`ExtractionError`]
+    pub fn is_recoverable(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::Truncated | ErrorCode::Timeout | ErrorCode::SizeLimitExceeded
+        )
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Extraction error details
+///
+/// `recoverable` follows the error code: limits that were hit
+/// (TRUNCATED, TIMEOUT, SIZE_LIMIT_EXCEEDED) are recoverable and worth a
+/// retry with other parameters, with whatever was extracted still
+/// returned; codes describing the input itself (FILE_NOT_FOUND,
+/// CORRUPTED, ENCRYPTED, ...) are not.
 #[pyclass]
-#[derive(Clone)]
-struct ExtractionError {
+#[derive(Clone, Serialize)]
+pub struct ExtractionError {
     #[pyo3(get)]
-    code: String,
+    pub code: String,
     #[pyo3(get)]
-    message: String,
+    pub message: String,
     #[pyo3(get)]
-    recoverable: bool,
+    pub recoverable: bool,
 }
 
 #[pymethods]
@@ -60,6 +323,91 @@ impl ExtractionError {
             recoverable,
         }
     }
+
+    /// Every code the extractor can report, for matching on `code`
+    #[classattr]
+    #[pyo3(name = "CODES")]
+    fn codes() -> Vec<&'static str> {
+        ErrorCode::ALL.iter().map(|c| c.as_str()).collect()
+    }
+}
+
+impl ExtractionError {
+    /// Error with `recoverable` set by the code's policy
+    fn from_code(code: ErrorCode, message: String) -> Self {
+        ExtractionError::new(code.to_string(), message, code.is_recoverable())
+    }
+
+    /// `code` as an [`ErrorCode`]; None for codes set by other producers
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        ErrorCode::parse(&self.code)
+    }
+}
+
+/// A table cell with its merge spans
+#[pyclass]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TableCellSegment {
+    #[pyo3(get)]
+    pub text: String,
+    /// Grid columns covered (`gridSpan`)
+    #[pyo3(get)]
+    pub colspan: usize,
+    /// Rows covered, counting the `vMerge` continuation cells below
+    #[pyo3(get)]
+    pub rowspan: usize,
+}
+
+#[pymethods]
+impl TableCellSegment {
+    #[new]
+    #[pyo3(signature = (text, colspan=1, rowspan=1))]
+    fn new(text: String, colspan: usize, rowspan: usize) -> Self {
+        TableCellSegment {
+            text,
+            colspan,
+            rowspan,
+        }
+    }
+}
+
+/// Structured table content
+///
+/// `rows` has one string per grid column: horizontally merged cells
+/// (`gridSpan`) keep their value in the first column and leave the spanned
+/// columns empty; vertically merged continuation cells (`vMerge`) are empty
+/// as well. `cells` has the originating cells only, with their spans, so
+/// the grid can be rebuilt unambiguously.
+///
+/// `section` is `table_{n}` for the table at position `n` in
+/// `ExtractionResult.tables`; its flattened text segment carries the same
+/// section, at its place in the reading order.
+#[pyclass]
+#[derive(Clone, Serialize)]
+pub struct TableSegment {
+    #[pyo3(get)]
+    pub rows: Vec<Vec<String>>,
+    #[pyo3(get)]
+    pub section: Option<String>,
+    #[pyo3(get)]
+    pub cells: Vec<Vec<TableCellSegment>>,
+}
+
+#[pymethods]
+impl TableSegment {
+    #[new]
+    #[pyo3(signature = (rows, section=None, cells=Vec::new()))]
+    fn new(
+        rows: Vec<Vec<String>>,
+        section: Option<String>,
+        cells: Vec<Vec<TableCellSegment>>,
+    ) -> Self {
+        TableSegment {
+            rows,
+            section,
+            cells,
+        }
+    }
 }
 
 /// Extraction result
@@ -68,6 +416,8 @@ struct ExtractionResult {
     #[pyo3(get)]
     segments: Vec<TextSegment>,
     #[pyo3(get)]
+    tables: Vec<TableSegment>,
+    #[pyo3(get)]
     metadata: Py<PyDict>,
     #[pyo3(get)]
     processing_time_ms: f64,
@@ -77,195 +427,4659 @@ struct ExtractionResult {
     errors: Vec<ExtractionError>,
     #[pyo3(get)]
     truncated: bool,
+    /// Path the document was read from; None for the bytes-based API
+    #[pyo3(get)]
+    source_path: Option<String>,
     #[pyo3(get)]
     extractor: String,
     #[pyo3(get)]
     version: String,
+    /// SHA-256 (hex) of the normalized segment texts, for deduplication
+    #[pyo3(get)]
+    content_hash: String,
 }
 
-/// Extract text from DOCX file
-///
-/// Args:
-///     file_path: Path to DOCX file
-///
-/// Returns:
-///     ExtractionResult with text segments and metadata
-#[pyfunction]
-fn extract_docx(py: Python, file_path: String) -> PyResult<ExtractionResult> {
-    let start_time = Instant::now();
+#[pymethods]
+impl ExtractionResult {
+    /// Render the extracted content as Markdown
+    ///
+    /// Headings become `#` lines, list paragraphs `-` / `1.` items, tables
+    /// GitHub pipe tables (first row as header) and everything else plain
+    /// text blocks. Deleted tracked changes are struck through.
+    fn to_markdown(&self) -> String {
+        render_markdown(&self.segments, &self.tables)
+    }
 
-    // Get file size
-    let file_size = match std::fs::metadata(&file_path) {
-        Ok(metadata) => metadata.len() as i64,
-        Err(_) => 0,
-    };
+    /// Serialize the result as JSON, e.g. to cache it on disk
+    ///
+    /// Schema (`schema_version` 1): an object with `schema_version`,
+    /// `extractor`, `version`, `content_hash`, `processing_time_ms`,
+    /// `file_size_bytes`, `truncated`, `source_path`, `metadata` (as in `metadata`),
+    /// `errors` (`code`, `message`, `recoverable`), `segments` and
+    /// `tables`. Segments and tables carry every attribute under its
+    /// Python name; `links` are `[text, url]` pairs and `runs` / `cells`
+    /// nested objects. Fields are only ever added within a schema version.
+    fn to_json(&self, py: Python<'_>) -> PyResult<String> {
+        let metadata: String = py
+            .import("json")?
+            .call_method1("dumps", (self.metadata.bind(py),))?
+            .extract()?;
+        let metadata: Value = serde_json::from_str(&metadata)
+            .map_err(|e| PyValueError::new_err(format!("Invalid metadata: {}", e)))?;
 
-    // Create metadata dict
-    let metadata = PyDict::new(py);
+        let result = serde_json::json!({
+            "schema_version": JSON_SCHEMA_VERSION,
+            "extractor": self.extractor,
+            "version": self.version,
+            "content_hash": self.content_hash,
+            "processing_time_ms": self.processing_time_ms,
+            "file_size_bytes": self.file_size_bytes,
+            "truncated": self.truncated,
+            "source_path": self.source_path,
+            "metadata": metadata,
+            "errors": self.errors,
+            "segments": self.segments,
+            "tables": self.tables,
+        });
+        serde_json::to_string(&result)
+            .map_err(|e| PyValueError::new_err(format!("Failed to serialize result: {}", e)))
+    }
+}
 
-    // Try to read file
-    let mut file = match File::open(&file_path) {
-        Ok(f) => f,
-        Err(e) => {
-            // File not found or permission denied
-            let error = ExtractionError::new(
-                "FILE_NOT_FOUND".to_string(),
-                format!("Failed to open file: {}", e),
-                false,
-            );
+/// Version of the `ExtractionResult.to_json` schema; bumped on any
+/// change that is not a pure addition
+const JSON_SCHEMA_VERSION: u32 = 1;
 
-            return Ok(ExtractionResult {
-                segments: vec![],
-                metadata: metadata.unbind(),
-                processing_time_ms: start_time.elapsed().as_secs_f64() * 1000.0,
-                file_size_bytes: file_size,
-                errors: vec![error],
-                truncated: false,
-                extractor: "docx_rust".to_string(),
-                version: "1.0.0".to_string(),
-            });
-        }
+/// Escape a table cell for a pipe table row
+fn markdown_cell(cell: &str) -> String {
+    cell.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+/// GitHub pipe table for `rows`, the first row as header; None if empty
+fn markdown_table(rows: &[Vec<String>]) -> Option<String> {
+    let columns = rows.iter().map(Vec::len).max().filter(|&n| n > 0)?;
+    let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+    let row = |r: &Vec<String>| {
+        line(
+            (0..columns)
+                .map(|i| markdown_cell(r.get(i).map_or("", String::as_str)))
+                .collect(),
+        )
     };
 
-    // Read file content
-    let mut buffer = Vec::new();
-    if let Err(e) = file.read_to_end(&mut buffer) {
-        let error = ExtractionError::new(
-            "READ_ERROR".to_string(),
-            format!("Failed to read file: {}", e),
-            false,
-        );
+    let mut lines = vec![row(&rows[0]), line(vec!["---".to_string(); columns])];
+    lines.extend(rows[1..].iter().map(row));
+    Some(lines.join("\n"))
+}
 
-        return Ok(ExtractionResult {
-            segments: vec![],
-            metadata: metadata.unbind(),
-            processing_time_ms: start_time.elapsed().as_secs_f64() * 1000.0,
-            file_size_bytes: file_size,
-            errors: vec![error],
-            truncated: false,
-            extractor: "docx_rust".to_string(),
-            version: "1.0.0".to_string(),
-        });
+/// Markdown list item for a segment whose text starts with its rendered
+/// list marker (`"\u{2022} "`, `"1. "`, `"a) "`, ...)
+fn markdown_list_item(text: &str, level: i32) -> String {
+    let indent = "    ".repeat(level.max(0) as usize);
+    match text.strip_prefix("\u{2022} ") {
+        Some(item) => format!("{}- {}", indent, item),
+        None => {
+            // Word numbering formats have no Markdown equivalent; the
+            // renderer renumbers ordered items anyway
+            let item = text.split_once(' ').map_or(text, |(_, item)| item);
+            format!("{}1. {}", indent, item)
+        }
     }
+}
 
-    // Parse DOCX
-    let docx = match docx_rs::read_docx(&buffer) {
-        Ok(d) => d,
-        Err(e) => {
-            let error = ExtractionError::new(
-                "CORRUPTED".to_string(),
-                format!("Failed to parse DOCX: {}", e),
-                false,
-            );
+fn render_markdown(segments: &[TextSegment], tables: &[TableSegment]) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut in_list = false;
+    let mut rendered_tables = Vec::new();
 
-            return Ok(ExtractionResult {
-                segments: vec![],
-                metadata: metadata.unbind(),
-                processing_time_ms: start_time.elapsed().as_secs_f64() * 1000.0,
-                file_size_bytes: file_size,
-                errors: vec![error],
-                truncated: false,
-                extractor: "docx_rust".to_string(),
-                version: "1.0.0".to_string(),
-            });
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
         }
-    };
 
-    // Extract text from document
-    let mut segments = Vec::new();
-    let mut errors = Vec::new();
+        let table = segment
+            .section
+            .as_ref()
+            .filter(|s| s.starts_with("table_"))
+            .and_then(|s| tables.iter().position(|t| t.section.as_ref() == Some(s)));
+        let block = if let Some(index) = table {
+            rendered_tables.push(index);
+            markdown_table(&tables[index].rows).unwrap_or_else(|| text.to_string())
+        } else if let Some(level) = segment.heading_level {
+            format!("{} {}", "#".repeat(level.clamp(1, 6) as usize), text)
+        } else if let Some(level) = segment.list_level {
+            let item = markdown_list_item(text, level);
+            match blocks.last_mut() {
+                // Consecutive items form one tight list
+                Some(list) if in_list => {
+                    list.push('\n');
+                    list.push_str(&item);
+                }
+                _ => blocks.push(item),
+            }
+            in_list = true;
+            continue;
+        } else if segment.change_type.as_deref() == Some("deleted") {
+            format!("~~{}~~", text)
+        } else {
+            text.to_string()
+        };
+        blocks.push(block);
+        in_list = false;
+    }
 
-    // Extract paragraphs
-    for (idx, child) in docx.document.children.iter().enumerate() {
-        match child {
-            docx_rs::DocumentChild::Paragraph(para) => {
-                let mut para_text = String::new();
+    // Tables without a flattened segment (flatten_tables=False) go last
+    for (index, table) in tables.iter().enumerate() {
+        if !rendered_tables.contains(&index) {
+            blocks.extend(markdown_table(&table.rows));
+        }
+    }
 
-                for run_child in &para.children {
-                    if let docx_rs::ParagraphChild::Run(run) = run_child {
-                        for run_child in &run.children {
-                            if let docx_rs::RunChild::Text(text) = run_child {
-                                para_text.push_str(&text.text);
-                            }
+    blocks.join("\n\n")
+}
+
+/// Tracked-change state of a run
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Revision {
+    Inserted,
+    Deleted,
+}
+
+impl Revision {
+    /// Value exposed as `TextSegment.change_type`
+    fn change_type(self) -> &'static str {
+        match self {
+            Revision::Inserted => "inserted",
+            Revision::Deleted => "deleted",
+        }
+    }
+}
+
+/// Collect runs from paragraph-level children, descending into hyperlinks
+/// and `w:ins` / `w:del` wrappers
+fn collect_runs<'a>(
+    children: &'a [docx_rs::ParagraphChild],
+    revision: Option<Revision>,
+    runs: &mut Vec<(&'a docx_rs::Run, Option<Revision>)>,
+) {
+    for child in children {
+        match child {
+            docx_rs::ParagraphChild::Run(run) => runs.push((run.as_ref(), revision)),
+            docx_rs::ParagraphChild::Hyperlink(link) => {
+                collect_runs(&link.children, revision, runs);
+            }
+            docx_rs::ParagraphChild::Insert(insert) => {
+                for insert_child in &insert.children {
+                    match insert_child {
+                        docx_rs::InsertChild::Run(run) => {
+                            runs.push((run.as_ref(), Some(Revision::Inserted)));
                         }
+                        docx_rs::InsertChild::Delete(delete) => collect_deleted_runs(delete, runs),
+                        _ => {}
                     }
                 }
-
-                // Only add non-empty paragraphs
-                if !para_text.trim().is_empty() {
-                    segments.push(TextSegment::new(
-                        para_text,
-                        None,
-                        Some(format!("paragraph_{}", idx)),
-                        1.0,
-                    ));
-                }
             }
-            docx_rs::DocumentChild::Table(table) => {
-                // Extract text from tables
-                let mut table_text = String::new();
-
-                for row in &table.rows {
-                    for cell in &row.cells {
-                        for cell_child in &cell.children {
-                            if let docx_rs::TableCellContent::Paragraph(para) = cell_child {
-                                for run_child in &para.children {
-                                    if let docx_rs::ParagraphChild::Run(run) = run_child {
-                                        for run_child in &run.children {
-                                            if let docx_rs::RunChild::Text(text) = run_child {
-                                                table_text.push_str(&text.text);
-                                                table_text.push(' ');
-                                            }
-                                        }
-                                    }
-                                }
+            docx_rs::ParagraphChild::Delete(delete) => collect_deleted_runs(delete, runs),
+            _ => {}
+        }
+    }
+}
+
+fn collect_deleted_runs<'a>(
+    delete: &'a docx_rs::Delete,
+    runs: &mut Vec<(&'a docx_rs::Run, Option<Revision>)>,
+) {
+    for delete_child in &delete.children {
+        if let docx_rs::DeleteChild::Run(run) = delete_child {
+            runs.push((run, Some(Revision::Deleted)));
+        }
+    }
+}
+
+/// Runs of a paragraph in reading order with their tracked-change state
+fn paragraph_runs(para: &docx_rs::Paragraph) -> Vec<(&docx_rs::Run, Option<Revision>)> {
+    let mut runs = Vec::new();
+    collect_runs(&para.children, None, &mut runs);
+    runs
+}
+
+/// Field types whose cached result is tagged as a `field` segment
+const FIELD_TYPES: [&str; 8] = [
+    "TOC", "PAGEREF", "REF", "NOTEREF", "DATE", "TIME", "PAGE", "NUMPAGES",
+];
+
+/// Recognized field type named by a field instruction
+/// (e.g. `" PAGEREF _Toc123 \h "` -> `"PAGEREF"`)
+fn field_type(instruction: &str) -> Option<String> {
+    let name = instruction.split_whitespace().next()?.to_uppercase();
+    FIELD_TYPES.contains(&name.as_str()).then_some(name)
+}
+
+/// Score of text whose field result is stale (`w:dirty`), missing, or
+/// whose field markup is unbalanced
+const STALE_FIELD_CONFIDENCE: f64 = 0.5;
+
+/// Score of text box content, which floats over the body and has no
+/// reliable reading order
+const TEXTBOX_CONFIDENCE: f64 = 0.7;
+
+/// Score of text from a part whose invalid UTF-8 was replaced with U+FFFD
+/// (see `ooxml::repair_utf8`), for segments showing a replacement
+const LOSSY_TEXT_CONFIDENCE: f64 = 0.6;
+
+/// A complex field whose `end` has not been seen yet
+#[derive(Default)]
+struct OpenField {
+    instruction: String,
+    /// Past the `separate` mark, i.e. in Word's cached result
+    in_result: bool,
+    /// Word flagged the cached result as out of date
+    dirty: bool,
+    /// Cached result contains visible text
+    has_result: bool,
+}
+
+impl OpenField {
+    /// Recognized field ended without a usable cached result
+    fn is_stale(&self) -> bool {
+        field_type(&self.instruction).is_some() && (self.dirty || !self.has_result)
+    }
+}
+
+/// Field state of one paragraph, see [`FieldTracker::paragraph_field`]
+#[derive(Debug, Default, PartialEq)]
+struct ParagraphField {
+    /// Set if all visible text is the result of a recognized field
+    field_type: Option<String>,
+    /// A field ending here had a stale or missing result, or the field
+    /// markup is unbalanced
+    stale: bool,
+}
+
+/// Tracks complex fields (`w:fldChar` begin/separate/end) across body
+/// paragraphs; a TOC field usually spans all of its entries
+///
+/// Instruction text is never part of the visible text; the runs between
+/// `separate` and `end` hold Word's cached result.
+#[derive(Default)]
+struct FieldTracker {
+    /// Open fields, outermost first
+    open: Vec<OpenField>,
+}
+
+impl FieldTracker {
+    /// Outermost recognized field among the open ones
+    fn current_type(&self) -> Option<String> {
+        self.open
+            .iter()
+            .find_map(|field| field_type(&field.instruction))
+    }
+
+    /// Advance through a paragraph's runs
+    fn paragraph_field(&mut self, para: &docx_rs::Paragraph) -> ParagraphField {
+        let mut field = None;
+        let mut plain = false;
+        let mut stale = false;
+
+        for (run, _) in paragraph_runs(para) {
+            for child in &run.children {
+                match child {
+                    docx_rs::RunChild::FieldChar(c) => match c.field_char_type {
+                        docx_rs::FieldCharType::Begin => self.open.push(OpenField {
+                            dirty: c.dirty,
+                            ..Default::default()
+                        }),
+                        docx_rs::FieldCharType::Separate => match self.open.last_mut() {
+                            Some(open) => open.in_result = true,
+                            None => stale = true,
+                        },
+                        docx_rs::FieldCharType::End => match self.open.pop() {
+                            Some(open) => stale |= open.is_stale(),
+                            None => stale = true,
+                        },
+                        docx_rs::FieldCharType::Unsupported => stale = true,
+                    },
+                    docx_rs::RunChild::InstrTextString(instruction) => {
+                        if let Some(open) = self.open.last_mut().filter(|f| !f.in_result) {
+                            open.instruction.push_str(instruction);
+                        }
+                    }
+                    docx_rs::RunChild::Text(t) if !t.text.trim().is_empty() => {
+                        for open in self.open.iter_mut().filter(|f| f.in_result) {
+                            open.has_result = true;
+                        }
+                        match self.current_type() {
+                            Some(kind) => {
+                                field.get_or_insert(kind);
                             }
+                            None => plain = true,
                         }
-                        table_text.push('\t'); // Tab between cells
                     }
-                    table_text.push('\n'); // Newline between rows
+                    _ => {}
                 }
+            }
+        }
+
+        ParagraphField {
+            field_type: field.filter(|_| !plain),
+            stale,
+        }
+    }
+}
+
+/// Collects segments while enforcing `max_segments` / `max_chars` and an
+/// optional deadline
+///
+/// Once a limit is hit the collector refuses further segments and reports
+/// `truncated`; the segment that crosses `max_chars` is cut to fit. The
+/// deadline is checked by `is_full`, i.e. between body children.
+struct SegmentCollector {
+    segments: Vec<TextSegment>,
+    max_segments: Option<usize>,
+    max_chars: Option<usize>,
+    chars: usize,
+    words: usize,
+    truncated: bool,
+    deadline: Option<Instant>,
+    timed_out: bool,
+    cancelled: bool,
+    /// Body child being walked, stamped on pushed segments
+    source_index: Option<usize>,
+    /// Invalid UTF-8 was replaced in the package; segments containing
+    /// U+FFFD are scored LOSSY_TEXT_CONFIDENCE
+    lossy_text: bool,
+    normalize_whitespace: bool,
+    ascii_fold: bool,
+    min_confidence: Option<f64>,
+}
+
+impl SegmentCollector {
+    fn new(
+        max_segments: Option<usize>,
+        max_chars: Option<usize>,
+        deadline: Option<Instant>,
+        normalize_whitespace: bool,
+        ascii_fold: bool,
+        min_confidence: Option<f64>,
+    ) -> Self {
+        SegmentCollector {
+            segments: Vec::new(),
+            max_segments,
+            max_chars,
+            chars: 0,
+            words: 0,
+            truncated: false,
+            deadline,
+            timed_out: false,
+            cancelled: false,
+            source_index: None,
+            lossy_text: false,
+            normalize_whitespace,
+            ascii_fold,
+            min_confidence,
+        }
+    }
 
-                if !table_text.trim().is_empty() {
-                    segments.push(TextSegment::new(
-                        table_text,
-                        None,
-                        Some(format!("table_{}", idx)),
-                        1.0,
-                    ));
+    fn push(&mut self, mut segment: TextSegment) {
+        if self.lossy_text && segment.text.contains(char::REPLACEMENT_CHARACTER) {
+            segment.confidence = segment.confidence.min(LOSSY_TEXT_CONFIDENCE);
+        }
+        if self.truncated
+            || self
+                .min_confidence
+                .is_some_and(|min| segment.confidence < min)
+        {
+            return;
+        }
+        if self
+            .max_segments
+            .is_some_and(|max| self.segments.len() >= max)
+        {
+            self.truncated = true;
+            return;
+        }
+
+        if self.ascii_fold {
+            // Every character folds to at least one, so no run empties
+            let (text, offsets) = glyphs::fold_ascii_mapped(&segment.text);
+            segment.text = text;
+            for run in &mut segment.runs {
+                run.start = offsets[run.start];
+                run.end = offsets[run.end];
+            }
+        }
+        if self.normalize_whitespace {
+            if segment.runs.is_empty() {
+                segment.text = collapse_whitespace(&segment.text);
+            } else {
+                let (text, offsets) = collapse_whitespace_mapped(&segment.text);
+                segment.text = text;
+                for run in &mut segment.runs {
+                    run.start = offsets[run.start];
+                    run.end = offsets[run.end];
                 }
+                segment.runs.retain(|run| run.start < run.end);
             }
-            _ => {
-                // Other document children (bookmarks, etc.) - skip for now
+        }
+        if segment.direction.is_none() {
+            segment.direction = text_direction(&segment.text).map(str::to_string);
+        }
+
+        let count = segment.text.chars().count();
+        if let Some(max) = self.max_chars {
+            let remaining = max.saturating_sub(self.chars);
+            if count > remaining {
+                segment.text = segment.text.chars().take(remaining).collect();
+                let len = segment.text.len();
+                for run in &mut segment.runs {
+                    run.end = run.end.min(len);
+                }
+                segment.runs.retain(|run| run.start < run.end);
+                self.truncated = true;
+                if segment.text.trim().is_empty() {
+                    return;
+                }
             }
         }
+
+        segment.source_index = segment.source_index.or(self.source_index);
+        segment.char_offset = self.chars;
+        self.chars += segment.text.chars().count();
+        self.words += segment.text.split_whitespace().count();
+        self.segments.push(segment);
     }
 
-    // Add metadata
-    if let Err(e) = metadata.set_item("paragraph_count", segments.len()) {
-        eprintln!("Failed to set metadata: {}", e);
+    /// Stop collecting at the caller's request
+    fn cancel(&mut self) {
+        self.truncated = true;
+        self.cancelled = true;
     }
 
-    let processing_time = start_time.elapsed().as_secs_f64() * 1000.0;
+    /// True once a limit was hit, the deadline passed or extraction was
+    /// cancelled, and iteration should stop
+    fn is_full(&mut self) -> bool {
+        if !self.truncated && self.deadline.is_some_and(|d| Instant::now() >= d) {
+            self.truncated = true;
+            self.timed_out = true;
+        }
+        self.truncated
+    }
+}
 
-    Ok(ExtractionResult {
-        segments,
-        metadata: metadata.unbind(),
-        processing_time_ms: processing_time,
-        file_size_bytes: file_size,
-        errors,
-        truncated: false,
-        extractor: "docx_rust".to_string(),
-        version: "1.0.0".to_string(),
+/// Collapse whitespace runs (tabs, newlines, NBSP, ...) to single spaces
+/// and trim both ends
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// [`collapse_whitespace`], also returning where each byte offset of
+/// `text` (including its end) lands in the collapsed string
+fn collapse_whitespace_mapped(text: &str) -> (String, Vec<usize>) {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+    let mut pending_space = false;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            pending_space = true;
+            offsets.extend(std::iter::repeat_n(collapsed.len(), c.len_utf8()));
+            continue;
+        }
+        if pending_space && !collapsed.is_empty() {
+            collapsed.push(' ');
+        }
+        pending_space = false;
+        offsets.extend(std::iter::repeat_n(collapsed.len(), c.len_utf8()));
+        collapsed.push(c);
+    }
+    offsets.push(collapsed.len());
+
+    (collapsed, offsets)
+}
+
+/// Characters of right-to-left scripts (Hebrew, Arabic, Syriac, Thaana,
+/// N'Ko, ...), i.e. bidi classes R and AL
+fn is_rtl_char(c: char) -> bool {
+    matches!(c,
+        '\u{0590}'..='\u{08FF}'
+        | '\u{FB1D}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFF}'
+        | '\u{10800}'..='\u{10FFF}'
+        | '\u{1E800}'..='\u{1EFFF}')
+}
+
+/// Base direction of `text` from its first strongly directional character
+/// (UAX #9 rules P2/P3); letters outside RTL scripts count as left-to-right
+fn text_direction(text: &str) -> Option<&'static str> {
+    text.chars().find_map(|c| {
+        if is_rtl_char(c) && c.is_alphabetic() {
+            Some("rtl")
+        } else if c.is_alphabetic() {
+            Some("ltr")
+        } else {
+            None
+        }
     })
 }
 
-/// Python module definition
-#[pymodule]
-fn docx_extractor(m: &Bound<'_, PyModule>) -> PyResult<()> {
-    m.add_function(wrap_pyfunction!(extract_docx, m)?)?;
-    m.add_class::<TextSegment>()?;
-    m.add_class::<ExtractionError>()?;
+/// Concatenate the visible text of a run, symbol glyphs included
+fn run_text(run: &docx_rs::Run) -> String {
+    let mut text = String::new();
+
+    for run_child in &run.children {
+        match run_child {
+            docx_rs::RunChild::Text(t) => text.push_str(&t.text),
+            docx_rs::RunChild::Sym(sym) => text.push(glyphs::resolve_sym(&sym.font, &sym.char)),
+            _ => {}
+        }
+    }
+
+    text
+}
+
+/// Concatenate the deleted (`w:delText`) text of a run
+fn deleted_run_text(run: &docx_rs::Run) -> String {
+    let mut text = String::new();
+
+    for run_child in &run.children {
+        if let docx_rs::RunChild::DeleteText(t) = run_child {
+            // DeleteText keeps its text private; read it through serde
+            if let Some(t) = serde_json::to_value(t)
+                .ok()
+                .and_then(|v| v["text"].as_str().map(str::to_string))
+            {
+                text.push_str(&t);
+            }
+        }
+    }
+
+    text
+}
+
+/// Concatenate the visible text of a paragraph's runs
+fn paragraph_text(para: &docx_rs::Paragraph) -> String {
+    paragraph_runs(para)
+        .into_iter()
+        .map(|(run, _)| run_text(run))
+        .collect()
+}
+
+/// Character formatting that matters when emitting a run's text
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+struct RunFormat {
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    /// Hex RGB; None for automatic color
+    color: Option<String>,
+}
+
+/// Direct formatting of a run
+fn run_format(run: &docx_rs::Run) -> RunFormat {
+    let property = &run.run_property;
+    // Bold/Italic keep their value private; they serialize as a bool
+    let toggle = |value: Option<Value>| value.and_then(|v| v.as_bool()).unwrap_or(false);
+
+    RunFormat {
+        bold: toggle(
+            property
+                .bold
+                .as_ref()
+                .and_then(|b| serde_json::to_value(b).ok()),
+        ),
+        italic: toggle(
+            property
+                .italic
+                .as_ref()
+                .and_then(|i| serde_json::to_value(i).ok()),
+        ),
+        underline: property
+            .underline
+            .as_ref()
+            .and_then(|u| serde_json::to_value(u).ok())
+            .is_some_and(|v| v.as_str().is_some_and(|val| val != "none")),
+        // Color keeps its value private; it serializes as a string
+        color: property
+            .color
+            .as_ref()
+            .and_then(|c| serde_json::to_value(c).ok())
+            .and_then(|v| v.as_str().map(str::to_string))
+            .filter(|val| !val.is_empty() && val != "auto"),
+    }
+}
+
+/// Paragraph text as run-level chunks
+///
+/// Word often splits a single word across several runs (spell-check
+/// regions, rsid changes); adjacent runs with the same format and
+/// revision are coalesced so each chunk is contiguous text.
+fn coalesce_runs(
+    para: &docx_rs::Paragraph,
+    accept_revisions: bool,
+) -> Vec<(String, RunFormat, Option<Revision>)> {
+    let mut chunks: Vec<(String, RunFormat, Option<Revision>)> = Vec::new();
+
+    for (run, revision) in paragraph_runs(para) {
+        let (text, revision) = match revision {
+            Some(Revision::Deleted) if accept_revisions => continue,
+            Some(Revision::Deleted) => (deleted_run_text(run), revision),
+            Some(Revision::Inserted) if accept_revisions => (run_text(run), None),
+            _ => (run_text(run), revision),
+        };
+        if text.is_empty() {
+            continue;
+        }
+
+        let format = run_format(run);
+        match chunks.last_mut() {
+            Some((chunk, chunk_format, chunk_revision))
+                if *chunk_format == format && *chunk_revision == revision =>
+            {
+                chunk.push_str(&text)
+            }
+            _ => chunks.push((text, format, revision)),
+        }
+    }
+
+    chunks
+}
+
+/// Wrap text in Markdown emphasis markers (`**_text_**` for bold italic)
+///
+/// Surrounding whitespace stays outside the markers so they remain valid
+/// Markdown; underline has no Markdown form and is left unmarked.
+fn emphasize(text: &str, format: &RunFormat) -> String {
+    let core = text.trim();
+    if core.is_empty() || !(format.bold || format.italic) {
+        return text.to_string();
+    }
+
+    let start = text.len() - text.trim_start().len();
+    let end = start + core.len();
+    let (open, close) = match (format.bold, format.italic) {
+        (true, true) => ("**_", "_**"),
+        (true, false) => ("**", "**"),
+        _ => ("_", "_"),
+    };
+
+    format!(
+        "{}{}{}{}{}",
+        &text[..start],
+        open,
+        core,
+        close,
+        &text[end..]
+    )
+}
+
+/// [`paragraph_spans_with_runs`] without the formatting runs
+#[cfg(test)]
+fn paragraph_spans(
+    para: &docx_rs::Paragraph,
+    accept_revisions: bool,
+    emphasis_markers: bool,
+) -> Vec<(String, Option<Revision>)> {
+    paragraph_spans_with_runs(para, accept_revisions, emphasis_markers, false)
+        .into_iter()
+        .map(|(text, revision, _)| (text, revision))
+        .collect()
+}
+
+/// Paragraph text split into spans at tracked-change boundaries, with
+/// each span's formatting runs (byte ranges) recorded when `detailed`
+///
+/// With `accept_revisions` deleted text is dropped and inserted text is
+/// treated as unchanged, yielding the "final" view as a single span.
+/// With `emphasis_markers` bold/italic runs are wrapped in Markdown markers.
+fn paragraph_spans_with_runs(
+    para: &docx_rs::Paragraph,
+    accept_revisions: bool,
+    emphasis_markers: bool,
+    detailed: bool,
+) -> Vec<(String, Option<Revision>, Vec<RunSpan>)> {
+    let mut spans: Vec<(String, Option<Revision>, Vec<RunSpan>)> = Vec::new();
+
+    for (text, format, revision) in coalesce_runs(para, accept_revisions) {
+        let text = match emphasis_markers {
+            true => emphasize(&text, &format),
+            false => text,
+        };
+        let index = match spans.last() {
+            Some((_, span_revision, _)) if *span_revision == revision => spans.len() - 1,
+            _ => {
+                spans.push((String::new(), revision, Vec::new()));
+                spans.len() - 1
+            }
+        };
+        let (span, _, runs) = &mut spans[index];
+        if detailed {
+            runs.push(RunSpan::new(
+                span.len(),
+                span.len() + text.len(),
+                format.bold,
+                format.italic,
+                format.color,
+            ));
+        }
+        span.push_str(&text);
+    }
+
+    spans
+}
+
+/// Human-readable name of a style (`w:name`), if it has one
+fn style_name(style: &docx_rs::Style) -> Option<String> {
+    // Name keeps its value private; it serializes as a string
+    serde_json::to_value(&style.name)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .filter(|name| !name.is_empty())
+}
+
+/// Paragraph style (`w:pStyle`), resolved to its name through the styles
+/// part where possible; None when no style is set ("Normal")
+fn paragraph_style(para: &docx_rs::Paragraph, styles: &docx_rs::Styles) -> Option<String> {
+    let style_id = para.property.style.as_ref().map(|s| s.val.as_str())?;
+
+    styles
+        .styles
+        .iter()
+        .find(|s| s.style_id == style_id)
+        .and_then(style_name)
+        .or_else(|| Some(style_id.to_string()))
+}
+
+/// Heading level (1-9) of a paragraph
+///
+/// Resolved from the paragraph style ("heading 1" / `Heading1`), the
+/// style's outline level, or the paragraph's own `w:outlineLvl`.
+fn heading_level(para: &docx_rs::Paragraph, styles: &docx_rs::Styles) -> Option<u8> {
+    let from_outline = |outline: &Option<docx_rs::OutlineLvl>| {
+        // Outline level 9 means body text
+        outline.as_ref().filter(|o| o.v < 9).map(|o| o.v as u8 + 1)
+    };
+
+    let Some(style_id) = para.property.style.as_ref().map(|s| s.val.as_str()) else {
+        return from_outline(&para.property.outline_lvl);
+    };
+
+    let style = styles.styles.iter().find(|s| s.style_id == style_id);
+    let name = style.and_then(style_name);
+
+    let from_name = |candidate: &str| {
+        candidate
+            .replace(' ', "")
+            .to_lowercase()
+            .strip_prefix("heading")
+            .and_then(|n| n.parse::<u8>().ok())
+            .filter(|n| (1..=9).contains(n))
+    };
+
+    name.as_deref()
+        .and_then(from_name)
+        .or_else(|| from_name(style_id))
+        .or_else(|| style.and_then(|s| from_outline(&s.paragraph_property.outline_lvl)))
+        .or_else(|| from_outline(&para.property.outline_lvl))
+}
+
+/// Hyperlinks of a paragraph as (display text, target) pairs
+///
+/// External links are resolved through the document relationships
+/// (`rid` -> URL); bookmark links resolve to `#anchor`.
+fn paragraph_links(
+    para: &docx_rs::Paragraph,
+    relationships: &HashMap<String, String>,
+) -> Vec<(String, String)> {
+    let mut links = Vec::new();
+
+    for child in &para.children {
+        if let docx_rs::ParagraphChild::Hyperlink(link) = child {
+            let target = match &link.link {
+                docx_rs::HyperlinkData::External { rid, .. } => match relationships.get(rid) {
+                    Some(url) => url.clone(),
+                    None => continue,
+                },
+                docx_rs::HyperlinkData::Anchor { anchor } => format!("#{}", anchor),
+            };
+
+            let mut display = String::new();
+            for link_child in &link.children {
+                if let docx_rs::ParagraphChild::Run(run) = link_child {
+                    display.push_str(&run_text(run));
+                }
+            }
+
+            links.push((display, target));
+        }
+    }
+
+    links
+}
+
+/// Estimated 1-based page of text at `offset` within a body child
+fn estimated_page(breaks: &[ooxml::Break], child_index: usize, offset: usize) -> i32 {
+    breaks.partition_point(|b| (b.child_index, b.offset) <= (child_index, offset)) as i32 + 1
+}
+
+/// Zero-width `page_break` / `section_break` marker segment for an explicit
+/// break; `None` for the renderer's page hints
+fn break_marker(brk: &ooxml::Break, page: i32) -> Option<TextSegment> {
+    let section = match brk.kind {
+        ooxml::BreakKind::Page => "page_break",
+        ooxml::BreakKind::Section { .. } => "section_break",
+        ooxml::BreakKind::Rendered => return None,
+    };
+    let mut marker = TextSegment::with_section(String::new(), section.to_string());
+    marker.page = Some(page);
+    marker.anchor_paragraph = Some(brk.child_index as i32);
+    Some(marker)
+}
+
+/// Insert `[3]` (footnote) / `[e3]` (endnote) indicators at the reference
+/// markers' character offsets so reading order is preserved
+///
+/// `base` is the character offset of `text` within its paragraph.
+fn insert_note_markers(
+    text: &mut String,
+    references: &[&ooxml::NoteReference],
+    base: usize,
+    runs: &mut [RunSpan],
+) {
+    let mut references = references.to_vec();
+    references.sort_by_key(|r| r.offset);
+
+    for reference in references.into_iter().rev() {
+        let indicator = match reference.kind {
+            ooxml::NoteKind::Footnote => format!("[{}]", reference.id),
+            ooxml::NoteKind::Endnote => format!("[e{}]", reference.id),
+        };
+        let at = text
+            .char_indices()
+            .nth(reference.offset.saturating_sub(base))
+            .map_or(text.len(), |(byte, _)| byte);
+        text.insert_str(at, &indicator);
+        // Later markers are already in, but they all lie after `at`
+        shift_runs(runs, at, indicator.len());
+    }
+}
+
+/// Maximum list nesting depth supported by WordprocessingML (`ilvl` 0-8)
+const MAX_LIST_LEVELS: usize = 9;
+
+/// Running list counters used to render numbering markers
+///
+/// As in Word, counters belong to the abstract numbering definition, so
+/// every `numId` instance of it continues the same sequence (also across
+/// interleaved paragraphs). An instance with a `startOverride` restarts
+/// the overridden levels where it is first used ("Restart numbering").
+/// Deeper levels restart when a shallower level advances, unless their
+/// `lvlRestart` says otherwise.
+struct ListCounters<'a> {
+    numberings: &'a docx_rs::Numberings,
+    /// Keyed by `abstractNumId`
+    counters: HashMap<usize, [Option<usize>; MAX_LIST_LEVELS]>,
+    /// Instances whose start overrides were applied already
+    started: std::collections::HashSet<usize>,
+}
+
+impl<'a> ListCounters<'a> {
+    fn new(numberings: &'a docx_rs::Numberings) -> Self {
+        ListCounters {
+            numberings,
+            counters: HashMap::new(),
+            started: std::collections::HashSet::new(),
+        }
+    }
+
+    fn numbering(&self, num_id: usize) -> Option<&'a docx_rs::Numbering> {
+        let numberings: &'a docx_rs::Numberings = self.numberings;
+        numberings.numberings.iter().find(|n| n.id == num_id)
+    }
+
+    /// Whether advancing level `used` restarts the deeper level `level`
+    ///
+    /// `lvlRestart` is the 1-based level whose use (or that of any
+    /// shallower level) restarts this one; 0 means never. Without it any
+    /// shallower level restarts it.
+    fn restarts(level: &docx_rs::Level, used: usize) -> bool {
+        let restart = level
+            .level_restart
+            .as_ref()
+            .and_then(|r| serde_json::to_value(r).ok()?.as_u64());
+        match restart {
+            None => true,
+            Some(restart) => (used as u64) < restart,
+        }
+    }
+
+    /// Level definition and start value for `ilvl` of a numbering instance,
+    /// with `lvlOverride` / `startOverride` applied
+    fn level(&self, num_id: usize, ilvl: usize) -> Option<(&'a docx_rs::Level, usize)> {
+        let numberings: &'a docx_rs::Numberings = self.numberings;
+        let numbering = self.numbering(num_id)?;
+        let abstract_num = numberings
+            .abstract_nums
+            .iter()
+            .find(|a| a.id == numbering.abstract_num_id)?;
+        let level_override = numbering.level_overrides.iter().find(|o| o.level == ilvl);
+
+        let level = level_override
+            .and_then(|o| o.override_level.as_ref())
+            .or_else(|| abstract_num.levels.iter().find(|l| l.level == ilvl))?;
+        let start = level_override
+            .and_then(|o| o.override_start)
+            .or_else(|| {
+                serde_json::to_value(&level.start)
+                    .ok()?
+                    .as_u64()
+                    .map(|v| v as usize)
+            })
+            .unwrap_or(1);
+
+        Some((level, start))
+    }
+
+    /// Advance the counter for a list paragraph and render its marker
+    /// (e.g. `"1. "`, `"a) "`, `"\u{2022} "`)
+    fn next_marker(&mut self, num_id: usize, ilvl: usize) -> Option<String> {
+        // numId 0 explicitly removes numbering from a paragraph
+        if num_id == 0 || ilvl >= MAX_LIST_LEVELS {
+            return None;
+        }
+
+        let (level, start) = self.level(num_id, ilvl)?;
+        let numbering = self.numbering(num_id)?;
+        let mut counters = self
+            .counters
+            .get(&numbering.abstract_num_id)
+            .copied()
+            .unwrap_or([None; MAX_LIST_LEVELS]);
+
+        if self.started.insert(num_id) {
+            for o in &numbering.level_overrides {
+                if o.override_start.is_some() && o.level < MAX_LIST_LEVELS {
+                    counters[o.level] = None;
+                }
+            }
+        }
+
+        counters[ilvl] = Some(counters[ilvl].map_or(start, |n| n + 1));
+        for (deeper, counter) in counters.iter_mut().enumerate().skip(ilvl + 1) {
+            let restarts = self
+                .level(num_id, deeper)
+                .is_none_or(|(l, _)| Self::restarts(l, ilvl));
+            if restarts {
+                *counter = None;
+            }
+        }
+        self.counters.insert(numbering.abstract_num_id, counters);
+
+        match level.format.val.as_str() {
+            "none" => return None,
+            "bullet" => return Some("\u{2022} ".to_string()),
+            _ => {}
+        }
+
+        let mut marker = serde_json::to_value(&level.text)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("%{}.", ilvl + 1));
+
+        for (lvl, counter) in counters.iter().enumerate().take(ilvl + 1) {
+            let placeholder = format!("%{}", lvl + 1);
+            if !marker.contains(&placeholder) {
+                continue;
+            }
+            let (format, value) = match self.level(num_id, lvl) {
+                Some((l, s)) => (l.format.val.as_str(), counter.unwrap_or(s)),
+                None => ("decimal", counter.unwrap_or(1)),
+            };
+            marker = marker.replace(&placeholder, &format_list_number(value, format));
+        }
+
+        marker.push(' ');
+        Some(marker)
+    }
+}
+
+/// Render a list counter in the given `w:numFmt`
+fn format_list_number(value: usize, format: &str) -> String {
+    match format {
+        "lowerLetter" => letter_number(value),
+        "upperLetter" => letter_number(value).to_uppercase(),
+        "lowerRoman" => roman_number(value).to_lowercase(),
+        "upperRoman" => roman_number(value),
+        "decimalZero" if value < 10 => format!("0{}", value),
+        _ => value.to_string(),
+    }
+}
+
+/// Counters above this are rendered in decimal: letter and Roman forms
+/// grow linearly with the value, so a huge `w:start` would otherwise
+/// allocate an enormous marker
+const MAX_STYLED_LIST_NUMBER: usize = 10_000;
+
+/// Word-style alphabetic counter: a..z, aa..zz, aaa..
+fn letter_number(value: usize) -> String {
+    if value == 0 {
+        return String::new();
+    }
+    if value > MAX_STYLED_LIST_NUMBER {
+        return value.to_string();
+    }
+    let letter = (b'a' + ((value - 1) % 26) as u8) as char;
+    letter.to_string().repeat((value - 1) / 26 + 1)
+}
+
+/// Upper-case Roman numeral
+fn roman_number(mut value: usize) -> String {
+    if value > MAX_STYLED_LIST_NUMBER {
+        return value.to_string();
+    }
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    let mut roman = String::new();
+    for (n, numeral) in NUMERALS {
+        while value >= n {
+            roman.push_str(numeral);
+            value -= n;
+        }
+    }
+    roman
+}
+
+/// Nested tables deeper than this are skipped
+const MAX_TABLE_DEPTH: usize = 8;
+
+/// docx-rs's serialized `type` of a body child (`"tableOfContents"`, ...),
+/// so variants added later are named without a code change here
+fn child_variant(child: &docx_rs::DocumentChild) -> String {
+    serde_json::to_value(child)
+        .ok()
+        .and_then(|value| value["type"].as_str().map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Paragraphs of a content control's `sdtContent`
+///
+/// docx-rs ends a control at the first nested control's end, so the
+/// repetitions of a repeating section arrive as sibling controls.
+fn sdt_paragraphs(sdt: &docx_rs::StructuredDataTag) -> impl Iterator<Item = &docx_rs::Paragraph> {
+    sdt.children.iter().filter_map(|child| match child {
+        docx_rs::StructuredDataTagChild::Paragraph(para) => Some(para.as_ref()),
+        _ => None,
+    })
+}
+
+/// Flatten a table into tab-separated cells and newline-separated rows
+fn table_text(table: &docx_rs::Table) -> String {
+    table_text_with(table, "\t", "\n")
+}
+
+/// Flatten a table, joining cells with `cell_sep` and ending rows with
+/// `row_sep`
+///
+/// Nested tables are flattened in reading order into their parent cell,
+/// their words space-separated like the cell's own text. Separator
+/// characters inside a cell become spaces so the result splits cleanly.
+fn table_text_with(table: &docx_rs::Table, cell_sep: &str, row_sep: &str) -> String {
+    fn push_cell_words(content: &[docx_rs::TableCellContent], depth: usize, text: &mut String) {
+        for cell_child in content {
+            match cell_child {
+                docx_rs::TableCellContent::StructuredDataTag(sdt) => {
+                    for para in sdt_paragraphs(sdt) {
+                        text.push_str(&paragraph_text(para));
+                        text.push(' ');
+                    }
+                }
+                docx_rs::TableCellContent::Paragraph(para) => {
+                    for (run, _) in paragraph_runs(para) {
+                        for run_child in &run.children {
+                            match run_child {
+                                docx_rs::RunChild::Text(t) => text.push_str(&t.text),
+                                docx_rs::RunChild::Sym(sym) => {
+                                    text.push(glyphs::resolve_sym(&sym.font, &sym.char))
+                                }
+                                _ => continue,
+                            }
+                            text.push(' ');
+                        }
+                    }
+                }
+                docx_rs::TableCellContent::Table(nested) if depth < MAX_TABLE_DEPTH => {
+                    for docx_rs::TableChild::TableRow(row) in &nested.rows {
+                        for docx_rs::TableRowChild::TableCell(cell) in &row.cells {
+                            push_cell_words(&cell.children, depth + 1, text);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let is_separator = |c: char| cell_sep.contains(c) || row_sep.contains(c);
+    let mut table_text = String::new();
+
+    for docx_rs::TableChild::TableRow(row) in &table.rows {
+        for docx_rs::TableRowChild::TableCell(cell) in &row.cells {
+            let mut cell_text = String::new();
+            push_cell_words(&cell.children, 0, &mut cell_text);
+            table_text.extend(
+                cell_text
+                    .chars()
+                    .map(|c| if is_separator(c) { ' ' } else { c }),
+            );
+            table_text.push_str(cell_sep);
+        }
+        table_text.push_str(row_sep);
+    }
+
+    table_text
+}
+
+/// Grid span and vertical-merge continuation of a table cell
+fn cell_merge(cell: &docx_rs::TableCell) -> (usize, bool) {
+    // TableCellProperty keeps its fields private; read them through serde
+    let property = serde_json::to_value(&cell.property).unwrap_or_default();
+    let span = property["gridSpan"].as_u64().unwrap_or(1).max(1) as usize;
+    let continued = property["verticalMerge"] == "continue";
+    (span, continued)
+}
+
+/// Text of a cell: its paragraphs one per line, nested tables as one line
+/// per row with tab-separated cells
+fn cell_text(content: &[docx_rs::TableCellContent], depth: usize) -> String {
+    let mut lines = Vec::new();
+    for child in content {
+        match child {
+            docx_rs::TableCellContent::Paragraph(para) => lines.push(paragraph_text(para)),
+            docx_rs::TableCellContent::StructuredDataTag(sdt) => {
+                lines.extend(sdt_paragraphs(sdt).map(paragraph_text));
+            }
+            docx_rs::TableCellContent::Table(nested) if depth < MAX_TABLE_DEPTH => {
+                for docx_rs::TableChild::TableRow(row) in &nested.rows {
+                    let cells: Vec<String> = row
+                        .cells
+                        .iter()
+                        .map(|docx_rs::TableRowChild::TableCell(cell)| {
+                            // Deeper levels collapse onto the nested row's line
+                            cell_text(&cell.children, depth + 1).replace(['\n', '\t'], " ")
+                        })
+                        .collect();
+                    lines.push(cells.join("\t"));
+                }
+            }
+            _ => {}
+        }
+    }
+    lines.join("\n")
+}
+
+/// Cell-by-cell table content, preserving tabs/newlines inside cells
+///
+/// Nested tables become rows of lines inside their parent cell.
+fn table_rows(table: &docx_rs::Table) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+
+    for docx_rs::TableChild::TableRow(row) in &table.rows {
+        let mut cells = Vec::new();
+
+        for docx_rs::TableRowChild::TableCell(cell) in &row.cells {
+            let (span, continued) = cell_merge(cell);
+
+            let text = if continued {
+                String::new()
+            } else {
+                cell_text(&cell.children, 0)
+            };
+
+            cells.push(text);
+            cells.extend(std::iter::repeat_n(String::new(), span - 1));
+        }
+
+        rows.push(cells);
+    }
+
+    rows
+}
+
+/// Originating cells of each row with their col/row spans
+///
+/// `vMerge` continuation cells are omitted; each one extends the rowspan
+/// of the cell that started the merge in the same grid column.
+fn table_cells(table: &docx_rs::Table) -> Vec<Vec<TableCellSegment>> {
+    let mut rows: Vec<Vec<TableCellSegment>> = Vec::new();
+    // Grid column -> (row, position in row) of the cell a merge started at
+    let mut merge_origins: HashMap<usize, (usize, usize)> = HashMap::new();
+
+    for (row_idx, docx_rs::TableChild::TableRow(row)) in table.rows.iter().enumerate() {
+        let mut cells = Vec::new();
+        let mut column = 0;
+
+        for docx_rs::TableRowChild::TableCell(cell) in &row.cells {
+            let (span, continued) = cell_merge(cell);
+
+            match merge_origins.get(&column) {
+                Some(&(origin_row, origin_idx)) if continued => {
+                    rows[origin_row][origin_idx].rowspan += 1;
+                }
+                _ => {
+                    // A stray continuation (no cell above) starts its own merge
+                    merge_origins.insert(column, (row_idx, cells.len()));
+                    cells.push(TableCellSegment::new(cell_text(&cell.children, 0), span, 1));
+                }
+            }
+            column += span;
+        }
+
+        rows.push(cells);
+    }
+
+    rows
+}
+
+/// Paragraphs of every cell in a table, row by row, including those of
+/// nested tables
+fn table_paragraphs(table: &docx_rs::Table) -> Vec<&docx_rs::Paragraph> {
+    fn collect<'a>(
+        table: &'a docx_rs::Table,
+        depth: usize,
+        paragraphs: &mut Vec<&'a docx_rs::Paragraph>,
+    ) {
+        for docx_rs::TableChild::TableRow(row) in &table.rows {
+            for docx_rs::TableRowChild::TableCell(cell) in &row.cells {
+                for child in &cell.children {
+                    match child {
+                        docx_rs::TableCellContent::Paragraph(para) => paragraphs.push(para),
+                        docx_rs::TableCellContent::StructuredDataTag(sdt) => {
+                            paragraphs.extend(sdt_paragraphs(sdt));
+                        }
+                        docx_rs::TableCellContent::Table(nested) if depth < MAX_TABLE_DEPTH => {
+                            collect(nested, depth + 1, paragraphs);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    let mut paragraphs = Vec::new();
+    collect(table, 0, &mut paragraphs);
+    paragraphs
+}
+
+/// Hyperlinks found in any cell paragraph of a table
+fn table_links(
+    table: &docx_rs::Table,
+    relationships: &HashMap<String, String>,
+) -> Vec<(String, String)> {
+    table_paragraphs(table)
+        .into_iter()
+        .flat_map(|para| paragraph_links(para, relationships))
+        .collect()
+}
+
+/// Bookmarks in document order as `{name, paragraph}` objects, where
+/// `paragraph` is the body child index the bookmark starts in
+///
+/// A bookmark opened between paragraphs is attributed to the next child.
+/// Word's hidden `_GoBack` bookmark (last edit position) is skipped.
+fn bookmark_anchors(children: &[docx_rs::DocumentChild]) -> Vec<Value> {
+    fn anchor(start: &docx_rs::BookmarkStart, idx: usize, bookmarks: &mut Vec<Value>) {
+        if start.name != "_GoBack" {
+            bookmarks.push(serde_json::json!({"name": start.name, "paragraph": idx}));
+        }
+    }
+    fn paragraph_starts(para: &docx_rs::Paragraph, idx: usize, bookmarks: &mut Vec<Value>) {
+        for child in &para.children {
+            if let docx_rs::ParagraphChild::BookmarkStart(start) = child {
+                anchor(start, idx, bookmarks);
+            }
+        }
+    }
+
+    let mut bookmarks = Vec::new();
+
+    for (idx, child) in children.iter().enumerate() {
+        match child {
+            docx_rs::DocumentChild::BookmarkStart(start) => anchor(start, idx + 1, &mut bookmarks),
+            docx_rs::DocumentChild::Paragraph(para) => paragraph_starts(para, idx, &mut bookmarks),
+            docx_rs::DocumentChild::Table(table) => {
+                for para in table_paragraphs(table) {
+                    paragraph_starts(para, idx, &mut bookmarks);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    bookmarks
+}
+
+/// Body child index where each comment range starts, keyed by comment id
+///
+/// A range opened between paragraphs is attributed to the next child.
+fn comment_anchors(children: &[docx_rs::DocumentChild]) -> HashMap<usize, usize> {
+    fn paragraph_starts(
+        para: &docx_rs::Paragraph,
+        idx: usize,
+        anchors: &mut HashMap<usize, usize>,
+    ) {
+        for child in &para.children {
+            if let docx_rs::ParagraphChild::CommentStart(start) = child {
+                anchors.entry(start.id).or_insert(idx);
+            }
+        }
+    }
+
+    let mut anchors = HashMap::new();
+
+    for (idx, child) in children.iter().enumerate() {
+        match child {
+            docx_rs::DocumentChild::CommentStart(start) => {
+                anchors.entry(start.id).or_insert(idx + 1);
+            }
+            docx_rs::DocumentChild::Paragraph(para) => paragraph_starts(para, idx, &mut anchors),
+            docx_rs::DocumentChild::Table(table) => {
+                for para in table_paragraphs(table) {
+                    paragraph_starts(para, idx, &mut anchors);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    anchors
+}
+
+/// Text of a comment, one line per paragraph
+fn comment_text(comment: &docx_rs::Comment) -> String {
+    comment
+        .children
+        .iter()
+        .map(|child| match child {
+            docx_rs::CommentChild::Paragraph(para) => paragraph_text(para),
+            docx_rs::CommentChild::Table(table) => table_text(table),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Text of each text box drawn in a paragraph, one line per box paragraph
+///
+/// docx-rs keeps box content out of the run text and skips the VML
+/// `mc:Fallback` copy, so each box is seen exactly once. Nested boxes
+/// follow the box containing them.
+fn paragraph_textboxes(para: &docx_rs::Paragraph) -> Vec<String> {
+    let mut textboxes = Vec::new();
+
+    for (run, _) in paragraph_runs(para) {
+        for run_child in &run.children {
+            let docx_rs::RunChild::Drawing(drawing) = run_child else {
+                continue;
+            };
+            let Some(docx_rs::DrawingData::TextBox(textbox)) = &drawing.data else {
+                continue;
+            };
+
+            let mut lines = Vec::new();
+            let mut nested = Vec::new();
+            for child in &textbox.children {
+                match child {
+                    docx_rs::TextBoxContentChild::Paragraph(p) => {
+                        lines.push(paragraph_text(p));
+                        nested.extend(paragraph_textboxes(p));
+                    }
+                    docx_rs::TextBoxContentChild::Table(t) => lines.push(table_text(t)),
+                }
+            }
+
+            let text = lines.join("\n").trim().to_string();
+            if !text.is_empty() {
+                textboxes.push(text);
+            }
+            textboxes.extend(nested);
+        }
+    }
+
+    textboxes
+}
+
+/// Text of a header part, one line per paragraph or table
+fn header_text(header: &docx_rs::Header) -> String {
+    let mut lines = Vec::new();
+
+    for child in &header.children {
+        match child {
+            docx_rs::HeaderChild::Paragraph(para) => lines.push(paragraph_text(para)),
+            docx_rs::HeaderChild::Table(table) => lines.push(table_text(table)),
+            _ => {}
+        }
+    }
+
+    lines.retain(|line| !line.trim().is_empty());
+    lines.join("\n")
+}
+
+/// Text of a footer part, one line per paragraph or table
+fn footer_text(footer: &docx_rs::Footer) -> String {
+    let mut lines = Vec::new();
+
+    for child in &footer.children {
+        match child {
+            docx_rs::FooterChild::Paragraph(para) => lines.push(paragraph_text(para)),
+            docx_rs::FooterChild::Table(table) => lines.push(table_text(table)),
+            _ => {}
+        }
+    }
+
+    lines.retain(|line| !line.trim().is_empty());
+    lines.join("\n")
+}
+
+/// Header/footer slots of a section, in emission order
+const HEADER_FOOTER_SLOTS: [&str; 6] = [
+    "header_default",
+    "header_first",
+    "header_even",
+    "footer_default",
+    "footer_first",
+    "footer_even",
+];
+
+/// Relationship ids of a section's headers/footers, by slot
+fn section_references(section: &docx_rs::SectionProperty) -> [Option<&str>; 6] {
+    [
+        section.header_reference.as_ref().map(|r| r.id.as_str()),
+        section
+            .first_header_reference
+            .as_ref()
+            .map(|r| r.id.as_str()),
+        section
+            .even_header_reference
+            .as_ref()
+            .map(|r| r.id.as_str()),
+        section.footer_reference.as_ref().map(|r| r.id.as_str()),
+        section
+            .first_footer_reference
+            .as_ref()
+            .map(|r| r.id.as_str()),
+        section
+            .even_footer_reference
+            .as_ref()
+            .map(|r| r.id.as_str()),
+    ]
+}
+
+/// Text of the header (slots 0-2) or footer part behind `rid`
+fn header_footer_part_text(
+    buffer: &[u8],
+    relationships: &HashMap<String, String>,
+    slot: usize,
+    rid: &str,
+) -> Option<String> {
+    use docx_rs::FromXML;
+
+    let target = relationships.get(rid)?;
+    let xml = ooxml::read_part(buffer, &ooxml::document_part_name(target))?;
+    if slot < 3 {
+        docx_rs::Header::from_xml(&xml[..])
+            .ok()
+            .map(|h| header_text(&h))
+    } else {
+        docx_rs::Footer::from_xml(&xml[..])
+            .ok()
+            .map(|f| footer_text(&f))
+    }
+}
+
+/// Newline-joined labels of the chart/diagram part behind `rid`; None if
+/// the part is missing, unparsable or has no text
+fn graphic_text(
+    buffer: &[u8],
+    relationships: &HashMap<String, String>,
+    rid: &str,
+) -> Option<String> {
+    let target = relationships.get(rid)?;
+    let xml = ooxml::read_part(buffer, &ooxml::document_part_name(target))?;
+    let labels = ooxml::read_graphic_text(&xml)?;
+    Some(labels.join("\n")).filter(|text| !text.is_empty())
+}
+
+/// Header and footer segments of every section
+///
+/// docx-rs only loads the parts of the last section, so the others are
+/// read from their relationships. A section without its own reference
+/// for a slot inherits the previous section's, as in Word. Identical
+/// texts are emitted once (under the first slot they appear in) with
+/// `repeat_count` set to the number of section slots showing them.
+fn header_footer_segments(
+    docx: &docx_rs::Docx,
+    buffer: &[u8],
+) -> (Vec<TextSegment>, Vec<TextSegment>) {
+    let relationships = ooxml::read_part(buffer, ooxml::DOCUMENT_RELS_PART)
+        .map(|xml| ooxml::read_relationship_targets(&xml))
+        .unwrap_or_default();
+    let sections = docx
+        .document
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            docx_rs::DocumentChild::Paragraph(para) => para.property.section_property.as_ref(),
+            _ => None,
+        })
+        .chain([&docx.document.section_property]);
+
+    let mut part_texts: HashMap<(usize, &str), Option<String>> = HashMap::new();
+    let mut inherited: [Option<&str>; 6] = [None; 6];
+    // (slot, text, count) in first-seen order
+    let mut seen: Vec<(usize, String, u32)> = Vec::new();
+    for section in sections {
+        for (slot, rid) in section_references(section).into_iter().enumerate() {
+            if rid.is_some() {
+                inherited[slot] = rid;
+            }
+            let Some(rid) = inherited[slot] else {
+                continue;
+            };
+            let text = part_texts
+                .entry((slot, rid))
+                .or_insert_with(|| header_footer_part_text(buffer, &relationships, slot, rid));
+            let Some(text) = text.as_ref().filter(|t| !t.trim().is_empty()) else {
+                continue;
+            };
+            let is_header = slot < 3;
+            match seen
+                .iter_mut()
+                .find(|(s, t, _)| (*s < 3) == is_header && t == text)
+            {
+                Some((_, _, count)) => *count += 1,
+                None => seen.push((slot, text.clone(), 1)),
+            }
+        }
+    }
+
+    let (headers, footers): (Vec<_>, Vec<_>) = seen
+        .into_iter()
+        .map(|(slot, text, count)| {
+            let mut segment =
+                TextSegment::with_section(text, HEADER_FOOTER_SLOTS[slot].to_string());
+            segment.repeat_count = Some(count);
+            (slot, segment)
+        })
+        .partition(|(slot, _)| *slot < 3);
+    (
+        headers.into_iter().map(|(_, s)| s).collect(),
+        footers.into_iter().map(|(_, s)| s).collect(),
+    )
+}
+
+/// Copy core and extended document properties into the metadata map
+///
+/// Only fields present in the package are set. Dates are W3CDTF in
+/// `core.xml`, which is already an ISO-8601 profile, so they pass through.
+/// `protection` is the enforced editing restriction from `settings.xml`.
+fn set_document_properties(metadata: &mut Map<String, Value>, buffer: &[u8]) {
+    if let Some(xml) = ooxml::read_part(buffer, ooxml::CORE_PROPERTIES_PART) {
+        let properties = ooxml::read_properties(&xml);
+        let fields = [
+            ("title", "title"),
+            ("subject", "subject"),
+            ("creator", "author"),
+            ("lastModifiedBy", "last_modified_by"),
+            ("created", "created"),
+            ("modified", "modified"),
+        ];
+        for (element, key) in fields {
+            if let Some(value) = properties.get(element) {
+                metadata.insert(key.to_string(), value.clone().into());
+            }
+        }
+    }
+
+    // Editing restrictions (read-only, comments/forms only) when enforced
+    let protection = ooxml::read_part(buffer, ooxml::SETTINGS_PART)
+        .and_then(|xml| ooxml::read_document_protection(&xml));
+    if let Some(protection) = protection {
+        metadata.insert("protection".to_string(), protection.into());
+    }
+
+    if let Some(xml) = ooxml::read_part(buffer, ooxml::APP_PROPERTIES_PART) {
+        let properties = ooxml::read_properties(&xml);
+        // Word's own saved count goes under its own key; `word_count` is ours
+        let fields = [("Words", "app_word_count"), ("Pages", "page_count")];
+        for (element, key) in fields {
+            if let Some(value) = properties.get(element).and_then(|v| v.parse::<i64>().ok()) {
+                metadata.insert(key.to_string(), value.into());
+            }
+        }
+    }
+}
+
+/// Document default font and theme color scheme, for callers that
+/// re-render extracted content
+///
+/// `default_font` is the docDefaults Latin font, resolved through the
+/// theme when it names a theme slot; `theme_colors` maps scheme names
+/// (`dk1`, `accent1`, `hlink`, ...) to `#RRGGBB`. Keys are only set when
+/// the parts provide them.
+fn set_theme(metadata: &mut Map<String, Value>, buffer: &[u8]) {
+    let theme = ooxml::read_part(buffer, ooxml::THEME_PART)
+        .map(|xml| ooxml::read_theme(&xml))
+        .unwrap_or_default();
+    let default_font = ooxml::read_part(buffer, ooxml::STYLES_PART)
+        .and_then(|xml| ooxml::read_default_font(&xml))
+        .and_then(|font| match font {
+            ooxml::DefaultFont::Named(name) => Some(name),
+            ooxml::DefaultFont::Theme(slot) => theme.font(&slot).map(str::to_string),
+        });
+
+    if let Some(font) = default_font {
+        metadata.insert("default_font".to_string(), font.into());
+    }
+    if !theme.colors.is_empty() {
+        let colors = theme
+            .colors
+            .into_iter()
+            .map(|(name, hex)| (name, hex.into()))
+            .collect();
+        metadata.insert("theme_colors".to_string(), Value::Object(colors));
+    }
+}
+
+/// Deduplicated hyperlink targets of the whole document, independent of
+/// which segments were emitted: external URLs from the relationships part
+/// as `hyperlinks`, bookmark names of internal links as `hyperlink_anchors`
+fn set_hyperlink_targets(metadata: &mut Map<String, Value>, buffer: &[u8], document_xml: &[u8]) {
+    fn dedup(values: impl Iterator<Item = String>) -> Vec<Value> {
+        let mut seen = std::collections::HashSet::new();
+        values
+            .filter(|v| seen.insert(v.clone()))
+            .map(Value::from)
+            .collect()
+    }
+
+    let targets = ooxml::read_part(buffer, ooxml::DOCUMENT_RELS_PART)
+        .map(|xml| ooxml::read_hyperlink_targets(&xml))
+        .unwrap_or_default();
+    let urls = targets.into_iter().filter(|t| t.external).map(|t| t.target);
+    metadata.insert("hyperlinks".to_string(), dedup(urls).into());
+
+    let anchors = ooxml::read_hyperlink_anchors(document_xml).into_iter();
+    metadata.insert("hyperlink_anchors".to_string(), dedup(anchors).into());
+}
+
+/// Convert a JSON metadata value into the equivalent Python object
+fn json_to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    match value {
+        Value::Null => Ok(py.None()),
+        Value::Bool(b) => b.into_py_any(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_py_any(py),
+            (None, Some(u)) => u.into_py_any(py),
+            _ => n.as_f64().unwrap_or_default().into_py_any(py),
+        },
+        Value::String(s) => s.into_py_any(py),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into_py_any(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, json_to_py(py, item)?)?;
+            }
+            dict.into_py_any(py)
+        }
+    }
+}
+
+/// Output of the extraction core, in plain Rust types
+///
+/// Assembled without holding the GIL. Mirrors `ExtractionResult`, but
+/// keeps metadata as JSON until `into_result` converts it to a Python dict.
+pub struct ExtractionData {
+    pub segments: Vec<TextSegment>,
+    pub tables: Vec<TableSegment>,
+    pub metadata: Map<String, Value>,
+    pub processing_time_ms: f64,
+    /// Length of the buffer actually processed (as read, before decryption)
+    pub file_size_bytes: i64,
+    pub errors: Vec<ExtractionError>,
+    pub truncated: bool,
+    pub source_path: Option<String>,
+}
+
+impl ExtractionData {
+    /// Output for an extraction that failed before any content was read
+    fn failed(error: ExtractionError, file_size: i64, start_time: Instant) -> Self {
+        ExtractionData {
+            segments: vec![],
+            tables: vec![],
+            metadata: Map::new(),
+            processing_time_ms: start_time.elapsed().as_secs_f64() * 1000.0,
+            file_size_bytes: file_size,
+            errors: vec![error],
+            truncated: false,
+            source_path: None,
+        }
+    }
+
+    fn into_result(self, py: Python<'_>) -> PyResult<ExtractionResult> {
+        let metadata = PyDict::new(py);
+        for (key, value) in &self.metadata {
+            metadata.set_item(key, json_to_py(py, value)?)?;
+        }
+        // Resolved from Cargo.lock by build.rs
+        metadata.set_item("docx_rs_version", env!("DOCX_RS_VERSION"))?;
+        let hash = content_hash(&self.segments);
+
+        Ok(ExtractionResult {
+            segments: self.segments,
+            tables: self.tables,
+            metadata: metadata.unbind(),
+            processing_time_ms: self.processing_time_ms,
+            file_size_bytes: self.file_size_bytes,
+            errors: self.errors,
+            truncated: self.truncated,
+            source_path: self.source_path,
+            extractor: "docx_rust".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            content_hash: hash,
+        })
+    }
+}
+
+/// SHA-256 over the segment texts, whitespace-collapsed and joined with
+/// newlines; empty segments (break markers) are skipped, so the hash only
+/// changes with the extracted content
+fn content_hash(segments: &[TextSegment]) -> String {
+    use sha2::Digest;
+
+    let mut hasher = sha2::Sha256::new();
+    let texts = segments
+        .iter()
+        .map(|s| collapse_whitespace(&s.text))
+        .filter(|text| !text.is_empty());
+    for (i, text) in texts.enumerate() {
+        if i > 0 {
+            hasher.update(b"\n");
+        }
+        hasher.update(text.as_bytes());
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Extract text from DOCX file
+///
+/// Args:
+///     file_path: Path to DOCX file
+///     options: ExtractOptions with all extraction settings (default
+///         ExtractOptions())
+///     progress_callback: Called as `progress_callback(processed, total)`
+///         every 100 body paragraphs/tables and once when the body is done,
+///         both counted within `start_index`..`end_index`;
+///         an exception raised by it cancels extraction, returning the
+///         partial result with a CANCELLED error
+///     **shortcuts: Any ExtractOptions field (`password=...`,
+///         `max_segments=...`, `include_headers_footers=False`, ...),
+///         overriding the same field of `options`; calls written before
+///         `options` existed keep working
+///
+/// Returns:
+///     ExtractionResult with text segments and metadata
+#[pyfunction]
+#[pyo3(signature = (file_path, options=None, *, progress_callback=None, **shortcuts))]
+fn extract_docx(
+    py: Python,
+    file_path: String,
+    options: Option<ExtractOptions>,
+    progress_callback: Option<PyObject>,
+    shortcuts: Option<&Bound<'_, PyDict>>,
+) -> PyResult<ExtractionResult> {
+    let opts = ExtractOptions::with_shortcuts(py, options, shortcuts)?;
+    with_progress(progress_callback.as_ref(), |progress| {
+        extract_path(&file_path, &opts, progress)
+    })
+    .into_result(py)
+}
+
+/// Extract text from DOCX file with the GIL released
+///
+/// Same arguments and result as `extract_docx`, but reading and parsing
+/// run without the GIL; it is only re-acquired to build the result
+/// objects. Use this when extracting on a thread pool next to other
+/// Python work.
+///
+/// Returns:
+///     ExtractionResult with text segments and metadata
+#[pyfunction]
+#[pyo3(signature = (file_path, options=None, *, progress_callback=None, **shortcuts))]
+fn extract_docx_nogil(
+    py: Python,
+    file_path: String,
+    options: Option<ExtractOptions>,
+    progress_callback: Option<PyObject>,
+    shortcuts: Option<&Bound<'_, PyDict>>,
+) -> PyResult<ExtractionResult> {
+    let opts = ExtractOptions::with_shortcuts(py, options, shortcuts)?;
+    with_progress(progress_callback.as_ref(), |progress| {
+        py.allow_threads(|| extract_path(&file_path, &opts, progress))
+    })
+    .into_result(py)
+}
+
+/// Read and extract a DOCX file without touching Python state
+pub fn extract_path(
+    file_path: &str,
+    opts: &ExtractOptions,
+    progress: Option<Progress<'_>>,
+) -> ExtractionData {
+    let start_time = Instant::now();
+
+    // Try to read file
+    let mut file = match File::open(file_path) {
+        Ok(f) => f,
+        Err(e) => {
+            // File not found or permission denied
+            let error = ExtractionError::from_code(
+                ErrorCode::FileNotFound,
+                format!("Failed to open file: {}", e),
+            );
+
+            return ExtractionData {
+                source_path: Some(file_path.to_string()),
+                ..ExtractionData::failed(error, 0, start_time)
+            };
+        }
+    };
+
+    // The stat is only an allocation hint: the file may change before it
+    // is read, so the reported size is what was actually read
+    let size_hint = file.metadata().map_or(0, |m| m.len() as usize);
+    let mut buffer = Vec::with_capacity(size_hint);
+    if let Err(e) = file.read_to_end(&mut buffer) {
+        let error =
+            ExtractionError::from_code(ErrorCode::ReadError, format!("Failed to read file: {}", e));
+
+        return ExtractionData {
+            source_path: Some(file_path.to_string()),
+            ..ExtractionData::failed(error, buffer.len() as i64, start_time)
+        };
+    }
+
+    let read_ms = elapsed_ms(start_time);
+
+    let mut output = extract_with_progress(&buffer, opts, progress);
+
+    // Report time including file I/O
+    output.processing_time_ms = elapsed_ms(start_time);
+    output.source_path = Some(file_path.to_string());
+    if let Some(Value::Object(timing)) = output.metadata.get_mut("timing") {
+        timing.insert("read_ms".to_string(), read_ms.into());
+    }
+    output
+}
+
+/// Milliseconds since `since`, as reported in the timing fields
+fn elapsed_ms(since: Instant) -> f64 {
+    since.elapsed().as_secs_f64() * 1000.0
+}
+
+/// `metadata["timing"]` once extraction is done; read_ms is filled in by
+/// extract_path, bytes are already in memory here
+fn timing(parse_ms: f64, extract_start: Instant) -> Value {
+    serde_json::json!({
+        "read_ms": 0.0,
+        "parse_ms": parse_ms,
+        "extract_ms": elapsed_ms(extract_start),
+    })
+}
+
+/// Extract text from DOCX bytes already in memory
+///
+/// Same arguments and result as `extract_docx`, with the file content
+/// (e.g. from an upload stream) in place of its path.
+///
+/// Returns:
+///     ExtractionResult with text segments and metadata
+#[pyfunction]
+#[pyo3(signature = (data, options=None, *, progress_callback=None, **shortcuts))]
+fn extract_docx_bytes(
+    py: Python,
+    data: &[u8],
+    options: Option<ExtractOptions>,
+    progress_callback: Option<PyObject>,
+    shortcuts: Option<&Bound<'_, PyDict>>,
+) -> PyResult<ExtractionResult> {
+    let opts = ExtractOptions::with_shortcuts(py, options, shortcuts)?;
+    with_progress(progress_callback.as_ref(), |progress| {
+        extract_with_progress(data, &opts, progress)
+    })
+    .into_result(py)
+}
+
+/// Progress hook of the extraction core, called with (processed body
+/// children, total body children); returning false cancels extraction
+pub type Progress<'a> = &'a mut (dyn FnMut(usize, usize) -> bool + Send);
+
+/// Body children (paragraphs, tables, ...) between progress reports
+const PROGRESS_INTERVAL: usize = 100;
+
+/// Run `extract` with a Python progress callback adapted to [`Progress`]
+///
+/// The GIL is re-acquired for each call, so this also works inside
+/// `allow_threads`. An exception raised by the callback cancels the
+/// extraction; the partial output is kept and its CANCELLED error names
+/// the exception.
+fn with_progress(
+    callback: Option<&PyObject>,
+    extract: impl FnOnce(Option<Progress<'_>>) -> ExtractionData,
+) -> ExtractionData {
+    let Some(callback) = callback else {
+        return extract(None);
+    };
+
+    let mut error = None;
+    let mut report = |processed: usize, total: usize| {
+        Python::with_gil(|py| callback.call1(py, (processed, total)))
+            .map_err(|e| error = Some(e))
+            .is_ok()
+    };
+    let mut output = extract(Some(&mut report));
+
+    if let Some(e) = error {
+        for err in &mut output.errors {
+            if err.error_code() == Some(ErrorCode::Cancelled) {
+                err.message = format!("Progress callback raised: {}", e);
+            }
+        }
+    }
+    output
+}
+
+/// Default cap on the total decompressed size of a package, well above
+/// real documents but far below what a zip bomb inflates to
+const DEFAULT_MAX_DECOMPRESSED_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Extraction settings shared by all entry points
+///
+/// Args:
+///     include_headers_footers: Emit header/footer parts as segments (default True)
+///     accept_revisions: Apply tracked changes (drop deletions, keep insertions)
+///         instead of emitting them as separate segments (default False)
+///     max_segments: Stop after this many segments (default unlimited)
+///     max_chars: Stop after this many characters in total, cutting the
+///         segment that crosses the limit (default unlimited)
+///     flatten_tables: Also emit each table as a tab/newline-joined text
+///         segment; structured rows are always in `tables` (default True)
+///     cell_sep, row_sep: Separators after each cell and row of flattened
+///         tables; their characters are replaced by spaces inside cells
+///         (default "\t" and "\n")
+///     emphasis_markers: Wrap bold/italic text in `**`/`_` Markdown markers
+///         and flag segments containing underlined text (default False)
+///     timeout_ms: Stop after this many milliseconds, returning the segments
+///         collected so far with a TIMEOUT error (default unlimited)
+///     password: Password for protected (encrypted) documents
+///     normalize_whitespace: Collapse whitespace runs (including tabs and
+///         non-breaking spaces) to single spaces and trim each segment
+///         (default False)
+///     ascii_fold: Replace smart quotes, dashes, ellipses and special
+///         spaces in segments with ASCII approximations (default False)
+///     min_confidence: Drop segments scoring below this confidence, e.g.
+///         text boxes (0.7) or stale field results (0.5) (default keep all)
+///     detailed_runs: Record each segment's bold/italic/color runs as byte
+///         ranges in `runs`, for highlighting (default False)
+///     max_decompressed_bytes: Refuse packages inflating to more than this
+///         many bytes with SIZE_LIMIT_EXCEEDED (default 512 MiB; None for
+///         unlimited)
+///     tables_only: Return only the structured body tables, skipping prose,
+///         headers/footers, notes and comments; limits count each table
+///         as one segment (default False)
+///     start_index, end_index: Only process body paragraphs/tables with
+///         `start_index <= index < end_index`, e.g. `end_index=50` for a
+///         preview; clamped to the document (default all)
+#[pyclass]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtractOptions {
+    #[pyo3(get, set)]
+    pub include_headers_footers: bool,
+    #[pyo3(get, set)]
+    pub accept_revisions: bool,
+    #[pyo3(get, set)]
+    pub max_segments: Option<usize>,
+    #[pyo3(get, set)]
+    pub max_chars: Option<usize>,
+    #[pyo3(get, set)]
+    pub flatten_tables: bool,
+    #[pyo3(get, set)]
+    pub cell_sep: String,
+    #[pyo3(get, set)]
+    pub row_sep: String,
+    #[pyo3(get, set)]
+    pub emphasis_markers: bool,
+    #[pyo3(get, set)]
+    pub timeout_ms: Option<u64>,
+    #[pyo3(get, set)]
+    pub password: Option<String>,
+    #[pyo3(get, set)]
+    pub normalize_whitespace: bool,
+    #[pyo3(get, set)]
+    pub ascii_fold: bool,
+    #[pyo3(get, set)]
+    pub min_confidence: Option<f64>,
+    #[pyo3(get, set)]
+    pub detailed_runs: bool,
+    #[pyo3(get, set)]
+    pub max_decompressed_bytes: Option<u64>,
+    #[pyo3(get, set)]
+    pub tables_only: bool,
+    #[pyo3(get, set)]
+    pub start_index: Option<usize>,
+    #[pyo3(get, set)]
+    pub end_index: Option<usize>,
+}
+
+#[pymethods]
+impl ExtractOptions {
+    #[new]
+    #[pyo3(signature = (*, include_headers_footers=true, accept_revisions=false, max_segments=None, max_chars=None, flatten_tables=true, cell_sep=String::from("\t"), row_sep=String::from("\n"), emphasis_markers=false, timeout_ms=None, password=None, normalize_whitespace=false, ascii_fold=false, min_confidence=None, detailed_runs=false, max_decompressed_bytes=Some(DEFAULT_MAX_DECOMPRESSED_BYTES), tables_only=false, start_index=None, end_index=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        include_headers_footers: bool,
+        accept_revisions: bool,
+        max_segments: Option<usize>,
+        max_chars: Option<usize>,
+        flatten_tables: bool,
+        cell_sep: String,
+        row_sep: String,
+        emphasis_markers: bool,
+        timeout_ms: Option<u64>,
+        password: Option<String>,
+        normalize_whitespace: bool,
+        ascii_fold: bool,
+        min_confidence: Option<f64>,
+        detailed_runs: bool,
+        max_decompressed_bytes: Option<u64>,
+        tables_only: bool,
+        start_index: Option<usize>,
+        end_index: Option<usize>,
+    ) -> Self {
+        ExtractOptions {
+            include_headers_footers,
+            accept_revisions,
+            max_segments,
+            max_chars,
+            flatten_tables,
+            cell_sep,
+            row_sep,
+            emphasis_markers,
+            timeout_ms,
+            password,
+            normalize_whitespace,
+            ascii_fold,
+            min_confidence,
+            detailed_runs,
+            max_decompressed_bytes,
+            tables_only,
+            start_index,
+            end_index,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        // The password is never echoed back
+        let mut shown = self.clone();
+        shown.password = shown.password.map(|_| "***".to_string());
+        format!("{:?}", shown)
+    }
+}
+
+/// Same defaults as the Python constructor
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        ExtractOptions {
+            include_headers_footers: true,
+            accept_revisions: false,
+            max_segments: None,
+            max_chars: None,
+            flatten_tables: true,
+            cell_sep: "\t".to_string(),
+            row_sep: "\n".to_string(),
+            emphasis_markers: false,
+            timeout_ms: None,
+            password: None,
+            normalize_whitespace: false,
+            ascii_fold: false,
+            min_confidence: None,
+            detailed_runs: false,
+            max_decompressed_bytes: Some(DEFAULT_MAX_DECOMPRESSED_BYTES),
+            tables_only: false,
+            start_index: None,
+            end_index: None,
+        }
+    }
+}
+
+impl ExtractOptions {
+    /// `options` (or the defaults) with the keyword shortcuts of the
+    /// Python entry points applied on top. Each keyword is set through the
+    /// field's Python setter, so it is type-checked the same way; unknown
+    /// names raise TypeError like any unexpected keyword argument.
+    fn with_shortcuts(
+        py: Python,
+        options: Option<ExtractOptions>,
+        shortcuts: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Self> {
+        let opts = Bound::new(py, options.unwrap_or_default())?;
+        for (name, value) in shortcuts.into_iter().flatten() {
+            let name: String = name.extract()?;
+            opts.setattr(name.as_str(), value).map_err(|e| {
+                if e.is_instance_of::<PyAttributeError>(py) {
+                    PyTypeError::new_err(format!("unexpected keyword argument '{}'", name))
+                } else {
+                    e
+                }
+            })?;
+        }
+        let opts = opts.borrow().clone();
+        Ok(opts)
+    }
+}
+
+/// Extract a DOCX held in memory, without Python
+///
+/// Failures are reported in `ExtractionData::errors`, never as a panic.
+pub fn extract(buffer: &[u8], opts: ExtractOptions) -> ExtractionData {
+    extract_with_progress(buffer, &opts, None)
+}
+
+/// Shared extraction core for the path- and bytes-based entry points,
+/// reporting to `progress` as the body is walked
+pub fn extract_with_progress(
+    buffer: &[u8],
+    opts: &ExtractOptions,
+    progress: Option<Progress<'_>>,
+) -> ExtractionData {
+    let start_time = Instant::now();
+    let file_size = buffer.len() as i64;
+    let ExtractOptions {
+        include_headers_footers,
+        accept_revisions,
+        max_segments,
+        max_chars,
+        flatten_tables,
+        emphasis_markers,
+        timeout_ms,
+        normalize_whitespace,
+        ascii_fold,
+        min_confidence,
+        detailed_runs,
+        max_decompressed_bytes,
+        tables_only,
+        start_index,
+        end_index,
+        ..
+    } = *opts;
+    let password = opts.password.as_deref();
+    let (cell_sep, row_sep) = (opts.cell_sep.as_str(), opts.row_sep.as_str());
+    let mut metadata = Map::new();
+
+    if buffer.is_empty() {
+        let error =
+            ExtractionError::from_code(ErrorCode::EmptyFile, "File is empty (0 bytes)".to_string());
+
+        return ExtractionData::failed(error, file_size, start_time);
+    }
+
+    let deadline = timeout_ms.map(|ms| start_time + Duration::from_millis(ms));
+
+    // OLE compound files are either password-protected packages or
+    // legacy Word 97-2003 documents, never a plain zip
+    let decrypted;
+    let buffer = if buffer.starts_with(&ooxml::OLE_MAGIC) {
+        if !encryption::is_encrypted(buffer) {
+            let error = ExtractionError::from_code(
+                ErrorCode::LegacyDocFormat,
+                "File is a legacy .doc (OLE) document; convert it to .docx first \
+                 (e.g. Save As in Word or `soffice --convert-to docx`)"
+                    .to_string(),
+            );
+
+            return ExtractionData::failed(error, file_size, start_time);
+        }
+
+        let decryption = match password {
+            Some(password) => encryption::decrypt(buffer, password, deadline),
+            None => Err(encryption::DecryptError::WrongPassword),
+        };
+        match decryption {
+            Ok(data) => {
+                decrypted = data;
+                &decrypted[..]
+            }
+            Err(encryption::DecryptError::TimedOut) => {
+                let error = ExtractionError::from_code(
+                    ErrorCode::Timeout,
+                    format!(
+                        "Decryption stopped after timeout_ms={}",
+                        timeout_ms.unwrap_or_default()
+                    ),
+                );
+
+                return ExtractionData::failed(error, file_size, start_time);
+            }
+            Err(e) => {
+                let message = match (password, e) {
+                    (None, _) => {
+                        "Document is password-protected; a password is required".to_string()
+                    }
+                    (Some(_), e) => format!("Failed to decrypt protected document: {}", e),
+                };
+                let error = ExtractionError::from_code(ErrorCode::Encrypted, message);
+
+                return ExtractionData::failed(error, file_size, start_time);
+            }
+        }
+    } else {
+        buffer
+    };
+
+    if let Some(limit) = max_decompressed_bytes {
+        if ooxml::exceeds_decompressed_size(buffer, limit) {
+            let error = ExtractionError::from_code(
+                ErrorCode::SizeLimitExceeded,
+                format!("Package decompresses to more than {} bytes", limit),
+            );
+
+            return ExtractionData::failed(error, file_size, start_time);
+        }
+    }
+
+    // A readable zip without a main document part is some other package
+    if let Some(names) = ooxml::part_names(buffer) {
+        if !names.iter().any(|name| name == ooxml::DOCUMENT_PART) {
+            let error = ExtractionError::from_code(
+                ErrorCode::NotADocx,
+                format!("ZIP archive has no {} part", ooxml::DOCUMENT_PART),
+            );
+
+            return ExtractionData::failed(error, file_size, start_time);
+        }
+    }
+
+    // Stray bytes in a text run would fail the whole part; keep the text
+    // with replacement characters instead
+    let repaired = ooxml::repair_utf8(buffer);
+    let (buffer, repaired_parts) = match &repaired {
+        Some((package, parts)) => {
+            log::warn!("Replaced invalid UTF-8 in {:?}", parts);
+            (&package[..], parts.clone())
+        }
+        None => (buffer, Vec::new()),
+    };
+
+    // Parse DOCX; docx-rs indexes and unwraps freely, so some malformed
+    // inputs panic instead of returning Err
+    let docx = match std::panic::catch_unwind(|| docx_rs::read_docx(buffer)) {
+        Ok(Ok(d)) => d,
+        Err(panic) => {
+            let reason = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".to_string());
+            let error = ExtractionError::from_code(
+                ErrorCode::ParserPanic,
+                format!("DOCX parser panicked: {}", reason),
+            );
+
+            return ExtractionData::failed(error, file_size, start_time);
+        }
+        Ok(Err(e)) => {
+            let error = ExtractionError::from_code(
+                ErrorCode::Corrupted,
+                format!("Failed to parse DOCX: {}", e),
+            );
+
+            return ExtractionData::failed(error, file_size, start_time);
+        }
+    };
+    // Decryption and package checks count as parsing
+    let parse_ms = elapsed_ms(start_time);
+    let extract_start = Instant::now();
+
+    let end = end_index.map_or(docx.document.children.len(), |end| {
+        end.min(docx.document.children.len())
+    });
+    let start = start_index.unwrap_or(0).min(end);
+
+    // Extract text from document
+    let mut segments = SegmentCollector::new(
+        max_segments,
+        max_chars,
+        deadline,
+        normalize_whitespace,
+        ascii_fold,
+        min_confidence,
+    );
+    segments.lossy_text = !repaired_parts.is_empty();
+    let mut tables = Vec::new();
+    let mut errors = Vec::new();
+    let mut paragraph_count = 0usize;
+    // Body children with no extraction yet, counted by variant
+    let mut skipped_children: BTreeMap<String, usize> = BTreeMap::new();
+
+    // External hyperlink targets keyed by relationship id
+    let relationships: HashMap<String, String> = docx
+        .hyperlinks
+        .iter()
+        .map(|(rid, url, _mode)| (rid.clone(), url.clone()))
+        .collect();
+
+    // Headers/footers are stored per section, not per page; identical
+    // ones across sections are emitted once
+    let mut footer_segments = Vec::new();
+    if include_headers_footers && !tables_only {
+        let (headers, footers) = header_footer_segments(&docx, buffer);
+        for segment in headers {
+            segments.push(segment);
+        }
+        footer_segments = footers;
+    }
+
+    let mut list_counters = ListCounters::new(&docx.numberings);
+    let mut fields = FieldTracker::default();
+
+    // Inline footnote/endnote markers and image alt text
+    // (docx-rs drops both while reading runs)
+    let document_xml = ooxml::read_part(buffer, ooxml::DOCUMENT_PART).unwrap_or_default();
+    let note_references = ooxml::read_note_references(&document_xml);
+    let image_alts = ooxml::read_image_alts(&document_xml);
+    let graphic_parts = ooxml::read_graphic_parts(&document_xml);
+    let document_rels = ooxml::read_part(buffer, ooxml::DOCUMENT_RELS_PART)
+        .map(|xml| ooxml::read_relationship_targets(&xml))
+        .unwrap_or_default();
+    let paragraph_bidi = ooxml::read_paragraph_bidi(&document_xml);
+    let run_languages = ooxml::read_run_languages(&document_xml);
+    let sdt_tags = ooxml::read_sdt_tags(&document_xml);
+    let default_language = ooxml::read_part(buffer, ooxml::STYLES_PART)
+        .and_then(|xml| ooxml::read_default_language(&xml));
+    let breaks = ooxml::read_breaks(&document_xml);
+    let page_breaks: Vec<_> = breaks.iter().filter(|b| b.starts_page()).cloned().collect();
+
+    // Break markers are interleaved in document order as the body is walked
+    let mut pending_breaks = breaks.iter().filter(|b| b.child_index >= start).peekable();
+    let mut push_breaks = |segments: &mut SegmentCollector, idx: usize, offset: usize| {
+        while let Some(brk) = pending_breaks.next_if(|b| (b.child_index, b.offset) <= (idx, offset))
+        {
+            let page = estimated_page(&page_breaks, brk.child_index, brk.offset);
+            if let Some(marker) = break_marker(brk, page) {
+                segments.push(marker);
+            }
+        }
+    };
+
+    let mut progress = progress;
+    // Counted within the start_index..end_index range being processed
+    let mut report_progress = |processed: usize| match progress.as_mut() {
+        Some(report) => report(processed, end - start),
+        None => true,
+    };
+
+    // Structured rows always; the flattened text as a segment on request,
+    // and a table whose segment the collector refuses is dropped whole.
+    // Numbered by position in `tables`, not by body child: tables nested in
+    // one content control share a child index. With `tables_only` every
+    // table's text still goes through the collector, so limits apply to
+    // tables as they would to segments, and the segments are dropped later
+    let push_table = |table: &docx_rs::Table,
+                      idx: usize,
+                      segments: &mut SegmentCollector,
+                      tables: &mut Vec<TableSegment>| {
+        let section = format!("table_{}", tables.len());
+
+        // Extract text from tables
+        let table_text = table_text_with(table, cell_sep, row_sep);
+
+        if tables_only || (flatten_tables && !table_text.trim().is_empty()) {
+            let mut segment = TextSegment::with_section(table_text, section.clone());
+            segment.page = Some(estimated_page(&page_breaks, idx, 0));
+            segment.links = table_links(table, &relationships);
+            segment.language = run_languages
+                .get(&idx)
+                .and_then(|runs| ooxml::language_between(runs, 0, usize::MAX))
+                .map(str::to_string);
+            let collected = segments.segments.len();
+            segments.push(segment);
+            if segments.segments.len() == collected {
+                return;
+            }
+        }
+
+        tables.push(TableSegment::new(
+            table_rows(table),
+            Some(section),
+            table_cells(table),
+        ));
+    };
+
+    // A paragraph's spans with list markers, note markers, revisions and
+    // breaks interleaved. `offset` is where it starts within its body child
+    // (content controls hold several); `closes_child` if no paragraph of the
+    // child follows. Returns whether anything was emitted and its length.
+    let push_paragraph = |para: &docx_rs::Paragraph,
+                          idx: usize,
+                          section_prefix: &str,
+                          (offset, closes_child): (usize, bool),
+                          segments: &mut SegmentCollector,
+                          list_counters: &mut ListCounters,
+                          fields: &mut FieldTracker,
+                          push_breaks: &mut dyn FnMut(&mut SegmentCollector, usize, usize)|
+     -> (bool, usize) {
+        let mut spans: Vec<_> =
+            paragraph_spans_with_runs(para, accept_revisions, emphasis_markers, detailed_runs);
+        let underline = emphasis_markers
+            && coalesce_runs(para, accept_revisions)
+                .iter()
+                .any(|(_, format, _)| format.underline);
+        let mut list_level = None;
+        let heading = heading_level(para, &docx.styles);
+        let style = paragraph_style(para, &docx.styles);
+        let field = fields.paragraph_field(para);
+
+        // Note markers go into whichever span their offset falls in
+        let mut span_start = offset;
+        let mut opens_later = offset > 0;
+        let span_count = spans.len();
+        let mut span_pages = Vec::with_capacity(span_count);
+        let mut span_ranges = Vec::with_capacity(span_count);
+        for (span_idx, (span, _, runs)) in spans.iter_mut().enumerate() {
+            span_pages.push(estimated_page(&page_breaks, idx, span_start));
+            let span_end = span_start + span.chars().count();
+            span_ranges.push((span_start, span_end));
+            let markers: Vec<_> = note_references
+                .iter()
+                .filter(|r| r.child_index == idx)
+                // A marker on the boundary closes the previous paragraph
+                .filter(|r| r.offset > span_start || (r.offset == span_start && !opens_later))
+                .filter(|r| {
+                    r.offset < span_end
+                        || (span_idx + 1 == span_count && (closes_child || r.offset == span_end))
+                })
+                .collect();
+            insert_note_markers(span, &markers, span_start, runs);
+            span_start = span_end;
+            opens_later = false;
+        }
+
+        if let Some(docx_rs::NumberingProperty {
+            id: Some(num_id),
+            level,
+        }) = &para.property.numbering_property
+        {
+            let ilvl = level.as_ref().map_or(0, |l| l.val);
+            if let Some(marker) = list_counters.next_marker(num_id.id, ilvl) {
+                if let Some((first, _, runs)) = spans.first_mut() {
+                    first.insert_str(0, &marker);
+                    shift_runs(runs, 0, marker.len());
+                }
+                list_level = Some(ilvl as i32);
+            }
+        }
+
+        // Only add non-empty spans; paragraph-level attributes
+        // (links, list level) go on the first one
+        let mut first = true;
+        for (((span, revision, runs), page), (start, end)) in
+            spans.into_iter().zip(span_pages).zip(span_ranges)
+        {
+            push_breaks(segments, idx, start);
+            if span.trim().is_empty() {
+                continue;
+            }
+            let section = match (&field.field_type, heading) {
+                (Some(_), _) => "field".to_string(),
+                (None, Some(_)) => "heading".to_string(),
+                (None, None) => format!("{}_{}", section_prefix, idx),
+            };
+            let mut segment = TextSegment::with_section(span, section);
+            segment.field_type = field.field_type.clone();
+            segment.runs = runs;
+            if field.stale {
+                segment.confidence = STALE_FIELD_CONFIDENCE;
+            }
+            segment.page = Some(page);
+            segment.underline = underline;
+            segment.heading_level = heading;
+            segment.style = style.clone();
+            segment.direction = paragraph_bidi
+                .get(&idx)
+                .map(|&rtl| if rtl { "rtl" } else { "ltr" }.to_string());
+            segment.change_type = revision.map(|r| r.change_type().to_string());
+            segment.language = run_languages
+                .get(&idx)
+                .and_then(|runs| ooxml::language_between(runs, start, end))
+                .map(str::to_string);
+            // Only the control's own prose: its tables, text boxes, images
+            // and charts are not content-control values
+            segment.sdt_tag = sdt_tags.get(&idx).cloned();
+            if first {
+                segment.links = paragraph_links(para, &relationships);
+                segment.list_level = list_level;
+                first = false;
+            }
+            segments.push(segment);
+        }
+        (!first, span_start - offset)
+    };
+
+    // Extract paragraphs
+    let body = docx.document.children.iter().enumerate();
+    for (idx, child) in body.take(end).skip(start) {
+        if segments.is_full() {
+            break;
+        }
+        segments.source_index = Some(idx);
+
+        match child {
+            docx_rs::DocumentChild::Paragraph(_) if tables_only => {}
+            docx_rs::DocumentChild::Paragraph(para) => {
+                let (emitted, _) = push_paragraph(
+                    para,
+                    idx,
+                    "paragraph",
+                    (0, true),
+                    &mut segments,
+                    &mut list_counters,
+                    &mut fields,
+                    &mut push_breaks,
+                );
+                if emitted {
+                    paragraph_count += 1;
+                }
+            }
+            docx_rs::DocumentChild::Table(table) => {
+                push_table(table, idx, &mut segments, &mut tables);
+            }
+            docx_rs::DocumentChild::StructuredDataTag(sdt) => {
+                // Content controls (form fields, repeating sections): their
+                // paragraphs and tables are emitted like body ones. Offsets
+                // of note markers and breaks run across the whole control
+                let last_para = sdt
+                    .children
+                    .iter()
+                    .rposition(|c| matches!(c, docx_rs::StructuredDataTagChild::Paragraph(_)));
+                let mut offset = 0;
+                for (n, sdt_child) in sdt.children.iter().enumerate() {
+                    match sdt_child {
+                        docx_rs::StructuredDataTagChild::Paragraph(_) if tables_only => {}
+                        docx_rs::StructuredDataTagChild::Paragraph(para) => {
+                            let (emitted, len) = push_paragraph(
+                                para,
+                                idx,
+                                "sdt",
+                                (offset, last_para == Some(n)),
+                                &mut segments,
+                                &mut list_counters,
+                                &mut fields,
+                                &mut push_breaks,
+                            );
+                            offset += len;
+                            if emitted {
+                                paragraph_count += 1;
+                            }
+                        }
+                        docx_rs::StructuredDataTagChild::Table(table) => {
+                            push_table(table, idx, &mut segments, &mut tables);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            // Read by bookmark_anchors / comment_anchors
+            docx_rs::DocumentChild::BookmarkStart(_)
+            | docx_rs::DocumentChild::BookmarkEnd(_)
+            | docx_rs::DocumentChild::CommentStart(_)
+            | docx_rs::DocumentChild::CommentEnd(_) => {}
+            other => {
+                let variant = child_variant(other);
+                log::debug!("Skipping unsupported body child {} at {}", variant, idx);
+                *skipped_children.entry(variant).or_default() += 1;
+            }
+        }
+
+        // Text boxes, image alt text, charts and breaks are prose
+        if !tables_only {
+            // Text boxes anchored in this paragraph (or its table's cells)
+            let textboxes = match child {
+                docx_rs::DocumentChild::Paragraph(para) => paragraph_textboxes(para),
+                docx_rs::DocumentChild::Table(table) => table_paragraphs(table)
+                    .into_iter()
+                    .flat_map(paragraph_textboxes)
+                    .collect(),
+                _ => Vec::new(),
+            };
+            for text in textboxes {
+                let mut segment = TextSegment::with_section(text, "textbox".to_string());
+                segment.confidence = TEXTBOX_CONFIDENCE;
+                segment.page = Some(estimated_page(&page_breaks, idx, 0));
+                segment.anchor_paragraph = Some(idx as i32);
+                segments.push(segment);
+            }
+
+            // Alt text of images drawn in this paragraph/table
+            for alt in image_alts.iter().filter(|a| a.child_index == idx) {
+                let mut segment =
+                    TextSegment::with_section(alt.text.clone(), "image_alt".to_string());
+                segment.page = Some(estimated_page(&page_breaks, idx, 0));
+                segment.anchor_paragraph = Some(idx as i32);
+                segment.media_id = alt.media_id.clone();
+                segments.push(segment);
+            }
+
+            // Titles and labels of charts/SmartArt drawn in this paragraph/table
+            for graphic in graphic_parts.iter().filter(|g| g.child_index == idx) {
+                let Some(text) = graphic_text(buffer, &document_rels, &graphic.rel_id) else {
+                    continue;
+                };
+                let mut segment = TextSegment::with_section(text, "chart".to_string());
+                segment.page = Some(estimated_page(&page_breaks, idx, 0));
+                segment.anchor_paragraph = Some(idx as i32);
+                segments.push(segment);
+            }
+
+            // Section breaks (and page breaks not placed before a span) close
+            // the body child
+            push_breaks(&mut segments, idx, usize::MAX);
+        }
+
+        let processed = idx + 1 - start;
+        if processed % PROGRESS_INTERVAL == 0 && !report_progress(processed) {
+            segments.cancel();
+        }
+    }
+    segments.source_index = None;
+    // The body is done; only a limit already hit suppresses the last report
+    if !segments.truncated && !report_progress(end - start) {
+        segments.cancel();
+    }
+
+    // Footnote/endnote bodies, each linked back to its marker's paragraph.
+    // Neither these nor comments are read for `tables_only`
+    let note_kinds = [ooxml::NoteKind::Footnote, ooxml::NoteKind::Endnote];
+    for kind in note_kinds.into_iter().filter(|_| !tables_only) {
+        let Some(xml) = ooxml::read_part(buffer, kind.part_name()) else {
+            continue;
+        };
+
+        for note in ooxml::read_notes(&xml, kind) {
+            if segments.is_full() {
+                break;
+            }
+            if note.text.is_empty() {
+                continue;
+            }
+            let mut segment = TextSegment::with_section(
+                note.text,
+                format!("{}_{}", kind.section_prefix(), note.id),
+            );
+            // Notes print on the page of their marker
+            if let Some(reference) = note_references
+                .iter()
+                .find(|r| r.kind == kind && r.id == note.id)
+            {
+                segment.anchor_paragraph = Some(reference.child_index as i32);
+                segment.page = Some(estimated_page(
+                    &page_breaks,
+                    reference.child_index,
+                    reference.offset,
+                ));
+            }
+            segments.push(segment);
+        }
+    }
+
+    // Reviewer comments, linked to where their range starts
+    let anchors = comment_anchors(&docx.document.children);
+    for comment in docx.comments.inner().iter().filter(|_| !tables_only) {
+        if segments.is_full() {
+            break;
+        }
+        let text = comment_text(comment);
+        if text.is_empty() {
+            continue;
+        }
+        let mut segment = TextSegment::with_section(text, "comment".to_string());
+        segment.comment_author = Some(comment.author.clone()).filter(|a| !a.is_empty());
+        segment.comment_ref_paragraph = anchors.get(&comment.id).copied();
+        segment.page = segment
+            .comment_ref_paragraph
+            .map(|idx| estimated_page(&page_breaks, idx, 0));
+        segment.reply_to = comment.parent_comment_id.map(|id| id.to_string());
+        segments.push(segment);
+    }
+
+    // Footers follow the body in reading order
+    for segment in footer_segments {
+        segments.push(segment);
+    }
+
+    // Not `is_full()`: a deadline passing after the last push dropped nothing
+    let truncated = segments.truncated;
+    if segments.cancelled {
+        errors.push(ExtractionError::from_code(
+            ErrorCode::Cancelled,
+            "Extraction cancelled by the progress callback".to_string(),
+        ));
+    } else if segments.timed_out {
+        errors.push(ExtractionError::from_code(
+            ErrorCode::Timeout,
+            format!(
+                "Extraction stopped after timeout_ms={}",
+                timeout_ms.unwrap_or_default()
+            ),
+        ));
+    } else if truncated {
+        errors.push(ExtractionError::from_code(
+            ErrorCode::Truncated,
+            format!(
+                "Extraction stopped at a limit (max_segments={:?}, max_chars={:?})",
+                max_segments, max_chars
+            ),
+        ));
+    }
+    let (word_count, char_count) = (segments.words, segments.chars);
+    let mut segments = segments.segments;
+    if tables_only {
+        segments.clear();
+    }
+    // Segments without tagged runs of their own are in the document default
+    for segment in &mut segments {
+        if segment.language.is_none() {
+            segment.language = default_language.clone();
+        }
+    }
+
+    // Add metadata
+    // Non-empty body paragraphs; tables, text boxes, notes etc. are only
+    // in segment_count
+    metadata.insert("paragraph_count".to_string(), paragraph_count.into());
+    metadata.insert("table_count".to_string(), tables.len().into());
+    metadata.insert(
+        "skipped_children".to_string(),
+        Value::Object(
+            skipped_children
+                .into_iter()
+                .map(|(variant, count)| (variant, count.into()))
+                .collect(),
+        ),
+    );
+    if !repaired_parts.is_empty() {
+        metadata.insert("invalid_utf8_parts".to_string(), repaired_parts.into());
+    }
+    metadata.insert("segment_count".to_string(), segments.len().into());
+    metadata.insert("word_count".to_string(), word_count.into());
+    metadata.insert("char_count".to_string(), char_count.into());
+    metadata.insert(
+        "bookmarks".to_string(),
+        bookmark_anchors(&docx.document.children).into(),
+    );
+    set_hyperlink_targets(&mut metadata, buffer, &document_xml);
+    if let Some(language) = default_language {
+        metadata.insert("default_language".to_string(), language.into());
+    }
+    set_document_properties(&mut metadata, buffer);
+    set_theme(&mut metadata, buffer);
+    metadata.insert("timing".to_string(), timing(parse_ms, extract_start));
+
+    let processing_time = start_time.elapsed().as_secs_f64() * 1000.0;
+
+    ExtractionData {
+        segments,
+        tables,
+        metadata,
+        processing_time_ms: processing_time,
+        file_size_bytes: file_size,
+        errors,
+        truncated,
+        source_path: None,
+    }
+}
+
+/// Extract many DOCX files in parallel
+///
+/// Files are parsed on a rayon thread pool with the GIL released. Each
+/// result carries its own errors, so one corrupt file does not fail the
+/// batch; results are returned in input order.
+///
+/// Args:
+///     paths: Paths to DOCX files
+///     max_threads: Worker thread count (default: one per CPU)
+///     options: ExtractOptions applied to every file (default
+///         ExtractOptions())
+///
+/// Returns:
+///     List of ExtractionResult, one per path
+#[pyfunction]
+#[pyo3(signature = (paths, max_threads=None, options=None))]
+fn extract_docx_batch(
+    py: Python,
+    paths: Vec<String>,
+    max_threads: Option<usize>,
+    options: Option<ExtractOptions>,
+) -> PyResult<Vec<ExtractionResult>> {
+    let opts = options.unwrap_or_default();
+    let outputs = py.allow_threads(|| extract_batch(&paths, max_threads, &opts));
+
+    let outputs = outputs.map_err(|e| {
+        PyRuntimeError::new_err(format!("Failed to start extraction thread pool: {}", e))
+    })?;
+
+    outputs
+        .into_iter()
+        .map(|output| output.into_result(py))
+        .collect()
+}
+
+/// Extract DOCX files on a rayon pool of `max_threads` workers (default:
+/// one per CPU), returning the outputs in input order
+pub fn extract_batch(
+    paths: &[String],
+    max_threads: Option<usize>,
+    opts: &ExtractOptions,
+) -> Result<Vec<ExtractionData>, rayon::ThreadPoolBuildError> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(max_threads.unwrap_or(0))
+        .build()?;
+
+    Ok(pool.install(|| {
+        paths
+            .par_iter()
+            .map(|path| extract_path(path, opts, None))
+            .collect()
+    }))
+}
+
+/// Python module definition
+#[pymodule]
+fn docx_extractor(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(extract_docx, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_docx_nogil, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_docx_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_docx_batch, m)?)?;
+    m.add_class::<ExtractOptions>()?;
+    m.add_class::<TextSegment>()?;
+    m.add_class::<RunSpan>()?;
+    m.add_class::<TableSegment>()?;
+    m.add_class::<TableCellSegment>()?;
+    m.add_class::<ExtractionError>()?;
     m.add_class::<ExtractionResult>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_number_formats() {
+        assert_eq!(format_list_number(3, "decimal"), "3");
+        assert_eq!(format_list_number(3, "lowerLetter"), "c");
+        assert_eq!(format_list_number(28, "lowerLetter"), "bb");
+        assert_eq!(format_list_number(4, "upperLetter"), "D");
+        assert_eq!(format_list_number(14, "lowerRoman"), "xiv");
+        assert_eq!(format_list_number(1999, "upperRoman"), "MCMXCIX");
+        assert_eq!(format_list_number(7, "decimalZero"), "07");
+    }
+
+    #[test]
+    fn test_huge_list_numbers_fall_back_to_decimal() {
+        assert_eq!(format_list_number(10_000, "upperRoman").len(), 10);
+        assert_eq!(format_list_number(10_001, "upperRoman"), "10001");
+        assert_eq!(
+            format_list_number(usize::MAX, "lowerRoman"),
+            usize::MAX.to_string()
+        );
+        assert_eq!(format_list_number(10_001, "lowerLetter"), "10001");
+        assert_eq!(
+            format_list_number(usize::MAX, "upperLetter"),
+            usize::MAX.to_string()
+        );
+    }
+
+    #[test]
+    fn test_multilevel_list_markers() {
+        let numberings = docx_rs::Numberings::new()
+            .add_abstract_numbering(
+                docx_rs::AbstractNumbering::new(1)
+                    .add_level(docx_rs::Level::new(
+                        0,
+                        docx_rs::Start::new(1),
+                        docx_rs::NumberFormat::new("decimal"),
+                        docx_rs::LevelText::new("%1."),
+                        docx_rs::LevelJc::new("left"),
+                    ))
+                    .add_level(docx_rs::Level::new(
+                        1,
+                        docx_rs::Start::new(1),
+                        docx_rs::NumberFormat::new("lowerLetter"),
+                        docx_rs::LevelText::new("%2)"),
+                        docx_rs::LevelJc::new("left"),
+                    )),
+            )
+            .add_numbering(docx_rs::Numbering::new(1, 1))
+            .add_numbering(
+                docx_rs::Numbering::new(2, 1).add_override(docx_rs::LevelOverride::new(0).start(5)),
+            );
+        let mut counters = ListCounters::new(&numberings);
+
+        assert_eq!(counters.next_marker(1, 0).as_deref(), Some("1. "));
+        assert_eq!(counters.next_marker(1, 1).as_deref(), Some("a) "));
+        assert_eq!(counters.next_marker(1, 1).as_deref(), Some("b) "));
+        assert_eq!(counters.next_marker(1, 0).as_deref(), Some("2. "));
+        assert_eq!(counters.next_marker(1, 1).as_deref(), Some("a) "));
+        assert_eq!(counters.next_marker(2, 0).as_deref(), Some("5. "));
+        assert_eq!(counters.next_marker(0, 0), None);
+    }
+
+    #[test]
+    fn test_list_restarts_and_continuation() {
+        let level = |ilvl: usize, format: &str, text: &str| {
+            docx_rs::Level::new(
+                ilvl,
+                docx_rs::Start::new(1),
+                docx_rs::NumberFormat::new(format),
+                docx_rs::LevelText::new(text),
+                docx_rs::LevelJc::new("left"),
+            )
+        };
+        let restart = |num_id: usize| {
+            docx_rs::Numbering::new(num_id, 1).add_override(docx_rs::LevelOverride::new(0).start(1))
+        };
+        let item = |num_id: usize, text: &str| {
+            docx_rs::Paragraph::new()
+                .add_run(docx_rs::Run::new().add_text(text))
+                .numbering(
+                    docx_rs::NumberingId::new(num_id),
+                    docx_rs::IndentLevel::new(0),
+                )
+        };
+        // "Restart numbering" twice, then "Continue numbering" through a
+        // fresh instance of the same definition
+        let docx = docx_rs::Docx::new()
+            .add_abstract_numbering(
+                docx_rs::AbstractNumbering::new(1).add_level(level(0, "decimal", "%1.")),
+            )
+            .add_numbering(docx_rs::Numbering::new(1, 1))
+            .add_numbering(restart(2))
+            .add_numbering(restart(3))
+            .add_numbering(docx_rs::Numbering::new(4, 1))
+            .add_paragraph(item(1, "Alpha"))
+            .add_paragraph(item(1, "Beta"))
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Interlude")),
+            )
+            .add_paragraph(item(2, "Gamma"))
+            .add_paragraph(item(2, "Delta"))
+            .add_paragraph(item(3, "Epsilon"))
+            .add_paragraph(item(4, "Zeta"));
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+
+        let output = extract(&buffer, ExtractOptions::default());
+        let texts: Vec<_> = output.segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "1. Alpha",
+                "2. Beta",
+                "Interlude",
+                "1. Gamma",
+                "2. Delta",
+                "1. Epsilon",
+                "2. Zeta",
+            ]
+        );
+
+        // lvlRestart 0: the sub-level keeps counting under each new item
+        let numberings = docx_rs::Numberings::new()
+            .add_abstract_numbering(
+                docx_rs::AbstractNumbering::new(1)
+                    .add_level(level(0, "decimal", "%1."))
+                    .add_level(level(1, "lowerLetter", "%2)").level_restart(0)),
+            )
+            .add_numbering(docx_rs::Numbering::new(1, 1));
+        let mut counters = ListCounters::new(&numberings);
+        assert_eq!(counters.next_marker(1, 0).as_deref(), Some("1. "));
+        assert_eq!(counters.next_marker(1, 1).as_deref(), Some("a) "));
+        assert_eq!(counters.next_marker(1, 0).as_deref(), Some("2. "));
+        assert_eq!(counters.next_marker(1, 1).as_deref(), Some("b) "));
+    }
+
+    #[test]
+    fn test_paragraph_spans_with_revisions() {
+        let para = docx_rs::Paragraph::new()
+            .add_run(docx_rs::Run::new().add_text("Pay "))
+            .add_delete(docx_rs::Delete::new().add_run(docx_rs::Run::new().add_delete_text("100")))
+            .add_insert(docx_rs::Insert::new(docx_rs::Run::new().add_text("200")))
+            .add_run(docx_rs::Run::new().add_text(" EUR"));
+
+        assert_eq!(
+            paragraph_spans(&para, false, false),
+            vec![
+                ("Pay ".to_string(), None),
+                ("100".to_string(), Some(Revision::Deleted)),
+                ("200".to_string(), Some(Revision::Inserted)),
+                (" EUR".to_string(), None),
+            ]
+        );
+        assert_eq!(
+            paragraph_spans(&para, true, false),
+            vec![("Pay 200 EUR".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn test_emphasis_markers_across_runs() {
+        let para = docx_rs::Paragraph::new()
+            .add_run(docx_rs::Run::new().add_text("Hel").bold())
+            .add_run(docx_rs::Run::new().add_text("lo ").bold())
+            .add_run(docx_rs::Run::new().add_text("big"))
+            .add_run(docx_rs::Run::new().add_text(" wor").bold().italic())
+            .add_run(docx_rs::Run::new().add_text("ld").bold().italic())
+            .add_run(docx_rs::Run::new().add_text(", "))
+            .add_run(docx_rs::Run::new().add_text("friend").underline("single"));
+
+        assert_eq!(
+            paragraph_spans(&para, false, true),
+            vec![("**Hello** big **_world_**, friend".to_string(), None)]
+        );
+        assert_eq!(
+            paragraph_spans(&para, false, false),
+            vec![("Hello big world, friend".to_string(), None)]
+        );
+        assert!(coalesce_runs(&para, false)[4].1.underline);
+    }
+
+    #[test]
+    fn test_segment_collector_limits() {
+        let mut by_count = SegmentCollector::new(Some(1), None, None, false, false, None);
+        by_count.push(TextSegment::with_section(
+            "one".into(),
+            "paragraph_0".into(),
+        ));
+        assert!(!by_count.is_full());
+        by_count.push(TextSegment::with_section(
+            "two".into(),
+            "paragraph_1".into(),
+        ));
+        assert!(by_count.is_full());
+        assert_eq!(by_count.segments.len(), 1);
+
+        let mut by_chars = SegmentCollector::new(None, Some(5), None, false, false, None);
+        by_chars.push(TextSegment::with_section(
+            "abc".into(),
+            "paragraph_0".into(),
+        ));
+        by_chars.push(TextSegment::with_section(
+            "defgh".into(),
+            "paragraph_1".into(),
+        ));
+        assert!(by_chars.is_full());
+        assert_eq!(by_chars.segments[1].text, "de");
+
+        let mut by_time =
+            SegmentCollector::new(None, None, Some(Instant::now()), false, false, None);
+        assert!(by_time.is_full());
+        assert!(by_time.timed_out);
+        by_time.push(TextSegment::with_section(
+            "late".into(),
+            "paragraph_0".into(),
+        ));
+        assert!(by_time.segments.is_empty());
+    }
+
+    #[test]
+    fn test_table_past_limit_is_dropped() {
+        let cell = |text: &str| {
+            docx_rs::TableCell::new().add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text)),
+            )
+        };
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Intro")))
+            .add_table(docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![
+                cell("Late"),
+            ])]));
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+
+        let output = extract(
+            &buffer,
+            ExtractOptions {
+                max_segments: Some(1),
+                ..Default::default()
+            },
+        );
+        assert_eq!(output.segments.len(), 1);
+        assert!(output.truncated);
+        assert!(output.tables.is_empty());
+    }
+
+    #[test]
+    fn test_whitespace_normalization() {
+        let raw = "\tName:\t\tAlice\u{00A0}\u{00A0}Smith  \n";
+        let mut plain = SegmentCollector::new(None, None, None, false, false, None);
+        plain.push(TextSegment::with_section(raw.into(), "paragraph_0".into()));
+        assert_eq!(plain.segments[0].text, raw);
+
+        let mut normalized = SegmentCollector::new(None, Some(17), None, true, false, None);
+        normalized.push(TextSegment::with_section(raw.into(), "paragraph_0".into()));
+        normalized.push(TextSegment::with_section(
+            "\u{00A0}x\ty\u{00A0}".into(),
+            "paragraph_1".into(),
+        ));
+        assert_eq!(normalized.segments[0].text, "Name: Alice Smith");
+        // Limits count the normalized text
+        assert!(normalized.truncated);
+        assert_eq!(normalized.segments.len(), 1);
+    }
+
+    /// Pack `docx`, then rewrite `word/document.xml` with `edit` for markup
+    /// docx-rs cannot write
+    fn pack_with(docx: docx_rs::Docx, edit: impl Fn(String) -> String) -> Vec<u8> {
+        pack_parts(docx, |name, content| {
+            if name == ooxml::DOCUMENT_PART {
+                edit(content)
+            } else {
+                content
+            }
+        })
+    }
+
+    /// Like `pack_with`, for edits to any part (given by its name)
+    fn pack_parts(docx: docx_rs::Docx, edit: impl Fn(&str, String) -> String) -> Vec<u8> {
+        use std::io::{Cursor, Write};
+
+        let mut packed = Cursor::new(Vec::new());
+        docx.build().pack(&mut packed).unwrap();
+        let mut archive = zip::ZipArchive::new(packed).unwrap();
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).unwrap();
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            content = edit(entry.name(), content);
+            writer
+                .start_file(entry.name(), zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_break_markers_in_segment_order() {
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(
+                docx_rs::Paragraph::new()
+                    .add_run(docx_rs::Run::new().add_break(docx_rs::BreakType::Page))
+                    .add_run(docx_rs::Run::new().add_text("One")),
+            )
+            .add_paragraph(
+                docx_rs::Paragraph::new()
+                    .add_run(docx_rs::Run::new().add_text("Two"))
+                    .add_run(docx_rs::Run::new().add_break(docx_rs::BreakType::Page))
+                    .add_run(docx_rs::Run::new().add_text("Three")),
+            )
+            .add_paragraph(
+                docx_rs::Paragraph::new()
+                    .style("SectionEnd")
+                    .add_run(docx_rs::Run::new().add_text("Chapter end")),
+            )
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Next")));
+        // docx-rs drops paragraph-level sectPr, so swap it in for the style
+        let buffer = pack_with(docx, |xml| {
+            xml.replace("<w:pStyle w:val=\"SectionEnd\" />", "<w:sectPr />")
+        });
+
+        let output = extract(&buffer, ExtractOptions::default());
+        let order: Vec<_> = output
+            .segments
+            .iter()
+            .map(|s| (s.text.as_str(), s.section.as_deref().unwrap_or_default()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("", "page_break"),
+                ("One", "paragraph_0"),
+                // Breaks inside a paragraph's text follow it
+                ("TwoThree", "paragraph_1"),
+                ("", "page_break"),
+                ("Chapter end", "paragraph_2"),
+                ("", "section_break"),
+                ("Next", "paragraph_3"),
+            ]
+        );
+        assert_eq!(output.segments[0].page, Some(2));
+        assert_eq!(output.segments[6].page, Some(4));
+    }
+
+    #[test]
+    fn test_size_stats_in_metadata() {
+        let para =
+            |text: &str| docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text));
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(para("Hello big world"))
+            .add_paragraph(para(""))
+            .add_table(docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![
+                docx_rs::TableCell::new().add_paragraph(para("a b")),
+            ])]))
+            .add_paragraph(para("Bye"));
+        // Word saves its own (stale) count in app.xml
+        let app_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties">
+            <Words>999</Words></Properties>"#;
+        let buffer = pack_parts(docx, |name, xml| {
+            if name == ooxml::APP_PROPERTIES_PART {
+                app_xml.to_string()
+            } else {
+                xml
+            }
+        });
+
+        let output = extract(&buffer, ExtractOptions::default());
+        // "Hello big world", "a b \t\n" (flattened table), "Bye"
+        assert_eq!(output.metadata["segment_count"], 3);
+        assert_eq!(output.metadata["paragraph_count"], 2);
+        assert_eq!(output.metadata["table_count"], 1);
+        assert_eq!(output.metadata["word_count"], 6);
+        assert_eq!(output.metadata["app_word_count"], 999);
+        assert_eq!(output.metadata["char_count"], 15 + 6 + 3);
+    }
+
+    #[test]
+    fn test_segment_direction() {
+        assert_eq!(text_direction("שלום world"), Some("rtl"));
+        assert_eq!(text_direction("123 - مرحبا Hello"), Some("rtl"));
+        assert_eq!(text_direction("(Hello) שלום"), Some("ltr"));
+        assert_eq!(text_direction("42 ..."), None);
+
+        let para =
+            |text: &str| docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text));
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(para("2024 مرحبا").style("Rtl"))
+            .add_paragraph(para("Version 2 שלום").style("Rtl"))
+            .add_paragraph(para("English only"));
+        let buffer = pack_with(docx, |xml| {
+            xml.replace("<w:pStyle w:val=\"Rtl\" />", "<w:bidi />")
+        });
+
+        let output = extract(&buffer, ExtractOptions::default());
+        let directions: Vec<_> = output
+            .segments
+            .iter()
+            .map(|s| s.direction.as_deref())
+            .collect();
+        // Mixed text in a bidi paragraph keeps the paragraph's direction
+        assert_eq!(directions, vec![Some("rtl"), Some("rtl"), Some("ltr")]);
+    }
+
+    #[test]
+    fn test_markdown_rendering() {
+        let segment =
+            |text: &str, section: &str| TextSegment::with_section(text.into(), section.into());
+        let mut title = segment("Report", "heading");
+        title.heading_level = Some(1);
+        let mut bullet = segment("\u{2022} Apples", "paragraph_1");
+        bullet.list_level = Some(0);
+        let mut nested = segment("a) Green", "paragraph_2");
+        nested.list_level = Some(1);
+        let mut removed = segment("old text", "paragraph_4");
+        removed.change_type = Some("deleted".into());
+        let segments = vec![
+            title,
+            bullet,
+            nested,
+            segment("", "page_break"),
+            segment("Fruit\tCount\t\nPear | Plum\t2\t\n", "table_3"),
+            removed,
+        ];
+        let tables = vec![
+            TableSegment::new(
+                vec![
+                    vec!["Fruit".into(), "Count".into()],
+                    vec!["Pear | Plum".into(), "2".into()],
+                ],
+                Some("table_3".into()),
+                Vec::new(),
+            ),
+            TableSegment::new(
+                vec![vec!["only".into()]],
+                Some("table_5".into()),
+                Vec::new(),
+            ),
+        ];
+
+        assert_eq!(
+            render_markdown(&segments, &tables),
+            "# Report\n\n\
+             - Apples\n    1. Green\n\n\
+             | Fruit | Count |\n| --- | --- |\n| Pear \\| Plum | 2 |\n\n\
+             ~~old text~~\n\n\
+             | only |\n| --- |"
+        );
+    }
+
+    #[test]
+    fn test_bookmark_anchors() {
+        let children = docx_rs::Docx::new()
+            .add_paragraph(
+                docx_rs::Paragraph::new()
+                    .add_bookmark_start(1, "_GoBack")
+                    .add_run(docx_rs::Run::new().add_text("Intro")),
+            )
+            .add_bookmark_start(2, "Parties")
+            .add_paragraph(
+                docx_rs::Paragraph::new()
+                    .add_run(docx_rs::Run::new().add_text("Between "))
+                    .add_bookmark_start(3, "_Ref123")
+                    .add_run(docx_rs::Run::new().add_text("ACME")),
+            )
+            .document
+            .children;
+
+        assert_eq!(
+            bookmark_anchors(&children),
+            vec![
+                serde_json::json!({"name": "Parties", "paragraph": 2}),
+                serde_json::json!({"name": "_Ref123", "paragraph": 2}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comment_replies() {
+        let comment = |id: usize, author: &str, text: &str| {
+            docx_rs::Comment::new(id).author(author).add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text)),
+            )
+        };
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Intro")))
+            .add_paragraph(
+                docx_rs::Paragraph::new()
+                    .add_comment_start(comment(1, "Ann", "Check the amount"))
+                    .add_comment_start(comment(2, "Bob", "Fixed").parent_comment_id(1))
+                    .add_run(docx_rs::Run::new().add_text("Total: 42"))
+                    .add_comment_end(1)
+                    .add_comment_end(2),
+            );
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+
+        let output = extract(&buffer, ExtractOptions::default());
+        let comments: Vec<_> = output
+            .segments
+            .iter()
+            .filter(|s| s.section.as_deref() == Some("comment"))
+            .map(|s| {
+                (
+                    s.text.as_str(),
+                    s.comment_author.as_deref(),
+                    s.comment_ref_paragraph,
+                    s.reply_to.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("Check the amount", Some("Ann"), Some(1), None),
+                ("Fixed", Some("Bob"), Some(1), Some("1")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_textbox_segments() {
+        let body = r#"<w:p><w:r><w:t>Anchor</w:t></w:r><w:r><w:drawing>
+            <wp:anchor xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"
+                behindDoc="0" distT="0" distB="0" distL="0" distR="0" simplePos="0" locked="0"
+                layoutInCell="1" allowOverlap="1" relativeHeight="1">
+                <wp:simplePos x="0" y="0"/>
+                <wp:positionH relativeFrom="column"><wp:posOffset>0</wp:posOffset></wp:positionH>
+                <wp:positionV relativeFrom="paragraph"><wp:posOffset>0</wp:posOffset></wp:positionV>
+                <wp:extent cx="914400" cy="457200"/>
+                <wp:docPr id="1" name="Text Box 1"/>
+                <a:graphic xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main">
+                <a:graphicData uri="http://schemas.microsoft.com/office/word/2010/wordprocessingShape">
+                <wps:wsp xmlns:wps="http://schemas.microsoft.com/office/word/2010/wordprocessingShape">
+                    <wps:txbx><w:txbxContent>
+                        <w:p><w:r><w:t>Boxed</w:t></w:r></w:p>
+                        <w:p><w:r><w:t>note</w:t></w:r></w:p>
+                    </w:txbxContent></wps:txbx>
+                </wps:wsp></a:graphicData></a:graphic>
+            </wp:anchor></w:drawing></w:r></w:p>"#;
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("BODY")));
+        let buffer = pack_with(docx, |xml| {
+            let start = xml.find("<w:p ").or_else(|| xml.find("<w:p>")).unwrap();
+            let end = xml.find("</w:p>").unwrap() + "</w:p>".len();
+            format!("{}{}{}", &xml[..start], body, &xml[end..])
+        });
+
+        let output = extract(&buffer, ExtractOptions::default());
+        let found: Vec<_> = output
+            .segments
+            .iter()
+            .map(|s| (s.text.as_str(), s.section.as_deref(), s.anchor_paragraph))
+            .collect();
+        // The box text is not part of the anchoring paragraph
+        assert_eq!(
+            found,
+            vec![
+                ("Anchor", Some("paragraph_0"), None),
+                ("Boxed\nnote", Some("textbox"), Some(0)),
+            ]
+        );
+        assert_eq!(output.segments[1].confidence, TEXTBOX_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_field_results_tagged_by_type() {
+        use docx_rs::FieldCharType::{Begin, End, Separate};
+        let instr = |text: &str| {
+            let mut run = docx_rs::Run::new();
+            run.children
+                .push(docx_rs::RunChild::InstrTextString(text.to_string()));
+            run
+        };
+        let text = |text: &str| docx_rs::Run::new().add_text(text);
+        let fld = |kind| docx_rs::Run::new().add_field_char(kind, false);
+
+        // A TOC spanning two entries, each with a nested PAGEREF
+        let toc_start = docx_rs::Paragraph::new()
+            .add_run(fld(Begin))
+            .add_run(instr(" TOC \\o \"1-3\" \\h "))
+            .add_run(fld(Separate))
+            .add_run(text("Introduction\t"))
+            .add_run(fld(Begin))
+            .add_run(instr(" PAGEREF _Toc1 \\h "))
+            .add_run(fld(Separate))
+            .add_run(text("3"))
+            .add_run(fld(End));
+        let toc_end = docx_rs::Paragraph::new()
+            .add_run(text("Scope"))
+            .add_run(fld(End));
+        let body = docx_rs::Paragraph::new()
+            .add_run(text("See section "))
+            .add_run(fld(Begin))
+            .add_run(instr(" REF _Ref2 \\h "))
+            .add_run(fld(Separate))
+            .add_run(text("2.1"))
+            .add_run(fld(End));
+        let date = docx_rs::Paragraph::new()
+            .add_run(fld(Begin))
+            .add_run(instr(" DATE \\@ \"d MMMM yyyy\" "))
+            .add_run(fld(Separate))
+            .add_run(text("1 March 2025"))
+            .add_run(fld(End));
+
+        let mut fields = FieldTracker::default();
+        let kind = |field: ParagraphField| field.field_type;
+        assert_eq!(
+            kind(fields.paragraph_field(&toc_start)).as_deref(),
+            Some("TOC")
+        );
+        assert_eq!(
+            kind(fields.paragraph_field(&toc_end)).as_deref(),
+            Some("TOC")
+        );
+        assert!(fields.open.is_empty());
+        // Inline fields leave the paragraph as body text
+        assert_eq!(fields.paragraph_field(&body), ParagraphField::default());
+        assert_eq!(kind(fields.paragraph_field(&date)).as_deref(), Some("DATE"));
+        assert_eq!(coalesce_runs(&date, false)[0].0, "1 March 2025");
+    }
+
+    #[test]
+    fn test_confidence_scoring_and_filter() {
+        use docx_rs::FieldCharType::{Begin, End, Separate};
+        let instr = |text: &str| {
+            let mut run = docx_rs::Run::new();
+            run.children
+                .push(docx_rs::RunChild::InstrTextString(text.to_string()));
+            run
+        };
+        let text = |text: &str| docx_rs::Run::new().add_text(text);
+
+        // PAGEREF whose cached page number is missing
+        let missing = docx_rs::Paragraph::new()
+            .add_run(text("See page "))
+            .add_run(docx_rs::Run::new().add_field_char(Begin, false))
+            .add_run(instr(" PAGEREF _Toc1 \\h "))
+            .add_run(docx_rs::Run::new().add_field_char(Separate, false))
+            .add_run(docx_rs::Run::new().add_field_char(End, false));
+        let dirty = docx_rs::Paragraph::new()
+            .add_run(docx_rs::Run::new().add_field_char(Begin, true))
+            .add_run(instr(" DATE "))
+            .add_run(docx_rs::Run::new().add_field_char(Separate, false))
+            .add_run(text("1 March 2025"))
+            .add_run(docx_rs::Run::new().add_field_char(End, false));
+        let unbalanced = docx_rs::Paragraph::new()
+            .add_run(text("Orphan"))
+            .add_run(docx_rs::Run::new().add_field_char(End, false));
+
+        let mut fields = FieldTracker::default();
+        assert!(fields.paragraph_field(&missing).stale);
+        assert!(fields.paragraph_field(&dirty).stale);
+        assert!(fields.paragraph_field(&unbalanced).stale);
+
+        let mut collector = SegmentCollector::new(None, None, None, false, false, Some(0.6));
+        let mut textbox = TextSegment::with_section("Floating".into(), "textbox".into());
+        textbox.confidence = TEXTBOX_CONFIDENCE;
+        let mut stale = TextSegment::with_section("See page ".into(), "paragraph_0".into());
+        stale.confidence = STALE_FIELD_CONFIDENCE;
+        collector.push(textbox);
+        collector.push(stale);
+        collector.push(TextSegment::with_section(
+            "Body".into(),
+            "paragraph_1".into(),
+        ));
+        let kept: Vec<_> = collector.segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(kept, vec!["Floating", "Body"]);
+        assert!(!collector.truncated);
+    }
+
+    #[test]
+    fn test_detailed_runs() {
+        let docx = docx_rs::Docx::new().add_paragraph(
+            docx_rs::Paragraph::new()
+                .add_run(docx_rs::Run::new().add_text("Total:  "))
+                .add_run(docx_rs::Run::new().add_text("€42").bold())
+                .add_run(docx_rs::Run::new().add_text("  due").color("FF0000")),
+        );
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+        let extract_runs = |detailed_runs| {
+            extract(
+                &buffer,
+                ExtractOptions {
+                    normalize_whitespace: true,
+                    detailed_runs,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let plain = extract_runs(false);
+        assert!(plain.segments[0].runs.is_empty());
+
+        // Offsets follow the whitespace-normalized text
+        let detailed = extract_runs(true);
+        let segment = &detailed.segments[0];
+        assert_eq!(segment.text, "Total: €42 due");
+        let red = Some("FF0000".to_string());
+        assert_eq!(
+            segment.runs,
+            vec![
+                RunSpan::new(0, 7, false, false, None),
+                RunSpan::new(7, 12, true, false, None),
+                RunSpan::new(12, 16, false, false, red),
+            ]
+        );
+        assert_eq!(&segment.text[7..12], "€42");
+
+        // A list marker in front moves every run; a note indicator inside
+        // a run widens it
+        let mut runs = segment.runs.clone();
+        shift_runs(&mut runs, 0, 2);
+        assert_eq!((runs[0].start, runs[0].end), (2, 9));
+        shift_runs(&mut runs, 16, 3);
+        assert_eq!((runs[2].start, runs[2].end), (14, 21));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let segment = |text: &str| TextSegment::with_section(text.into(), "paragraph_0".into());
+
+        // SHA-256 of "Hello world\nSecond"
+        let hash = content_hash(&[segment("Hello  world "), segment(""), segment("Second")]);
+        assert_eq!(
+            hash,
+            "e779cb1641d883314760f45e7c2b0f07170e1adfa33d92b44e8795de2d621a5d"
+        );
+        assert_eq!(
+            hash,
+            content_hash(&[segment("Hello world"), segment("\tSecond\n")])
+        );
+        assert_ne!(hash, content_hash(&[segment("Hello world Second")]));
+    }
+
+    #[test]
+    fn test_empty_and_oversized_inputs() {
+        let extract_limited = |buffer: &[u8], limit| {
+            extract(
+                buffer,
+                ExtractOptions {
+                    max_decompressed_bytes: limit,
+                    ..Default::default()
+                },
+            )
+        };
+
+        let empty = extract_limited(b"", Some(DEFAULT_MAX_DECOMPRESSED_BYTES));
+        assert_eq!(empty.errors[0].code, "EMPTY_FILE");
+        assert!(!empty.errors[0].recoverable);
+
+        let mut buffer = Vec::new();
+        docx_rs::Docx::new()
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("x")))
+            .build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+        let limited = extract_limited(&buffer, Some(1024));
+        assert_eq!(limited.errors[0].code, "SIZE_LIMIT_EXCEEDED");
+        assert!(limited.errors[0].recoverable);
+        assert!(limited.segments.is_empty());
+
+        let unlimited = extract_limited(&buffer, None);
+        assert!(unlimited.errors.is_empty());
+        assert_eq!(unlimited.segments[0].text, "x");
+    }
+
+    #[test]
+    fn test_progress_reports_and_cancel() {
+        let mut docx = docx_rs::Docx::new();
+        for i in 0..250 {
+            docx = docx.add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(i.to_string())),
+            );
+        }
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+        let extract_reporting = |progress: Option<Progress>| {
+            extract_with_progress(&buffer, &ExtractOptions::default(), progress)
+        };
+
+        let mut calls = Vec::new();
+        let output = extract_reporting(Some(&mut |processed, total| {
+            calls.push((processed, total));
+            true
+        }));
+        assert!(output.errors.is_empty());
+        assert_eq!(output.segments.len(), 250);
+        assert_eq!(calls, vec![(100, 250), (200, 250), (250, 250)]);
+
+        let cancelled = extract_reporting(Some(&mut |processed, _| processed < 200));
+        assert_eq!(cancelled.errors[0].code, "CANCELLED");
+        assert!(!cancelled.errors[0].recoverable);
+        // Segments up to the cancelling report are kept
+        assert_eq!(cancelled.segments.len(), 200);
+        assert!(cancelled.truncated);
+
+        // Counts are relative to the requested range
+        let mut calls = Vec::new();
+        extract_with_progress(
+            &buffer,
+            &ExtractOptions {
+                start_index: Some(30),
+                end_index: Some(250),
+                ..Default::default()
+            },
+            Some(&mut |processed, total| {
+                calls.push((processed, total));
+                true
+            }),
+        );
+        assert_eq!(calls, vec![(100, 220), (200, 220), (220, 220)]);
+    }
+
+    #[test]
+    fn test_recoverable_by_code() {
+        let recoverable = |code| ExtractionError::from_code(code, String::new()).recoverable;
+        for code in [
+            ErrorCode::Truncated,
+            ErrorCode::Timeout,
+            ErrorCode::SizeLimitExceeded,
+        ] {
+            assert!(recoverable(code), "{}", code);
+        }
+        for code in [
+            ErrorCode::FileNotFound,
+            ErrorCode::Corrupted,
+            ErrorCode::Encrypted,
+            ErrorCode::EmptyFile,
+            ErrorCode::Cancelled,
+        ] {
+            assert!(!recoverable(code), "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_error_codes_round_trip() {
+        let names = ExtractionError::codes();
+        assert_eq!(names.len(), ErrorCode::ALL.len());
+        assert_eq!(
+            names.iter().collect::<std::collections::HashSet<_>>().len(),
+            names.len()
+        );
+        for code in ErrorCode::ALL {
+            assert_eq!(ErrorCode::parse(code.as_str()), Some(code));
+        }
+
+        let error = ExtractionError::from_code(ErrorCode::NotADocx, String::new());
+        assert_eq!(error.code, "NOT_A_DOCX");
+        assert_eq!(error.error_code(), Some(ErrorCode::NotADocx));
+        let foreign = ExtractionError::new("OCR_FAILED".into(), String::new(), false);
+        assert_eq!(foreign.error_code(), None);
+    }
+
+    #[test]
+    fn test_legacy_doc_is_reported() {
+        use std::io::{Cursor, Write};
+
+        // An unencrypted compound file, as Word 97-2003 writes them
+        let mut file = cfb::CompoundFile::create(Cursor::new(Vec::new())).unwrap();
+        file.create_stream("WordDocument")
+            .unwrap()
+            .write_all(b"legacy")
+            .unwrap();
+        let buffer = file.into_inner().into_inner();
+
+        let output = extract(&buffer, ExtractOptions::default());
+        assert_eq!(output.errors[0].code, "LEGACY_DOC_FORMAT");
+        assert!(!output.errors[0].recoverable);
+        assert!(output.segments.is_empty());
+    }
+
+    #[test]
+    fn test_zip_without_document_part_is_reported() {
+        use std::io::{Cursor, Write};
+
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file("xl/workbook.xml", zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(b"<workbook/>").unwrap();
+        let buffer = writer.finish().unwrap().into_inner();
+
+        let output = extract(&buffer, ExtractOptions::default());
+        assert_eq!(output.errors[0].code, "NOT_A_DOCX");
+        assert!(output.errors[0].message.contains("word/document.xml"));
+        assert!(output.segments.is_empty());
+    }
+
+    #[test]
+    fn test_body_index_range() {
+        let mut docx = docx_rs::Docx::new();
+        for i in 0..10 {
+            let mut run = docx_rs::Run::new();
+            if i == 1 {
+                run = run.add_break(docx_rs::BreakType::Page);
+            }
+            docx =
+                docx.add_paragraph(docx_rs::Paragraph::new().add_run(run.add_text(i.to_string())));
+        }
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+        let texts = |start, end| {
+            let output = extract(
+                &buffer,
+                ExtractOptions {
+                    start_index: start,
+                    end_index: end,
+                    ..Default::default()
+                },
+            );
+            assert!(output.errors.is_empty());
+            output
+                .segments
+                .into_iter()
+                .map(|s| s.text)
+                .collect::<Vec<_>>()
+        };
+
+        // The page break in paragraph 1 is outside the range
+        assert_eq!(texts(Some(2), Some(5)), vec!["2", "3", "4"]);
+        // ... and kept as a marker inside it
+        assert_eq!(texts(None, Some(2)), vec!["0", "", "1"]);
+        assert_eq!(texts(Some(8), Some(100)), vec!["8", "9"]);
+        assert!(texts(Some(20), None).is_empty());
+        assert!(texts(Some(5), Some(3)).is_empty());
+    }
+
+    #[test]
+    fn test_parser_panic_is_reported() {
+        // docx-rs reads `w:color` through attributes[0], which panics
+        // when the element has no attributes
+        let docx = docx_rs::Docx::new().add_paragraph(
+            docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("x").color("FF0000")),
+        );
+        let buffer = pack_with(docx, |xml| {
+            xml.replace("<w:color w:val=\"FF0000\" />", "<w:color />")
+        });
+
+        let output = extract(&buffer, ExtractOptions::default());
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.errors[0].code, "PARSER_PANIC");
+        assert!(!output.errors[0].recoverable);
+        assert!(output.errors[0].message.contains("index out of bounds"));
+    }
+
+    #[test]
+    fn test_source_index_and_char_offset() {
+        let para =
+            |text: &str| docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text));
+        let table =
+            docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![docx_rs::TableCell::new(
+            )
+            .add_paragraph(para("cell"))])]);
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(para("ab"))
+            .add_paragraph(para(""))
+            .add_paragraph(para("cde"))
+            .add_table(table);
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+
+        let output = extract(&buffer, ExtractOptions::default());
+        let positions: Vec<_> = output
+            .segments
+            .iter()
+            .map(|s| (s.source_index, s.char_offset))
+            .collect();
+        assert_eq!(positions, vec![(Some(0), 0), (Some(2), 2), (Some(3), 5)]);
+    }
+
+    #[test]
+    fn test_segment_json_fields() {
+        let mut segment = TextSegment::with_section("Hi".into(), "paragraph_0".into());
+        segment.links = vec![("site".into(), "https://example.com".into())];
+        segment.runs = vec![RunSpan::new(0, 2, true, false, None)];
+
+        let value = serde_json::to_value(&segment).unwrap();
+        assert_eq!(value["text"], "Hi");
+        assert_eq!(value["section"], "paragraph_0");
+        assert_eq!(value["page"], Value::Null);
+        assert_eq!(value["links"][0][1], "https://example.com");
+        assert_eq!(value["runs"][0]["bold"], true);
+        assert_eq!(value["char_offset"], 0);
+    }
+
+    #[test]
+    fn test_segment_languages() {
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Guten Tag")),
+            )
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Hello")))
+            .add_paragraph(
+                docx_rs::Paragraph::new()
+                    .add_run(docx_rs::Run::new().add_text("Reply: "))
+                    .add_insert(docx_rs::Insert::new(
+                        docx_rs::Run::new().add_text("Vielen Dank"),
+                    )),
+            );
+
+        let german = "<w:r><w:rPr><w:lang w:val=\"de-DE\" /></w:rPr>";
+        let buffer = pack_parts(docx.clone(), |name, content| match name {
+            ooxml::DOCUMENT_PART => {
+                // Tag the first run and the inserted one
+                let plain = "<w:r><w:rPr />";
+                let content = content.replacen(plain, german, 1);
+                let at = content[..content.find("Vielen").unwrap()]
+                    .rfind(plain)
+                    .unwrap();
+                format!(
+                    "{}{}{}",
+                    &content[..at],
+                    german,
+                    &content[at + plain.len()..]
+                )
+            }
+            ooxml::STYLES_PART => content.replacen(
+                "<w:rPrDefault><w:rPr />",
+                "<w:rPrDefault><w:rPr><w:lang w:val=\"en-US\" /></w:rPr>",
+                1,
+            ),
+            _ => content,
+        });
+        let output = extract(&buffer, ExtractOptions::default());
+        let languages: Vec<_> = output
+            .segments
+            .iter()
+            .map(|s| s.language.as_deref())
+            .collect();
+        // The tracked insertion is its own segment with its own language
+        assert_eq!(
+            languages,
+            [Some("de-DE"), Some("en-US"), Some("en-US"), Some("de-DE")]
+        );
+        assert_eq!(output.metadata["default_language"], "en-US");
+
+        // Without a document default only tagged runs get a language
+        let buffer = pack_with(docx, |xml| xml.replacen("<w:r><w:rPr />", german, 1));
+        let output = extract(&buffer, ExtractOptions::default());
+        assert_eq!(output.segments[1].language, None);
+        assert!(!output.metadata.contains_key("default_language"));
+    }
+
+    #[test]
+    fn test_extract_core_without_python() {
+        let mut docx = docx_rs::Docx::new();
+        for text in ["One", "Two", "Three"] {
+            docx = docx.add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text)),
+            );
+        }
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+
+        let output = extract(&buffer, ExtractOptions::default());
+        assert!(output.errors.is_empty());
+        assert_eq!(output.file_size_bytes, buffer.len() as i64);
+        assert_eq!(output.metadata["paragraph_count"], 3);
+        let texts: Vec<_> = output.segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["One", "Two", "Three"]);
+        let timing = &output.metadata["timing"];
+        assert_eq!(timing["read_ms"], 0.0);
+        assert!(timing["parse_ms"].as_f64().unwrap() >= 0.0);
+        assert!(timing["extract_ms"].as_f64().unwrap() >= 0.0);
+
+        let path = std::env::temp_dir().join(format!("timing-{}.docx", std::process::id()));
+        std::fs::write(&path, &buffer).unwrap();
+        let from_file = extract_path(path.to_str().unwrap(), &ExtractOptions::default(), None);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(from_file.source_path.as_deref(), path.to_str());
+        assert_eq!(from_file.file_size_bytes, buffer.len() as i64);
+        let timing = &from_file.metadata["timing"];
+        let total: f64 = ["read_ms", "parse_ms", "extract_ms"]
+            .iter()
+            .map(|key| timing[key].as_f64().unwrap())
+            .sum();
+        assert!(timing["read_ms"].as_f64().unwrap() > 0.0);
+        assert!(total <= from_file.processing_time_ms);
+
+        let limited = extract(
+            &buffer,
+            ExtractOptions {
+                max_segments: Some(2),
+                ..Default::default()
+            },
+        );
+        assert!(limited.truncated);
+        assert_eq!(limited.segments.len(), 2);
+        assert_eq!(limited.errors[0].code, "TRUNCATED");
+
+        assert_eq!(output.source_path, None);
+        let missing = extract_path("/nonexistent/file.docx", &ExtractOptions::default(), None);
+        assert_eq!(
+            missing.source_path.as_deref(),
+            Some("/nonexistent/file.docx")
+        );
+        assert_eq!(missing.errors[0].code, "FILE_NOT_FOUND");
+        assert_eq!(missing.file_size_bytes, 0);
+    }
+
+    #[test]
+    fn test_batch_keeps_input_order() {
+        let pack = |paragraphs: usize, label: &str| {
+            let mut docx = docx_rs::Docx::new();
+            for _ in 0..paragraphs {
+                docx = docx.add_paragraph(
+                    docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(label)),
+                );
+            }
+            let mut buffer = Vec::new();
+            docx.build()
+                .pack(&mut std::io::Cursor::new(&mut buffer))
+                .unwrap();
+            buffer
+        };
+        // The large first file finishes after the small ones behind it
+        let dir = std::env::temp_dir();
+        let mut paths = Vec::new();
+        for (n, paragraphs) in [5000, 1, 1, 1].into_iter().enumerate() {
+            let path = dir.join(format!("batch-{}-{}.docx", std::process::id(), n));
+            std::fs::write(&path, pack(paragraphs, &format!("file {}", n))).unwrap();
+            paths.push(path.to_str().unwrap().to_string());
+        }
+        paths.insert(2, "/nonexistent/file.docx".to_string());
+
+        let outputs = extract_batch(&paths, Some(4), &ExtractOptions::default()).unwrap();
+        for path in paths.iter().filter(|p| !p.starts_with("/nonexistent")) {
+            std::fs::remove_file(path).unwrap();
+        }
+        let found: Vec<_> = outputs
+            .iter()
+            .map(|o| o.segments.first().map(|s| s.text.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                Some("file 0"),
+                Some("file 1"),
+                None,
+                Some("file 2"),
+                Some("file 3")
+            ]
+        );
+        assert_eq!(outputs[0].segments.len(), 5000);
+        assert_eq!(outputs[2].errors[0].code, "FILE_NOT_FOUND");
+        let sources: Vec<_> = outputs.iter().map(|o| o.source_path.as_deref()).collect();
+        assert_eq!(
+            sources,
+            paths.iter().map(|p| Some(p.as_str())).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_options_repr_hides_password() {
+        assert!(!ExtractOptions {
+            password: Some("secret".into()),
+            ..Default::default()
+        }
+        .__repr__()
+        .contains("secret"));
+    }
+
+    #[test]
+    fn test_repeated_headers_are_merged() {
+        let text = |t: &str| docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(t));
+        let docx = docx_rs::Docx::new()
+            .header(docx_rs::Header::new().add_paragraph(text("Running")))
+            .first_header(docx_rs::Header::new().add_paragraph(text("Cover")))
+            .even_header(docx_rs::Header::new().add_paragraph(text("Running")))
+            .footer(docx_rs::Footer::new().add_paragraph(text("Page")))
+            .add_paragraph(text("Chapter 1"))
+            .add_paragraph(text("Chapter 2"));
+        // The first chapter ends a section that only sets the default header
+        let buffer = pack_with(docx, |xml| {
+            xml.replacen(
+                "<w:pPr><w:rPr /></w:pPr>",
+                "<w:pPr><w:rPr /><w:sectPr><w:headerReference w:type=\"default\" r:id=\"rIdHeader1\" /></w:sectPr></w:pPr>",
+                1,
+            )
+        });
+
+        let output = extract(&buffer, ExtractOptions::default());
+        let segments: Vec<_> = output
+            .segments
+            .iter()
+            .map(|s| {
+                (
+                    s.section.as_deref().unwrap(),
+                    s.text.as_str(),
+                    s.repeat_count,
+                )
+            })
+            .collect();
+        assert_eq!(
+            segments,
+            [
+                ("header_default", "Running", Some(3)),
+                ("header_first", "Cover", Some(1)),
+                ("paragraph_0", "Chapter 1", None),
+                ("section_break", "", None),
+                ("paragraph_1", "Chapter 2", None),
+                ("footer_default", "Page", Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_symbols_and_ascii_fold() {
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(
+                    docx_rs::Run::new()
+                        .add_text("Done ")
+                        .add_sym(docx_rs::Sym::new("Wingdings", "F0FC"))
+                        .add_sym(docx_rs::Sym::new("Symbol", "F0B1"))
+                        .add_sym(docx_rs::Sym::new("Webdings", "F021")),
+                ),
+            )
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("“Smart” — it’s…")),
+            );
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+
+        let texts = |ascii_fold| -> Vec<String> {
+            let opts = ExtractOptions {
+                ascii_fold,
+                ..Default::default()
+            };
+            extract(&buffer, opts)
+                .segments
+                .into_iter()
+                .map(|s| s.text)
+                .collect()
+        };
+        // Unknown symbols leave a placeholder instead of vanishing
+        assert_eq!(texts(false), ["Done ✓±\u{FFFD}", "“Smart” — it’s…"]);
+        assert_eq!(texts(true), ["Done ✓±\u{FFFD}", "\"Smart\" -- it's..."]);
+    }
+
+    #[test]
+    fn test_invalid_utf8_run_is_kept_with_low_confidence() {
+        use std::io::{Cursor, Write};
+
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Clean")))
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Caf@@ au lait")),
+            );
+        let mut packed = Cursor::new(Vec::new());
+        docx.build().pack(&mut packed).unwrap();
+
+        // A stray continuation byte and a truncated two-byte sequence
+        let mut archive = zip::ZipArchive::new(packed).unwrap();
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).unwrap();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).unwrap();
+            if entry.name() == ooxml::DOCUMENT_PART {
+                let at = content.windows(2).position(|w| w == b"@@").unwrap();
+                content.splice(at..at + 2, [0x80, 0xC3]);
+            }
+            writer
+                .start_file(entry.name(), zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(&content).unwrap();
+        }
+        let buffer = writer.finish().unwrap().into_inner();
+
+        let output = extract(&buffer, ExtractOptions::default());
+        assert!(output.errors.is_empty());
+        let texts: Vec<_> = output.segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["Clean", "Caf\u{FFFD}\u{FFFD} au lait"]);
+        assert_eq!(output.segments[0].confidence, 1.0);
+        assert_eq!(output.segments[1].confidence, LOSSY_TEXT_CONFIDENCE);
+        assert_eq!(
+            output.metadata["invalid_utf8_parts"],
+            serde_json::json!([ooxml::DOCUMENT_PART])
+        );
+    }
+
+    #[test]
+    fn test_tables_only() {
+        let table = |text: &str| {
+            docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![docx_rs::TableCell::new(
+            )
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text)),
+            )])])
+        };
+        let docx = docx_rs::Docx::new()
+            .header(docx_rs::Header::new().add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Header")),
+            ))
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Intro")))
+            .add_table(table("First"))
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Between")),
+            )
+            .add_table(table("Second"));
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+
+        let output = extract(
+            &buffer,
+            ExtractOptions {
+                tables_only: true,
+                ..Default::default()
+            },
+        );
+        assert!(output.segments.is_empty());
+        assert!(output.errors.is_empty());
+        let sections: Vec<_> = output.tables.iter().map(|t| t.section.clone()).collect();
+        assert_eq!(sections, [Some("table_0".into()), Some("table_1".into())]);
+        assert_eq!(output.tables[1].rows, [["Second"]]);
+        assert_eq!(output.metadata["table_count"], 2);
+
+        let tail = extract(
+            &buffer,
+            ExtractOptions {
+                tables_only: true,
+                start_index: Some(2),
+                ..Default::default()
+            },
+        );
+        assert_eq!(tail.tables.len(), 1);
+
+        // Same walk as a full extraction: tables in content controls are
+        // found and limits count tables
+        let sdt = r#"<w:sdt><w:sdtPr><w:tag w:val="totals"/></w:sdtPr><w:sdtContent>
+            <w:tbl><w:tr><w:tc><w:p><w:r><w:t>Total</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+            </w:sdtContent></w:sdt>"#;
+        let docx = docx_rs::Docx::new()
+            .add_table(table("First"))
+            .add_paragraph(docx_rs::Paragraph::new());
+        let controlled = pack_with(docx, |xml| {
+            let at = xml.find("<w:sectPr").unwrap();
+            format!("{}{}{}", &xml[..at], sdt, &xml[at..])
+        });
+        let output = extract(
+            &controlled,
+            ExtractOptions {
+                tables_only: true,
+                ..Default::default()
+            },
+        );
+        assert!(output.segments.is_empty());
+        assert_eq!(output.tables[1].rows, [["Total"]]);
+
+        let limited = extract(
+            &controlled,
+            ExtractOptions {
+                tables_only: true,
+                max_segments: Some(1),
+                ..Default::default()
+            },
+        );
+        assert_eq!(limited.tables.len(), 1);
+        assert!(limited.truncated);
+        assert_eq!(limited.errors[0].code, "TRUNCATED");
+    }
+
+    #[test]
+    fn test_content_controls() {
+        let body = r#"
+            <w:sdt><w:sdtPr><w:alias w:val="Customer name"/><w:tag w:val="customer"/></w:sdtPr>
+                <w:sdtContent><w:p><w:r><w:t>ACME</w:t></w:r></w:p></w:sdtContent></w:sdt>
+            <w:p><w:r><w:t xml:space="preserve">Signed on </w:t></w:r><w:sdt><w:sdtPr><w:alias w:val="Date"/></w:sdtPr>
+                <w:sdtContent><w:r><w:t>today</w:t></w:r></w:sdtContent></w:sdt></w:p>
+            <w:sdt><w:sdtPr><w:tag w:val="items"/></w:sdtPr><w:sdtContent>
+                <w:sdt><w:sdtPr/><w:sdtContent><w:p><w:r><w:t>Item A</w:t></w:r></w:p></w:sdtContent></w:sdt>
+                <w:sdt><w:sdtPr/><w:sdtContent><w:p><w:r><w:t>Item B</w:t></w:r></w:p></w:sdtContent></w:sdt>
+            </w:sdtContent></w:sdt>
+            <w:p><w:r><w:t>After</w:t></w:r></w:p>
+            <w:tbl><w:tr><w:tc><w:sdt><w:sdtPr><w:tag w:val="amount"/></w:sdtPr>
+                <w:sdtContent><w:p><w:r><w:t>42</w:t></w:r></w:p></w:sdtContent></w:sdt></w:tc></w:tr></w:tbl>"#;
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("BODY")));
+        let buffer = pack_with(docx, |xml| {
+            let start = xml.find("<w:p ").or_else(|| xml.find("<w:p>")).unwrap();
+            let end = xml.find("</w:p>").unwrap() + "</w:p>".len();
+            format!("{}{}{}", &xml[..start], body, &xml[end..])
+        });
+
+        let output = extract(&buffer, ExtractOptions::default());
+        let found: Vec<_> = output
+            .segments
+            .iter()
+            .map(|s| (s.text.trim(), s.sdt_tag.as_deref(), s.source_index))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("ACME", Some("customer"), Some(0)),
+                ("Signed on today", Some("Date"), Some(1)),
+                // Each repetition of a repeating section is emitted; docx-rs
+                // splits the later ones into body children of their own
+                ("Item A", Some("items"), Some(2)),
+                ("Item B", Some("items"), Some(3)),
+                ("After", None, Some(4)),
+                // The flattened table is not the control's own paragraph
+                ("42", None, Some(5)),
+            ]
+        );
+        assert_eq!(output.tables[0].rows, [["42"]]);
+        assert_eq!(output.metadata["paragraph_count"], 5);
+    }
+
+    #[test]
+    fn test_content_control_tag_skips_wrapped_table() {
+        let body = r#"
+            <w:sdt><w:sdtPr><w:tag w:val="order"/></w:sdtPr><w:sdtContent>
+                <w:p><w:r><w:t>Order lines</w:t></w:r></w:p>
+                <w:tbl><w:tr>
+                    <w:tc><w:p><w:r><w:t>Widget</w:t></w:r></w:p></w:tc>
+                    <w:tc><w:p><w:r><w:t>3</w:t></w:r></w:p></w:tc>
+                </w:tr></w:tbl>
+            </w:sdtContent></w:sdt>"#;
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("BODY")));
+        let buffer = pack_with(docx, |xml| {
+            let start = xml.find("<w:p ").or_else(|| xml.find("<w:p>")).unwrap();
+            let end = xml.find("</w:p>").unwrap() + "</w:p>".len();
+            format!("{}{}{}", &xml[..start], body, &xml[end..])
+        });
+
+        let output = extract(&buffer, ExtractOptions::default());
+        assert_eq!(output.tables[0].rows, [["Widget", "3"]]);
+        let tag_of = |prefix: &str| {
+            let segment = output
+                .segments
+                .iter()
+                .find(|s| s.section.as_deref().is_some_and(|s| s.starts_with(prefix)))
+                .unwrap();
+            segment.sdt_tag.as_deref()
+        };
+        assert_eq!(tag_of("sdt_"), Some("order"));
+        assert_eq!(tag_of("table_"), None);
+    }
+
+    #[test]
+    fn test_content_control_paragraphs_keep_formatting() {
+        let body = r#"
+            <w:sdt><w:sdtPr><w:tag w:val="terms"/></w:sdtPr><w:sdtContent>
+                <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr>
+                    <w:r><w:t>First term</w:t></w:r></w:p>
+                <w:p><w:r><w:t xml:space="preserve">Pay </w:t></w:r>
+                    <w:ins w:id="1" w:author="Reviewer" w:date="2024-01-01T00:00:00Z"><w:r><w:t>200</w:t></w:r></w:ins>
+                    <w:r><w:t xml:space="preserve"> EUR</w:t></w:r></w:p>
+            </w:sdtContent></w:sdt>"#;
+        let docx = docx_rs::Docx::new()
+            .add_abstract_numbering(docx_rs::AbstractNumbering::new(1).add_level(
+                docx_rs::Level::new(
+                    0,
+                    docx_rs::Start::new(1),
+                    docx_rs::NumberFormat::new("decimal"),
+                    docx_rs::LevelText::new("%1."),
+                    docx_rs::LevelJc::new("left"),
+                ),
+            ))
+            .add_numbering(docx_rs::Numbering::new(1, 1))
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("BODY")));
+        let buffer = pack_with(docx, |xml| {
+            let start = xml.find("<w:p ").or_else(|| xml.find("<w:p>")).unwrap();
+            let end = xml.find("</w:p>").unwrap() + "</w:p>".len();
+            format!("{}{}{}", &xml[..start], body, &xml[end..])
+        });
+
+        let output = extract(&buffer, ExtractOptions::default());
+        let found: Vec<_> = output
+            .segments
+            .iter()
+            .map(|s| {
+                (
+                    s.text.as_str(),
+                    s.section.as_deref(),
+                    s.list_level,
+                    s.change_type.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("1. First term", Some("sdt_0"), Some(0), None),
+                ("Pay ", Some("sdt_0"), None, None),
+                ("200", Some("sdt_0"), None, Some("inserted")),
+                (" EUR", Some("sdt_0"), None, None),
+            ]
+        );
+        assert!(output
+            .segments
+            .iter()
+            .all(|s| s.sdt_tag.as_deref() == Some("terms")));
+        assert_eq!(output.metadata["paragraph_count"], 2);
+    }
+
+    #[test]
+    fn test_skipped_children() {
+        let toc = docx_rs::DocumentChild::TableOfContents(Box::default());
+        assert_eq!(child_variant(&toc), "tableOfContents");
+
+        // Bookmarks are read elsewhere and not reported as skipped
+        let docx = docx_rs::Docx::new()
+            .add_bookmark_start(1, "Intro")
+            .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Hello")))
+            .add_bookmark_end(1);
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+        let output = extract(&buffer, ExtractOptions::default());
+        assert_eq!(output.metadata["skipped_children"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_tables_stay_in_reading_order() {
+        let para =
+            |text: &str| docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text));
+        let table = |text: &str| {
+            docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![docx_rs::TableCell::new(
+            )
+            .add_paragraph(para(text))])])
+        };
+        let docx = docx_rs::Docx::new()
+            .add_paragraph(para("Intro"))
+            .add_paragraph(para(""))
+            .add_paragraph(para("Before"))
+            .add_table(table("Cell A"))
+            .add_paragraph(para("Between"))
+            .add_table(table("Cell B"))
+            .add_paragraph(para("After"));
+        let mut buffer = Vec::new();
+        docx.build()
+            .pack(&mut std::io::Cursor::new(&mut buffer))
+            .unwrap();
+
+        let output = extract(&buffer, ExtractOptions::default());
+        let flow: Vec<_> = output
+            .segments
+            .iter()
+            .map(|s| (s.section.clone().unwrap(), s.text.trim().to_string()))
+            .collect();
+        assert_eq!(
+            flow,
+            [
+                ("paragraph_0".to_string(), "Intro".to_string()),
+                ("paragraph_2".to_string(), "Before".to_string()),
+                ("table_0".to_string(), "Cell A".to_string()),
+                ("paragraph_4".to_string(), "Between".to_string()),
+                ("table_1".to_string(), "Cell B".to_string()),
+                ("paragraph_6".to_string(), "After".to_string()),
+            ]
+        );
+        // Each flattened segment names its structured table by position
+        for (n, table) in output.tables.iter().enumerate() {
+            assert_eq!(table.section, Some(format!("table_{}", n)));
+        }
+        assert_eq!(output.tables[1].rows, [["Cell B"]]);
+        let sources: Vec<_> = output.segments.iter().map(|s| s.source_index).collect();
+        assert!(sources.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_table_rows_with_merged_cells() {
+        let cell = |text: &str| {
+            docx_rs::TableCell::new().add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text)),
+            )
+        };
+        let table = docx_rs::Table::new(vec![
+            docx_rs::TableRow::new(vec![
+                cell("Q1\tQ2").grid_span(2),
+                cell("Total").vertical_merge(docx_rs::VMergeType::Restart),
+            ]),
+            docx_rs::TableRow::new(vec![
+                cell("10"),
+                cell("20"),
+                docx_rs::TableCell::new().vertical_merge(docx_rs::VMergeType::Continue),
+            ]),
+        ]);
+
+        assert_eq!(
+            table_rows(&table),
+            vec![
+                vec!["Q1\tQ2".to_string(), String::new(), "Total".to_string()],
+                vec!["10".to_string(), "20".to_string(), String::new()],
+            ]
+        );
+        assert_eq!(
+            table_cells(&table),
+            vec![
+                vec![
+                    TableCellSegment::new("Q1\tQ2".into(), 2, 1),
+                    TableCellSegment::new("Total".into(), 1, 2),
+                ],
+                vec![
+                    TableCellSegment::new("10".into(), 1, 1),
+                    TableCellSegment::new("20".into(), 1, 1),
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_tables_keep_inner_content() {
+        let cell = |text: &str| {
+            docx_rs::TableCell::new().add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text)),
+            )
+        };
+        let schedule = docx_rs::Table::new(vec![
+            docx_rs::TableRow::new(vec![cell("Fee"), cell("100")]),
+            docx_rs::TableRow::new(vec![cell("Tax"), cell("20")]),
+        ]);
+        let table = docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![
+            cell("Schedule A"),
+            cell("See:").add_table(schedule),
+        ])]);
+
+        assert_eq!(
+            table_rows(&table),
+            vec![vec![
+                "Schedule A".to_string(),
+                "See:\nFee\t100\nTax\t20".to_string()
+            ]]
+        );
+        assert_eq!(table_text(&table), "Schedule A \tSee: Fee 100 Tax 20 \t\n");
+        assert_eq!(
+            table_text_with(&table, ";", "|"),
+            "Schedule A ;See: Fee 100 Tax 20 ;|"
+        );
+        let tabbed = docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![
+            cell("a\tb"),
+            cell("c;d"),
+        ])]);
+        assert_eq!(table_text(&tabbed), "a b \tc;d \t\n");
+        assert_eq!(table_text_with(&tabbed, ";", "\n"), "a\tb ;c d ;\n");
+        assert_eq!(table_paragraphs(&table).len(), 6);
+
+        // Pathologically deep nesting stops at the depth guard
+        let mut deep = docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![cell("bottom")])]);
+        for _ in 0..MAX_TABLE_DEPTH + 2 {
+            deep =
+                docx_rs::Table::new(vec![docx_rs::TableRow::new(vec![docx_rs::TableCell::new(
+                )
+                .add_table(deep)])]);
+        }
+        assert!(!table_text(&deep).contains("bottom"));
+        assert!(table_paragraphs(&deep).is_empty());
+    }
+
+    #[test]
+    fn test_coalesce_split_runs() {
+        let para = docx_rs::Paragraph::new()
+            .add_run(docx_rs::Run::new().add_text("he"))
+            .add_run(docx_rs::Run::new().add_text("ll"))
+            .add_run(docx_rs::Run::new().add_text("o"))
+            .add_run(docx_rs::Run::new().add_text(" world").bold());
+
+        let chunks = coalesce_runs(&para, false);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].0, "hello");
+        assert!(!chunks[0].1.bold);
+        assert_eq!(chunks[1].0, " world");
+        assert!(chunks[1].1.bold);
+
+        let spans = paragraph_spans(&para, false, false);
+        assert_eq!(spans, vec![("hello world".to_string(), None)]);
+    }
+
+    #[test]
+    fn test_heading_level_from_style() {
+        let styles = docx_rs::Styles::new().add_style(
+            docx_rs::Style::new("Titre2", docx_rs::StyleType::Paragraph).name("heading 2"),
+        );
+
+        let localized = docx_rs::Paragraph::new().style("Titre2");
+        let by_id = docx_rs::Paragraph::new().style("Heading3");
+        let body = docx_rs::Paragraph::new().style("Normal");
+
+        assert_eq!(heading_level(&localized, &styles), Some(2));
+        assert_eq!(heading_level(&by_id, &styles), Some(3));
+        assert_eq!(heading_level(&body, &styles), None);
+    }
+
+    #[test]
+    fn test_paragraph_style_names() {
+        let styles = docx_rs::Styles::new().add_style(
+            docx_rs::Style::new("IntenseQuote", docx_rs::StyleType::Paragraph)
+                .name("Intense Quote"),
+        );
+
+        let named = docx_rs::Paragraph::new().style("IntenseQuote");
+        let unknown = docx_rs::Paragraph::new().style("Caption");
+
+        assert_eq!(
+            paragraph_style(&named, &styles).as_deref(),
+            Some("Intense Quote")
+        );
+        assert_eq!(
+            paragraph_style(&unknown, &styles).as_deref(),
+            Some("Caption")
+        );
+        assert_eq!(paragraph_style(&docx_rs::Paragraph::new(), &styles), None);
+    }
+}
@@ -0,0 +1,1581 @@
+//! Raw OOXML package access
+//!
+//! docx-rs does not load every part of a DOCX package (footnotes,
+//! endnotes, ...). These helpers read parts straight from the zip
+//! container and scan them with xml-rs.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use xml::reader::{EventReader, XmlEvent};
+
+/// Main document part (docx-rs falls back to the same default path)
+pub(crate) const DOCUMENT_PART: &str = "word/document.xml";
+
+/// Top-level `w:body` elements that docx-rs turns into a `DocumentChild`
+const BODY_CHILDREN: [&str; 7] = [
+    "p",
+    "tbl",
+    "bookmarkStart",
+    "bookmarkEnd",
+    "commentRangeStart",
+    "commentRangeEnd",
+    "sdt",
+];
+
+/// Signature of OLE compound files (legacy `.doc`, encrypted packages)
+pub(crate) const OLE_MAGIC: [u8; 4] = [0xD0, 0xCF, 0x11, 0xE0];
+
+/// Names of all entries in the zip container, or None if it is not a zip
+pub(crate) fn part_names(buffer: &[u8]) -> Option<Vec<String>> {
+    let archive = zip::ZipArchive::new(Cursor::new(buffer)).ok()?;
+    Some(archive.file_names().map(str::to_string).collect())
+}
+
+/// True if the zip entries inflate to more than `limit` bytes in total
+///
+/// Entries are actually decompressed (into a sink, at most `limit + 1`
+/// bytes) since declared sizes in the central directory can lie.
+/// Unreadable entries are left for the parser to report.
+pub(crate) fn exceeds_decompressed_size(buffer: &[u8], limit: u64) -> bool {
+    let Ok(mut archive) = zip::ZipArchive::new(Cursor::new(buffer)) else {
+        return false;
+    };
+
+    let mut total = 0u64;
+    for i in 0..archive.len() {
+        let Ok(file) = archive.by_index(i) else {
+            continue;
+        };
+        let remaining = limit - total;
+        match std::io::copy(
+            &mut file.take(remaining.saturating_add(1)),
+            &mut std::io::sink(),
+        ) {
+            Ok(read) if read > remaining => return true,
+            Ok(read) => total += read,
+            Err(_) => continue,
+        }
+    }
+
+    false
+}
+
+/// Read a part (e.g. `word/footnotes.xml`) from the DOCX zip container
+pub(crate) fn read_part(buffer: &[u8], name: &str) -> Option<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(buffer)).ok()?;
+    let mut file = archive.by_name(name).ok()?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).ok()?;
+    Some(data)
+}
+
+/// Zip entries holding XML: parts and their relationships
+fn is_xml_part(name: &str) -> bool {
+    name.ends_with(".xml") || name.ends_with(".rels")
+}
+
+/// True unless the part is UTF-16 (BOM) or declares a non-UTF-8 encoding
+/// in its prolog, i.e. its bytes are meant to be read as UTF-8
+fn is_utf8_part(xml: &[u8]) -> bool {
+    if xml.starts_with(&[0xFF, 0xFE]) || xml.starts_with(&[0xFE, 0xFF]) {
+        return false;
+    }
+    let prolog = match xml.windows(2).position(|w| w == b"?>") {
+        Some(end) if xml.starts_with(b"<?xml") => String::from_utf8_lossy(&xml[..end]),
+        _ => return true,
+    };
+    match prolog.split_once("encoding=") {
+        Some((_, rest)) => {
+            let encoding = rest.trim_start_matches(['"', '\'']);
+            let encoding = encoding.split(['"', '\'']).next().unwrap_or_default();
+            matches!(encoding.to_ascii_lowercase().as_str(), "utf-8" | "utf8")
+        }
+        None => true,
+    }
+}
+
+/// Replace invalid UTF-8 in the package's XML parts with U+FFFD
+///
+/// Corrupted files occasionally carry stray bytes in text runs, which
+/// would make the XML parser reject the whole part. Returns the rebuilt
+/// package and the names of the repaired parts, or None if nothing needed
+/// repair (or the container is not a readable zip).
+pub(crate) fn repair_utf8(buffer: &[u8]) -> Option<(Vec<u8>, Vec<String>)> {
+    use std::io::Write;
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(buffer)).ok()?;
+    let mut repaired: HashMap<String, Vec<u8>> = HashMap::new();
+    for i in 0..archive.len() {
+        let Ok(mut file) = archive.by_index(i) else {
+            continue;
+        };
+        if !is_xml_part(file.name()) {
+            continue;
+        }
+        let mut data = Vec::new();
+        if file.read_to_end(&mut data).is_err() {
+            continue;
+        }
+        if std::str::from_utf8(&data).is_err() && is_utf8_part(&data) {
+            let text = String::from_utf8_lossy(&data).into_owned();
+            repaired.insert(file.name().to_string(), text.into_bytes());
+        }
+    }
+    if repaired.is_empty() {
+        return None;
+    }
+
+    // Untouched entries are copied without recompressing
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for i in 0..archive.len() {
+        let name = archive.by_index_raw(i).ok()?.name().to_string();
+        match repaired.get(&name) {
+            Some(data) => {
+                writer.start_file(name, options).ok()?;
+                writer.write_all(data).ok()?;
+            }
+            None => writer.raw_copy_file(archive.by_index_raw(i).ok()?).ok()?,
+        }
+    }
+    let package = writer.finish().ok()?.into_inner();
+
+    let mut names: Vec<_> = repaired.into_keys().collect();
+    names.sort();
+    Some((package, names))
+}
+
+/// Core document properties part (title, author, dates)
+pub(crate) const CORE_PROPERTIES_PART: &str = "docProps/core.xml";
+
+/// Extended (application) properties part (word/page counts)
+pub(crate) const APP_PROPERTIES_PART: &str = "docProps/app.xml";
+
+/// Text of each leaf element in a flat properties part, keyed by local name
+///
+/// Empty values are skipped so callers can omit missing fields.
+pub(crate) fn read_properties(xml: &[u8]) -> HashMap<String, String> {
+    let mut properties = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+
+    for event in EventReader::new(xml) {
+        match event {
+            Ok(XmlEvent::StartElement { name, .. }) => {
+                current = Some((name.local_name, String::new()));
+            }
+            Ok(XmlEvent::Characters(text)) => {
+                if let Some((_, value)) = current.as_mut() {
+                    value.push_str(&text);
+                }
+            }
+            Ok(XmlEvent::EndElement { name }) => {
+                if let Some((key, value)) = current.take() {
+                    let value = value.trim();
+                    if key == name.local_name && !value.is_empty() {
+                        properties.insert(key, value.to_string());
+                    }
+                }
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    properties
+}
+
+/// Relationships part of the main document (hyperlink targets, media, ...)
+pub(crate) const DOCUMENT_RELS_PART: &str = "word/_rels/document.xml.rels";
+
+/// Target of a hyperlink relationship
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct HyperlinkTarget {
+    pub target: String,
+    /// `TargetMode="External"` (a URL) rather than a part in the package
+    pub external: bool,
+}
+
+/// Hyperlink relationships in a `.rels` part, in document order
+pub(crate) fn read_hyperlink_targets(rels_xml: &[u8]) -> Vec<HyperlinkTarget> {
+    let mut targets = Vec::new();
+
+    for event in EventReader::new(rels_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) if name.local_name == "Relationship" => {
+                let is_hyperlink = attribute(&attributes, "Type")
+                    .is_some_and(|t| t.ends_with("/relationships/hyperlink"));
+                let Some(target) = attribute(&attributes, "Target").filter(|_| is_hyperlink) else {
+                    continue;
+                };
+                let external = attribute(&attributes, "TargetMode")
+                    .is_some_and(|mode| mode.eq_ignore_ascii_case("External"));
+                targets.push(HyperlinkTarget { target, external });
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    targets
+}
+
+/// Targets of all relationships in a `.rels` part, keyed by id
+pub(crate) fn read_relationship_targets(rels_xml: &[u8]) -> HashMap<String, String> {
+    let mut targets = HashMap::new();
+
+    for event in EventReader::new(rels_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) if name.local_name == "Relationship" => {
+                if let (Some(id), Some(target)) = (
+                    attribute(&attributes, "Id"),
+                    attribute(&attributes, "Target"),
+                ) {
+                    targets.insert(id, target);
+                }
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    targets
+}
+
+/// Zip entry name of a relationship target of the main document
+///
+/// Targets are relative to `word/` unless they start with `/`.
+pub(crate) fn document_part_name(target: &str) -> String {
+    match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("word/{}", target),
+    }
+}
+
+/// Bookmark names targeted by internal `w:hyperlink w:anchor` links
+pub(crate) fn read_hyperlink_anchors(document_xml: &[u8]) -> Vec<String> {
+    let mut anchors = Vec::new();
+
+    for event in EventReader::new(document_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) if name.local_name == "hyperlink" => {
+                if let Some(anchor) = attribute(&attributes, "anchor") {
+                    anchors.push(anchor);
+                }
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    anchors
+}
+
+/// Value of an attribute by local name (ignores the namespace prefix)
+fn attribute(attributes: &[xml::attribute::OwnedAttribute], name: &str) -> Option<String> {
+    attributes
+        .iter()
+        .find(|a| a.name.local_name == name)
+        .map(|a| a.value.clone())
+}
+
+/// Footnote or endnote
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum NoteKind {
+    Footnote,
+    Endnote,
+}
+
+impl NoteKind {
+    /// Package part holding the note bodies
+    pub(crate) fn part_name(self) -> &'static str {
+        match self {
+            NoteKind::Footnote => "word/footnotes.xml",
+            NoteKind::Endnote => "word/endnotes.xml",
+        }
+    }
+
+    /// Element name of a note body inside its part
+    fn element(self) -> &'static str {
+        match self {
+            NoteKind::Footnote => "footnote",
+            NoteKind::Endnote => "endnote",
+        }
+    }
+
+    /// Element name of the inline reference in the document body
+    fn reference_element(self) -> &'static str {
+        match self {
+            NoteKind::Footnote => "footnoteReference",
+            NoteKind::Endnote => "endnoteReference",
+        }
+    }
+
+    /// Prefix used for segment section names (`footnote_3`)
+    pub(crate) fn section_prefix(self) -> &'static str {
+        self.element()
+    }
+}
+
+/// Body text of a footnote/endnote
+pub(crate) struct Note {
+    pub id: usize,
+    pub text: String,
+}
+
+/// Parse the notes part, skipping separator and continuation notes
+pub(crate) fn read_notes(xml: &[u8], kind: NoteKind) -> Vec<Note> {
+    let mut notes = Vec::new();
+    let mut current: Option<Note> = None;
+    let mut in_text = false;
+
+    for event in EventReader::new(xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => match name.local_name.as_str() {
+                n if n == kind.element() => {
+                    // Separators carry a w:type; real notes do not
+                    let id = attribute(&attributes, "id").and_then(|v| v.parse().ok());
+                    current = match (id, attribute(&attributes, "type")) {
+                        (Some(id), None) => Some(Note {
+                            id,
+                            text: String::new(),
+                        }),
+                        _ => None,
+                    };
+                }
+                "t" => in_text = true,
+                "tab" => {
+                    if let Some(note) = current.as_mut() {
+                        note.text.push('\t');
+                    }
+                }
+                _ => {}
+            },
+            Ok(XmlEvent::Characters(text)) | Ok(XmlEvent::Whitespace(text)) => {
+                if let (true, Some(note)) = (in_text, current.as_mut()) {
+                    note.text.push_str(&text);
+                }
+            }
+            Ok(XmlEvent::EndElement { name }) => match name.local_name.as_str() {
+                n if n == kind.element() => {
+                    if let Some(mut note) = current.take() {
+                        note.text = note.text.trim().to_string();
+                        notes.push(note);
+                    }
+                }
+                "t" => in_text = false,
+                "p" => {
+                    if let Some(note) = current.as_mut() {
+                        note.text.push('\n');
+                    }
+                }
+                _ => {}
+            },
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    notes
+}
+
+/// Location of an inline footnote/endnote marker in the document body
+pub(crate) struct NoteReference {
+    pub kind: NoteKind,
+    pub id: usize,
+    /// Index into `docx.document.children`
+    pub child_index: usize,
+    /// Character offset into the visible (`w:t`) text of that child
+    pub offset: usize,
+}
+
+/// Elements holding a separate story (text box content) or a duplicate
+/// rendering of one (`mc:Fallback`), which is not part of the body text
+const NESTED_STORIES: [&str; 2] = ["txbxContent", "Fallback"];
+
+/// Tracks which top-level body child the current XML event belongs to
+///
+/// Child indices are counted the same way docx-rs builds
+/// `Document::children`, so they line up with `paragraph_{idx}` sections.
+/// That includes its handling of nested content controls: a block `w:sdt`
+/// ends at the first nested `w:sdt` end outside a paragraph, table or run,
+/// and what follows starts new children.
+#[derive(Default)]
+struct BodyCursor {
+    depth: usize,
+    next_index: usize,
+    /// (depth the top-level element opened at, its child index)
+    current: Option<(usize, Option<usize>)>,
+    /// Depth a nested story opened at
+    nested: Option<usize>,
+    /// The current child is a block content control
+    in_sdt: bool,
+    /// Depth of the paragraph/table/run open in it; docx-rs reads those whole
+    sdt_block: Option<usize>,
+    /// Depth of a nested `w:sdt` whose end docx-rs takes as the control's
+    sdt_nested: Option<usize>,
+}
+
+impl BodyCursor {
+    /// Register an opening element; true if it starts a new body child
+    fn start(&mut self, local: &str) -> bool {
+        self.depth += 1;
+        if self.nested.is_none() && NESTED_STORIES.contains(&local) {
+            self.nested = Some(self.depth);
+        }
+        if self.in_sdt && self.sdt_block.is_none() {
+            match local {
+                "p" | "tbl" | "r" => self.sdt_block = Some(self.depth),
+                "sdt" if self.sdt_nested.is_none() => self.sdt_nested = Some(self.depth),
+                _ => {}
+            }
+        }
+        match self.current {
+            None if BODY_CHILDREN.contains(&local) => {
+                self.current = Some((self.depth, Some(self.next_index)));
+                self.next_index += 1;
+                self.in_sdt = local == "sdt";
+                true
+            }
+            None if local == "sectPr" => {
+                self.current = Some((self.depth, None));
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Register a closing element
+    fn end(&mut self) {
+        if self.nested == Some(self.depth) {
+            self.nested = None;
+        }
+        if self.sdt_block == Some(self.depth) {
+            self.sdt_block = None;
+        }
+        let nested_sdt_end = self.sdt_nested == Some(self.depth);
+        if nested_sdt_end || matches!(self.current, Some((start, _)) if start == self.depth) {
+            self.current = None;
+            self.in_sdt = false;
+            self.sdt_nested = None;
+        }
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Index of the body child currently open, if any (None inside
+    /// nested stories, whose text does not belong to the child)
+    fn child_index(&self) -> Option<usize> {
+        match self.nested {
+            Some(_) => None,
+            None => self.current.and_then(|(_, index)| index),
+        }
+    }
+}
+
+/// Scan `word/document.xml` for footnote/endnote reference markers
+pub(crate) fn read_note_references(document_xml: &[u8]) -> Vec<NoteReference> {
+    let mut references = Vec::new();
+    let mut cursor = BodyCursor::default();
+    let mut in_text = false;
+    let mut offset = 0usize;
+
+    for event in EventReader::new(document_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                let local = name.local_name.as_str();
+                if cursor.start(local) {
+                    offset = 0;
+                    continue;
+                }
+                let Some(child_index) = cursor.child_index() else {
+                    continue;
+                };
+
+                if local == "t" {
+                    in_text = true;
+                }
+                for kind in [NoteKind::Footnote, NoteKind::Endnote] {
+                    if local != kind.reference_element() {
+                        continue;
+                    }
+                    if let Some(id) = attribute(&attributes, "id").and_then(|v| v.parse().ok()) {
+                        references.push(NoteReference {
+                            kind,
+                            id,
+                            child_index,
+                            offset,
+                        });
+                    }
+                }
+            }
+            Ok(XmlEvent::Characters(text)) | Ok(XmlEvent::Whitespace(text)) if in_text => {
+                offset += text.chars().count();
+            }
+            Ok(XmlEvent::EndElement { name }) => {
+                if name.local_name == "t" {
+                    in_text = false;
+                }
+                cursor.end();
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    references
+}
+
+/// Alt text of an image drawn in the document body
+pub(crate) struct ImageAlt {
+    /// Index into `docx.document.children`
+    pub child_index: usize,
+    /// `wp:docPr` description, falling back to its title
+    pub text: String,
+    /// Relationship id of the embedded picture (`a:blip r:embed`)
+    pub media_id: Option<String>,
+}
+
+/// Scan `word/document.xml` for image alt text (docx-rs drops `wp:docPr`)
+///
+/// Images without a description or title are skipped.
+pub(crate) fn read_image_alts(document_xml: &[u8]) -> Vec<ImageAlt> {
+    let mut alts = Vec::new();
+    let mut cursor = BodyCursor::default();
+    // (alt text, media id) of the drawing currently open
+    let mut drawing: Option<(String, Option<String>)> = None;
+
+    for event in EventReader::new(document_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                let local = name.local_name.as_str();
+                cursor.start(local);
+
+                match (local, drawing.as_mut()) {
+                    ("drawing", _) => drawing = Some((String::new(), None)),
+                    ("docPr", Some((text, _))) => {
+                        let describe =
+                            |key| attribute(&attributes, key).filter(|v| !v.trim().is_empty());
+                        if let Some(alt) = describe("descr").or_else(|| describe("title")) {
+                            *text = alt.trim().to_string();
+                        }
+                    }
+                    ("blip", Some((_, media_id))) if media_id.is_none() => {
+                        *media_id = attribute(&attributes, "embed");
+                    }
+                    _ => {}
+                }
+            }
+            Ok(XmlEvent::EndElement { name }) => {
+                if name.local_name == "drawing" {
+                    if let (Some((text, media_id)), Some(child_index)) =
+                        (drawing.take(), cursor.child_index())
+                    {
+                        if !text.is_empty() {
+                            alts.push(ImageAlt {
+                                child_index,
+                                text,
+                                media_id,
+                            });
+                        }
+                    }
+                }
+                cursor.end();
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    alts
+}
+
+/// Scan `word/document.xml` for body paragraphs with an explicit base
+/// direction (`w:pPr/w:bidi`), which docx-rs does not read
+///
+/// Maps the paragraph's body child index to true for right-to-left,
+/// false where bidi is explicitly switched off.
+pub(crate) fn read_paragraph_bidi(document_xml: &[u8]) -> HashMap<usize, bool> {
+    let mut bidi = HashMap::new();
+    let mut cursor = BodyCursor::default();
+    // Depth of the open paragraph's own `w:pPr`
+    let mut ppr_depth = None;
+
+    for event in EventReader::new(document_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                let local = name.local_name.as_str();
+                if cursor.start(local) {
+                    continue;
+                }
+                let (Some(child_index), Some((child_depth, _))) =
+                    (cursor.child_index(), cursor.current)
+                else {
+                    continue;
+                };
+
+                match local {
+                    "pPr" if cursor.depth == child_depth + 1 => {
+                        ppr_depth = Some(cursor.depth);
+                    }
+                    "bidi" if ppr_depth == Some(cursor.depth - 1) => {
+                        let on = attribute(&attributes, "val")
+                            .is_none_or(|v| !matches!(v.as_str(), "0" | "false" | "off"));
+                        bidi.insert(child_index, on);
+                    }
+                    _ => {}
+                }
+            }
+            Ok(XmlEvent::EndElement { .. }) => {
+                if ppr_depth == Some(cursor.depth) {
+                    ppr_depth = None;
+                }
+                cursor.end();
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    bidi
+}
+
+/// Scan `word/document.xml` for content controls (`w:sdt`), whose
+/// `w:sdtPr` docx-rs does not read
+///
+/// Maps each body child index to the name of the first control in it
+/// (the block control itself, or one inline in a paragraph): its `w:tag`,
+/// else its `w:alias`. Children docx-rs split off a control, such as the
+/// later items of a repeating section, fall back to that control's name.
+pub(crate) fn read_sdt_tags(document_xml: &[u8]) -> HashMap<usize, String> {
+    let mut tags = HashMap::new();
+    let mut inherited = HashMap::new();
+    let mut cursor = BodyCursor::default();
+    // Depth of the open `w:sdtPr` and the names read from it so far
+    let mut sdt_pr = None;
+    let (mut tag, mut alias) = (None, None);
+    // Open `w:sdt` elements: (depth, name once its sdtPr is read)
+    let mut open: Vec<(usize, Option<String>)> = Vec::new();
+
+    for event in EventReader::new(document_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                let local = name.local_name.as_str();
+                let starts_child = cursor.start(local);
+                if starts_child {
+                    let enclosing = open.iter().rev().find_map(|(_, name)| name.clone());
+                    if let (Some(name), Some(child_index)) = (enclosing, cursor.child_index()) {
+                        inherited.insert(child_index, name);
+                    }
+                }
+                if local == "sdt" {
+                    open.push((cursor.depth, None));
+                }
+                let Some(child_index) = cursor.child_index() else {
+                    continue;
+                };
+
+                match local {
+                    "sdtPr" if sdt_pr.is_none() && !tags.contains_key(&child_index) => {
+                        sdt_pr = Some(cursor.depth);
+                    }
+                    "tag" if sdt_pr == Some(cursor.depth - 1) => {
+                        tag = attribute(&attributes, "val").filter(|v| !v.is_empty());
+                    }
+                    "alias" if sdt_pr == Some(cursor.depth - 1) => {
+                        alias = attribute(&attributes, "val").filter(|v| !v.is_empty());
+                    }
+                    _ => {}
+                }
+            }
+            Ok(XmlEvent::EndElement { .. }) => {
+                if sdt_pr == Some(cursor.depth) {
+                    sdt_pr = None;
+                    let name = tag.take().or(alias.take());
+                    alias = None;
+                    if let Some((_, open_name)) = open.last_mut() {
+                        open_name.clone_from(&name);
+                    }
+                    if let (Some(name), Some(child_index)) = (name, cursor.child_index()) {
+                        tags.entry(child_index).or_insert(name);
+                    }
+                }
+                if open.last().is_some_and(|(depth, _)| *depth == cursor.depth) {
+                    open.pop();
+                }
+                cursor.end();
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    for (child_index, name) in inherited {
+        tags.entry(child_index).or_insert(name);
+    }
+    tags
+}
+
+/// Style definitions part (document defaults, named styles)
+pub(crate) const STYLES_PART: &str = "word/styles.xml";
+
+/// Document default language (`w:docDefaults/w:rPrDefault/w:rPr/w:lang`),
+/// which docx-rs does not read
+pub(crate) fn read_default_language(styles_xml: &[u8]) -> Option<String> {
+    let mut in_defaults = false;
+
+    for event in EventReader::new(styles_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => match name.local_name.as_str() {
+                "rPrDefault" => in_defaults = true,
+                "lang" if in_defaults => {
+                    return attribute(&attributes, "val").filter(|v| !v.is_empty());
+                }
+                _ => {}
+            },
+            Ok(XmlEvent::EndElement { name }) if name.local_name == "rPrDefault" => {
+                in_defaults = false;
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Document settings part (compatibility options, protection)
+pub(crate) const SETTINGS_PART: &str = "word/settings.xml";
+
+/// Editing restriction of an enforced `w:documentProtection`: `readOnly`,
+/// `comments`, `trackedChanges` or `forms`
+///
+/// None if the element is missing, not enforced or restricts nothing.
+/// Restrictions are advisory; the content itself is not encrypted.
+pub(crate) fn read_document_protection(settings_xml: &[u8]) -> Option<String> {
+    for event in EventReader::new(settings_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) if name.local_name == "documentProtection" => {
+                let enforced = attribute(&attributes, "enforcement")
+                    .is_some_and(|v| matches!(v.as_str(), "1" | "true" | "on"));
+                return attribute(&attributes, "edit")
+                    .filter(|edit| enforced && !edit.is_empty() && edit != "none");
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Font named by `w:docDefaults/w:rPrDefault/w:rPr/w:rFonts`
+#[derive(Debug, PartialEq)]
+pub(crate) enum DefaultFont {
+    /// Explicit typeface (`w:ascii`)
+    Named(String),
+    /// Theme font slot (`w:asciiTheme`, e.g. `minorHAnsi`)
+    Theme(String),
+}
+
+/// Document default font for Latin text, which docx-rs does not read
+pub(crate) fn read_default_font(styles_xml: &[u8]) -> Option<DefaultFont> {
+    let mut in_defaults = false;
+
+    for event in EventReader::new(styles_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => match name.local_name.as_str() {
+                "rPrDefault" => in_defaults = true,
+                "rFonts" if in_defaults => {
+                    // The theme reference wins over the cached name
+                    let theme = attribute(&attributes, "asciiTheme").map(DefaultFont::Theme);
+                    let named = || attribute(&attributes, "ascii").map(DefaultFont::Named);
+                    return theme.or_else(named);
+                }
+                _ => {}
+            },
+            Ok(XmlEvent::EndElement { name }) if name.local_name == "rPrDefault" => {
+                in_defaults = false;
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Theme part (color scheme, font scheme)
+pub(crate) const THEME_PART: &str = "word/theme/theme1.xml";
+
+/// Color and font schemes of a theme part
+#[derive(Debug, Default)]
+pub(crate) struct Theme {
+    /// (scheme name, `#RRGGBB`) in part order, e.g. `("accent1", "#4472C4")`
+    pub colors: Vec<(String, String)>,
+    /// Latin typeface of headings (`a:majorFont`)
+    pub major_font: Option<String>,
+    /// Latin typeface of body text (`a:minorFont`)
+    pub minor_font: Option<String>,
+}
+
+impl Theme {
+    /// Typeface of a theme font slot such as `minorHAnsi` or `majorBidi`
+    pub(crate) fn font(&self, slot: &str) -> Option<&str> {
+        if slot.starts_with("major") {
+            self.major_font.as_deref()
+        } else if slot.starts_with("minor") {
+            self.minor_font.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+/// Read the `a:clrScheme` colors and `a:fontScheme` Latin fonts of a theme
+///
+/// System colors (`a:sysClr`) use their last computed value (`lastClr`).
+pub(crate) fn read_theme(theme_xml: &[u8]) -> Theme {
+    let mut theme = Theme::default();
+    let mut in_colors = false;
+    // Scheme color (`a:dk1`, `a:accent1`, ...) currently open
+    let mut color: Option<String> = None;
+    // `a:majorFont` / `a:minorFont` currently open
+    let mut font: Option<String> = None;
+
+    for event in EventReader::new(theme_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => match name.local_name.as_str() {
+                "clrScheme" => in_colors = true,
+                "srgbClr" | "sysClr" => {
+                    let value = attribute(&attributes, "lastClr")
+                        .or_else(|| attribute(&attributes, "val"))
+                        .filter(|v| v.len() == 6 && v.chars().all(|c| c.is_ascii_hexdigit()));
+                    if let (Some(name), Some(value)) = (color.take(), value) {
+                        theme
+                            .colors
+                            .push((name, format!("#{}", value.to_ascii_uppercase())));
+                    }
+                }
+                "majorFont" | "minorFont" => font = Some(name.local_name.clone()),
+                "latin" => {
+                    let typeface = attribute(&attributes, "typeface").filter(|t| !t.is_empty());
+                    match font.take().as_deref() {
+                        Some("majorFont") => theme.major_font = typeface,
+                        Some("minorFont") => theme.minor_font = typeface,
+                        _ => {}
+                    }
+                }
+                local if in_colors => color = Some(local.to_string()),
+                _ => {}
+            },
+            Ok(XmlEvent::EndElement { name }) => match name.local_name.as_str() {
+                "clrScheme" => {
+                    in_colors = false;
+                    color = None;
+                }
+                "majorFont" | "minorFont" => font = None,
+                _ => {}
+            },
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    theme
+}
+
+/// Text of a body child under one run-level language tag
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct LanguageRun {
+    /// Character range in the visible (`w:t`) text of the child
+    pub start: usize,
+    pub end: usize,
+    /// None for runs inheriting the document default
+    pub language: Option<String>,
+}
+
+/// Scan `word/document.xml` for run-level language tags (`w:r/w:rPr/w:lang`)
+///
+/// Maps each body child index to the languages of its text in order;
+/// adjacent runs with the same tag are merged.
+pub(crate) fn read_run_languages(document_xml: &[u8]) -> HashMap<usize, Vec<LanguageRun>> {
+    let mut languages: HashMap<usize, Vec<LanguageRun>> = HashMap::new();
+    let mut cursor = BodyCursor::default();
+    // Depths of the open run and of its own `w:rPr`
+    let mut run_depth = None;
+    let mut rpr_depth = None;
+    let mut run_language: Option<String> = None;
+    let mut in_text = false;
+    let mut offset = 0usize;
+
+    for event in EventReader::new(document_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                let local = name.local_name.as_str();
+                if cursor.start(local) {
+                    offset = 0;
+                    continue;
+                }
+                if cursor.child_index().is_none() {
+                    continue;
+                }
+
+                match local {
+                    "r" => {
+                        run_depth = Some(cursor.depth);
+                        run_language = None;
+                    }
+                    "rPr" if run_depth == Some(cursor.depth - 1) => {
+                        rpr_depth = Some(cursor.depth);
+                    }
+                    "lang" if rpr_depth == Some(cursor.depth - 1) => {
+                        run_language = attribute(&attributes, "val").filter(|v| !v.is_empty());
+                    }
+                    "t" if run_depth.is_some() => in_text = true,
+                    _ => {}
+                }
+            }
+            Ok(XmlEvent::Characters(text)) | Ok(XmlEvent::Whitespace(text)) if in_text => {
+                let Some(child_index) = cursor.child_index() else {
+                    continue;
+                };
+                let end = offset + text.chars().count();
+                let runs = languages.entry(child_index).or_default();
+                match runs.last_mut() {
+                    Some(last) if last.end == offset && last.language == run_language => {
+                        last.end = end;
+                    }
+                    _ => runs.push(LanguageRun {
+                        start: offset,
+                        end,
+                        language: run_language.clone(),
+                    }),
+                }
+                offset = end;
+            }
+            Ok(XmlEvent::EndElement { name }) => {
+                if name.local_name == "t" {
+                    in_text = false;
+                }
+                if rpr_depth == Some(cursor.depth) {
+                    rpr_depth = None;
+                }
+                if run_depth == Some(cursor.depth) {
+                    run_depth = None;
+                }
+                cursor.end();
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    languages
+}
+
+/// Language covering most of the characters `start..end` of `runs`; None
+/// when untagged text wins (or there is none)
+pub(crate) fn language_between(runs: &[LanguageRun], start: usize, end: usize) -> Option<&str> {
+    let mut counts: HashMap<Option<&str>, usize> = HashMap::new();
+    for run in runs {
+        let overlap = run.end.min(end).saturating_sub(run.start.max(start));
+        if overlap > 0 {
+            *counts.entry(run.language.as_deref()).or_default() += overlap;
+        }
+    }
+
+    // Ties go to the smaller tag so the result is deterministic
+    let (language, _) = counts
+        .into_iter()
+        .max_by(|(a, x), (b, y)| x.cmp(y).then_with(|| b.cmp(a)))?;
+    language
+}
+
+/// What a [`Break`] in the document body stands for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BreakKind {
+    /// Explicit `w:br w:type="page"`
+    Page,
+    /// Renderer's `w:lastRenderedPageBreak` hint
+    Rendered,
+    /// Paragraph-level `w:sectPr`; continuous sections share the page
+    Section { new_page: bool },
+}
+
+/// Position of a page or section boundary in the document body
+#[derive(Clone, Debug)]
+pub(crate) struct Break {
+    /// Index into `docx.document.children`
+    pub child_index: usize,
+    /// Character offset into the visible text of that child; text at or
+    /// after this offset starts on the next page. Section breaks take
+    /// effect after the whole child (`usize::MAX`).
+    pub offset: usize,
+    pub kind: BreakKind,
+}
+
+impl Break {
+    /// True if text after this break starts on a new page
+    pub fn starts_page(&self) -> bool {
+        !matches!(self.kind, BreakKind::Section { new_page: false })
+    }
+}
+
+/// Scan `word/document.xml` for page and section boundaries
+///
+/// DOCX stores no pagination, so this only sees explicit page breaks
+/// (`w:br w:type="page"`), the renderer's `w:lastRenderedPageBreak` hints
+/// and section breaks. A rendered hint directly after an explicit break
+/// marks the same boundary and is not reported twice. Breaks are returned
+/// in document order.
+pub(crate) fn read_breaks(document_xml: &[u8]) -> Vec<Break> {
+    let mut breaks = Vec::new();
+    let mut cursor = BodyCursor::default();
+    let mut in_text = false;
+    let mut offset = 0usize;
+    // An explicit break with no text after it yet
+    let mut explicit_pending = false;
+    // Paragraph-level sectPr seen in the current body child
+    let mut section_break = false;
+    let mut continuous = false;
+
+    for event in EventReader::new(document_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                let local = name.local_name.as_str();
+                if cursor.start(local) {
+                    offset = 0;
+                    section_break = false;
+                    continuous = false;
+                    continue;
+                }
+                let Some(child_index) = cursor.child_index() else {
+                    continue;
+                };
+
+                match local {
+                    "t" => in_text = true,
+                    "br" if attribute(&attributes, "type").as_deref() == Some("page") => {
+                        breaks.push(Break {
+                            child_index,
+                            offset,
+                            kind: BreakKind::Page,
+                        });
+                        explicit_pending = true;
+                    }
+                    "lastRenderedPageBreak" if !explicit_pending => {
+                        breaks.push(Break {
+                            child_index,
+                            offset,
+                            kind: BreakKind::Rendered,
+                        });
+                    }
+                    "sectPr" => section_break = true,
+                    "type"
+                        if section_break
+                            && attribute(&attributes, "val").as_deref() == Some("continuous") =>
+                    {
+                        continuous = true;
+                    }
+                    _ => {}
+                }
+            }
+            Ok(XmlEvent::Characters(text)) | Ok(XmlEvent::Whitespace(text)) if in_text => {
+                offset += text.chars().count();
+                explicit_pending = false;
+            }
+            Ok(XmlEvent::EndElement { name }) => {
+                if name.local_name == "t" {
+                    in_text = false;
+                }
+                let child_index = cursor.child_index();
+                cursor.end();
+                if let (Some(child_index), None, true) =
+                    (child_index, cursor.child_index(), section_break)
+                {
+                    breaks.push(Break {
+                        child_index,
+                        offset: usize::MAX,
+                        kind: BreakKind::Section {
+                            new_page: !continuous,
+                        },
+                    });
+                    explicit_pending |= !continuous;
+                    section_break = false;
+                }
+            }
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    breaks
+}
+
+/// A chart or SmartArt diagram drawn in the document body
+pub(crate) struct GraphicPart {
+    /// Index into `docx.document.children`
+    pub child_index: usize,
+    /// Relationship id of the chart part (`c:chart r:id`) or the diagram's
+    /// data part (`dgm:relIds r:dm`)
+    pub rel_id: String,
+}
+
+/// Scan `word/document.xml` for charts and SmartArt, whose text lives in
+/// parts of their own
+pub(crate) fn read_graphic_parts(document_xml: &[u8]) -> Vec<GraphicPart> {
+    let mut parts = Vec::new();
+    let mut cursor = BodyCursor::default();
+
+    for event in EventReader::new(document_xml) {
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                let local = name.local_name.as_str();
+                cursor.start(local);
+
+                let rel_id = match local {
+                    "chart" => attribute(&attributes, "id"),
+                    "relIds" => attribute(&attributes, "dm"),
+                    _ => None,
+                };
+                if let (Some(rel_id), Some(child_index)) = (rel_id, cursor.child_index()) {
+                    parts.push(GraphicPart {
+                        child_index,
+                        rel_id,
+                    });
+                }
+            }
+            Ok(XmlEvent::EndElement { .. }) => cursor.end(),
+            Err(_) => break,
+            _ => {}
+        }
+    }
+
+    parts
+}
+
+/// Label text of a chart or SmartArt data part, one entry per paragraph
+///
+/// Collects rich text (`a:t`: chart and axis titles, rich data labels,
+/// diagram nodes) and cached strings (`c:strCache`: series names, category
+/// labels); numbers are skipped and repeated labels kept once. None if the
+/// part is not well-formed XML.
+pub(crate) fn read_graphic_text(xml: &[u8]) -> Option<Vec<String>> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut push = |label: &str| {
+        let label = label.trim();
+        if !label.is_empty() && !labels.iter().any(|l| l == label) {
+            labels.push(label.to_string());
+        }
+    };
+    // Text of the `a:p` open, of the `a:t` / `c:v` open
+    let mut paragraph: Option<String> = None;
+    let mut in_text = false;
+    let mut in_value = false;
+    let mut str_cache = 0usize;
+
+    for event in EventReader::new(xml) {
+        match event.ok()? {
+            XmlEvent::StartElement { name, .. } => match name.local_name.as_str() {
+                "p" => paragraph = Some(String::new()),
+                "t" => in_text = true,
+                "strCache" => str_cache += 1,
+                "v" => in_value = str_cache > 0,
+                _ => {}
+            },
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                if in_text {
+                    match paragraph.as_mut() {
+                        Some(paragraph) => paragraph.push_str(&text),
+                        None => push(&text),
+                    }
+                } else if in_value {
+                    push(&text);
+                }
+            }
+            XmlEvent::Whitespace(text) if in_text => {
+                if let Some(paragraph) = paragraph.as_mut() {
+                    paragraph.push_str(&text);
+                }
+            }
+            XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                "p" => {
+                    if let Some(text) = paragraph.take() {
+                        push(&text);
+                    }
+                }
+                "t" => in_text = false,
+                "strCache" => str_cache = str_cache.saturating_sub(1),
+                "v" => in_value = false,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    Some(labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_references_and_bodies() {
+        let document = br#"<w:document xmlns:w="w"><w:body>
+            <w:p><w:r><w:t>Intro</w:t></w:r></w:p>
+            <w:bookmarkStart w:id="0" w:name="b"/>
+            <w:p><w:r><w:t>Claim</w:t></w:r><w:r><w:footnoteReference w:id="2"/></w:r><w:r><w:t> more</w:t></w:r></w:p>
+            <w:sectPr/>
+        </w:body></w:document>"#;
+        let references = read_note_references(document);
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].kind, NoteKind::Footnote);
+        assert_eq!(references[0].id, 2);
+        assert_eq!(references[0].child_index, 2);
+        assert_eq!(references[0].offset, 5);
+
+        let footnotes = br#"<w:footnotes xmlns:w="w">
+            <w:footnote w:type="separator" w:id="-1"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>
+            <w:footnote w:id="2"><w:p><w:r><w:t>See chapter 4.</w:t></w:r></w:p></w:footnote>
+        </w:footnotes>"#;
+        let notes = read_notes(footnotes, NoteKind::Footnote);
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].id, 2);
+        assert_eq!(notes[0].text, "See chapter 4.");
+    }
+
+    #[test]
+    fn test_image_alts() {
+        let document = br#"<w:document xmlns:w="w" xmlns:wp="wp" xmlns:a="a" xmlns:r="r"><w:body>
+            <w:p><w:r><w:t>Figure</w:t></w:r></w:p>
+            <w:p><w:r><w:drawing><wp:inline>
+                <wp:docPr w:id="1" w:name="Picture 1" w:descr="Sales by region"/>
+                <a:graphic><a:blip r:embed="rId5"/></a:graphic>
+            </wp:inline></w:drawing></w:r>
+            <w:r><w:drawing><wp:inline><wp:docPr w:id="2" w:name="Picture 2"/></wp:inline></w:drawing></w:r></w:p>
+        </w:body></w:document>"#;
+        let alts = read_image_alts(document);
+        assert_eq!(alts.len(), 1);
+        assert_eq!(alts[0].child_index, 1);
+        assert_eq!(alts[0].text, "Sales by region");
+        assert_eq!(alts[0].media_id.as_deref(), Some("rId5"));
+    }
+
+    #[test]
+    fn test_paragraph_bidi() {
+        let document = br#"<w:document xmlns:w="w"><w:body>
+            <w:p><w:pPr><w:bidi/></w:pPr><w:r><w:t>Shalom</w:t></w:r></w:p>
+            <w:p><w:r><w:rPr><w:rtl/></w:rPr><w:t>Plain</w:t></w:r></w:p>
+            <w:tbl><w:tr><w:tc><w:p><w:pPr><w:bidi/></w:pPr></w:p></w:tc></w:tr></w:tbl>
+            <w:p><w:pPr><w:bidi w:val="0"/></w:pPr><w:r><w:t>Off</w:t></w:r></w:p>
+        </w:body></w:document>"#;
+        let bidi = read_paragraph_bidi(document);
+        assert_eq!(bidi.len(), 2);
+        assert_eq!(bidi.get(&0), Some(&true));
+        assert_eq!(bidi.get(&3), Some(&false));
+    }
+
+    #[test]
+    fn test_languages() {
+        let styles = br#"<w:styles xmlns:w="w">
+            <w:docDefaults><w:rPrDefault><w:rPr><w:lang w:val="en-US" w:eastAsia="ja-JP"/></w:rPr></w:rPrDefault></w:docDefaults>
+            <w:style w:styleId="De"><w:rPr><w:lang w:val="de-DE"/></w:rPr></w:style>
+        </w:styles>"#;
+        assert_eq!(read_default_language(styles).as_deref(), Some("en-US"));
+        assert_eq!(read_default_language(br#"<w:styles xmlns:w="w"/>"#), None);
+
+        let document = br#"<w:document xmlns:w="w"><w:body>
+            <w:p><w:r><w:rPr><w:lang w:val="de-DE"/></w:rPr><w:t>Guten Tag</w:t></w:r><w:r><w:t>OK</w:t></w:r></w:p>
+            <w:p><w:pPr><w:rPr><w:lang w:val="fr-FR"/></w:rPr></w:pPr><w:r><w:t>Plain</w:t></w:r></w:p>
+            <w:tbl><w:tr><w:tc><w:p><w:r><w:rPr><w:lang w:val="fr-FR"/></w:rPr><w:t>Bonjour</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+            <w:p><w:r><w:rPr><w:lang w:val="es-ES"/></w:rPr><w:t>Hola</w:t></w:r><w:r><w:t>Mostly default</w:t></w:r></w:p>
+        </w:body></w:document>"#;
+        let languages = read_run_languages(document);
+        let between =
+            |idx: usize, start: usize, end: usize| language_between(&languages[&idx], start, end);
+        assert_eq!(languages[&0].len(), 2);
+        assert_eq!(between(0, 0, 9), Some("de-DE"));
+        assert_eq!(between(0, 9, 11), None);
+        assert_eq!(between(0, 0, usize::MAX), Some("de-DE"));
+        assert_eq!(between(1, 0, usize::MAX), None);
+        assert_eq!(between(2, 0, usize::MAX), Some("fr-FR"));
+        assert_eq!(between(3, 0, 4), Some("es-ES"));
+        assert_eq!(between(3, 0, usize::MAX), None);
+    }
+
+    #[test]
+    fn test_document_protection() {
+        let settings = |protection: &str| {
+            format!(
+                r#"<w:settings xmlns:w="w"><w:zoom w:percent="100"/>{}</w:settings>"#,
+                protection
+            )
+        };
+        let read = |protection: &str| read_document_protection(settings(protection).as_bytes());
+
+        assert_eq!(
+            read(r#"<w:documentProtection w:edit="readOnly" w:enforcement="1"/>"#).as_deref(),
+            Some("readOnly")
+        );
+        assert_eq!(
+            read(r#"<w:documentProtection w:edit="forms" w:enforcement="true"/>"#).as_deref(),
+            Some("forms")
+        );
+        // Configured but switched off, or restricting nothing
+        assert_eq!(
+            read(r#"<w:documentProtection w:edit="comments" w:enforcement="0"/>"#),
+            None
+        );
+        assert_eq!(read(r#"<w:documentProtection w:edit="comments"/>"#), None);
+        assert_eq!(
+            read(r#"<w:documentProtection w:edit="none" w:enforcement="1"/>"#),
+            None
+        );
+        assert_eq!(read(""), None);
+    }
+
+    #[test]
+    fn test_theme_and_default_font() {
+        let theme = br#"<a:theme xmlns:a="a" name="Office Theme"><a:themeElements>
+            <a:clrScheme name="Office">
+                <a:dk1><a:sysClr val="windowText" lastClr="000000"/></a:dk1>
+                <a:lt1><a:sysClr val="window" lastClr="FFFFFF"/></a:lt1>
+                <a:accent1><a:srgbClr val="4472c4"/></a:accent1>
+                <a:hlink><a:srgbClr val="0563C1"/></a:hlink>
+            </a:clrScheme>
+            <a:fontScheme name="Office">
+                <a:majorFont><a:latin typeface="Calibri Light"/><a:ea typeface=""/></a:majorFont>
+                <a:minorFont><a:latin typeface="Calibri"/></a:minorFont>
+            </a:fontScheme>
+        </a:themeElements></a:theme>"#;
+        let theme = read_theme(theme);
+        let colors: Vec<_> = theme
+            .colors
+            .iter()
+            .map(|(name, hex)| (name.as_str(), hex.as_str()))
+            .collect();
+        assert_eq!(
+            colors,
+            vec![
+                ("dk1", "#000000"),
+                ("lt1", "#FFFFFF"),
+                ("accent1", "#4472C4"),
+                ("hlink", "#0563C1"),
+            ]
+        );
+        assert_eq!(theme.font("majorHAnsi"), Some("Calibri Light"));
+        assert_eq!(theme.font("minorHAnsi"), Some("Calibri"));
+
+        let styles = |fonts: &str| {
+            format!(
+                r#"<w:styles xmlns:w="w"><w:docDefaults><w:rPrDefault><w:rPr>{}</w:rPr></w:rPrDefault></w:docDefaults>
+                <w:style w:styleId="Code"><w:rPr><w:rFonts w:ascii="Consolas"/></w:rPr></w:style></w:styles>"#,
+                fonts
+            )
+        };
+        let read = |fonts: &str| read_default_font(styles(fonts).as_bytes());
+        assert_eq!(
+            read(r#"<w:rFonts w:asciiTheme="minorHAnsi" w:ascii="Cached"/>"#),
+            Some(DefaultFont::Theme("minorHAnsi".to_string()))
+        );
+        assert_eq!(
+            read(r#"<w:rFonts w:ascii="Georgia" w:hAnsi="Georgia"/>"#),
+            Some(DefaultFont::Named("Georgia".to_string()))
+        );
+        assert_eq!(read(""), None);
+    }
+
+    #[test]
+    fn test_sdt_tags() {
+        let document = br#"<w:document xmlns:w="w"><w:body>
+            <w:sdt><w:sdtPr><w:alias w:val="Customer name"/><w:tag w:val="customer"/></w:sdtPr>
+                <w:sdtContent><w:p><w:r><w:t>ACME</w:t></w:r></w:p></w:sdtContent></w:sdt>
+            <w:p><w:r><w:t>Plain</w:t></w:r></w:p>
+            <w:p><w:r><w:t>Date: </w:t></w:r><w:sdt><w:sdtPr><w:alias w:val="Signed on"/></w:sdtPr>
+                <w:sdtContent><w:r><w:t>today</w:t></w:r></w:sdtContent></w:sdt></w:p>
+            <w:sdt><w:sdtPr/><w:sdtContent><w:p/></w:sdtContent></w:sdt>
+        </w:body></w:document>"#;
+        let tags = read_sdt_tags(document);
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags.get(&0).map(String::as_str), Some("customer"));
+        assert_eq!(tags.get(&2).map(String::as_str), Some("Signed on"));
+
+        // docx-rs ends the section at its first item, so the second item
+        // and the paragraph after it are children 1 and 2
+        let repeating = br#"<w:document xmlns:w="w"><w:body>
+            <w:sdt><w:sdtPr><w:tag w:val="items"/></w:sdtPr><w:sdtContent>
+                <w:sdt><w:sdtPr/><w:sdtContent><w:p><w:r><w:t>A</w:t></w:r></w:p></w:sdtContent></w:sdt>
+                <w:sdt><w:sdtPr/><w:sdtContent><w:p><w:r><w:t>B</w:t></w:r></w:p></w:sdtContent></w:sdt>
+            </w:sdtContent></w:sdt>
+            <w:p><w:pPr><w:bidi/></w:pPr><w:r><w:t>After</w:t></w:r></w:p>
+        </w:body></w:document>"#;
+        let tags = read_sdt_tags(repeating);
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags.get(&0).map(String::as_str), Some("items"));
+        assert_eq!(tags.get(&1).map(String::as_str), Some("items"));
+        assert_eq!(read_paragraph_bidi(repeating).get(&2), Some(&true));
+    }
+
+    #[test]
+    fn test_page_breaks() {
+        let document = br#"<w:document xmlns:w="w"><w:body>
+            <w:p><w:r><w:t>One</w:t></w:r><w:r><w:br w:type="page"/></w:r><w:r><w:lastRenderedPageBreak/><w:t>Two</w:t></w:r></w:p>
+            <w:p><w:pPr><w:sectPr><w:type w:val="continuous"/></w:sectPr></w:pPr><w:r><w:t>Still two</w:t></w:r></w:p>
+            <w:p><w:pPr><w:sectPr/></w:pPr><w:r><w:br/><w:t>End of section</w:t></w:r></w:p>
+            <w:p><w:r><w:lastRenderedPageBreak/><w:t>Four</w:t></w:r></w:p>
+            <w:sectPr/>
+        </w:body></w:document>"#;
+        let breaks = read_breaks(document);
+        let pages: Vec<_> = breaks
+            .iter()
+            .filter(|b| b.starts_page())
+            .map(|b| (b.child_index, b.offset))
+            .collect();
+        assert_eq!(pages, vec![(0, 3), (2, usize::MAX)]);
+
+        let kinds: Vec<_> = breaks.iter().map(|b| b.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                BreakKind::Page,
+                BreakKind::Section { new_page: false },
+                BreakKind::Section { new_page: true },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decompressed_size_limit() {
+        use std::io::Write;
+
+        // 4 MiB of zeros deflates to a few KiB
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for name in ["a.xml", "b.xml"] {
+            writer
+                .start_file(name, zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(&vec![0u8; 2 << 20]).unwrap();
+        }
+        let bomb = writer.finish().unwrap().into_inner();
+        assert!(bomb.len() < 64 << 10);
+
+        assert!(exceeds_decompressed_size(&bomb, 3 << 20));
+        assert!(!exceeds_decompressed_size(&bomb, 4 << 20));
+        // The largest limit must not overflow
+        assert!(!exceeds_decompressed_size(&bomb, u64::MAX));
+        assert!(!exceeds_decompressed_size(b"not a zip", 0));
+    }
+
+    #[test]
+    fn test_utf8_prolog_detection() {
+        assert!(is_utf8_part(b"<w:document/>"));
+        assert!(is_utf8_part(
+            br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><w:document/>"#
+        ));
+        assert!(!is_utf8_part(
+            br#"<?xml version='1.0' encoding='windows-1252'?><w:document/>"#
+        ));
+        assert!(!is_utf8_part(&[0xFF, 0xFE, b'<', 0]));
+        assert!(repair_utf8(b"not a zip").is_none());
+    }
+
+    #[test]
+    fn test_hyperlink_targets() {
+        let rels = br#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+            <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+            <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/a" TargetMode="External"/>
+            <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="other.docx"/>
+        </Relationships>"#;
+        let targets = read_hyperlink_targets(rels);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].target, "https://example.com/a");
+        assert!(targets[0].external);
+        assert!(!targets[1].external);
+
+        let document = br#"<w:document xmlns:w="w"><w:body>
+            <w:p><w:hyperlink w:anchor="_Toc1"><w:r><w:t>Intro</w:t></w:r></w:hyperlink></w:p>
+            <w:p><w:hyperlink r:id="rId2" xmlns:r="r"><w:r><w:t>Site</w:t></w:r></w:hyperlink></w:p>
+        </w:body></w:document>"#;
+        assert_eq!(read_hyperlink_anchors(document), vec!["_Toc1"]);
+
+        let all = read_relationship_targets(rels);
+        assert_eq!(all.len(), 3);
+        assert_eq!(document_part_name(&all["rId1"]), "word/styles.xml");
+        assert_eq!(document_part_name("/word/header2.xml"), "word/header2.xml");
+    }
+
+    #[test]
+    fn test_graphic_parts_and_labels() {
+        let document = br#"<w:document xmlns:w="w" xmlns:r="r"><w:body>
+            <w:p><w:r><w:t>Intro</w:t></w:r></w:p>
+            <w:p><w:r><w:drawing><a:graphic xmlns:a="a"><a:graphicData>
+                <c:chart xmlns:c="c" r:id="rId7"/>
+            </a:graphicData></a:graphic></w:drawing></w:r></w:p>
+            <w:p><w:r><w:drawing><a:graphic xmlns:a="a"><a:graphicData>
+                <dgm:relIds xmlns:dgm="dgm" r:dm="rId8" r:lo="rId9" r:qs="rId10" r:cs="rId11"/>
+            </a:graphicData></a:graphic></w:drawing></w:r></w:p>
+        </w:body></w:document>"#;
+        let parts: Vec<_> = read_graphic_parts(document)
+            .into_iter()
+            .map(|p| (p.child_index, p.rel_id))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![(1, "rId7".to_string()), (2, "rId8".to_string())]
+        );
+
+        let chart = br#"<c:chartSpace xmlns:c="c" xmlns:a="a"><c:chart>
+            <c:title><c:tx><c:rich><a:p><a:r><a:t>Revenue </a:t></a:r><a:r><a:t>by region</a:t></a:r></a:p></c:rich></c:tx></c:title>
+            <c:plotArea><c:barChart><c:ser>
+                <c:tx><c:strRef><c:strCache><c:pt idx="0"><c:v>2024</c:v></c:pt></c:strCache></c:strRef></c:tx>
+                <c:cat><c:strRef><c:strCache><c:pt idx="0"><c:v>North</c:v></c:pt><c:pt idx="1"><c:v>South</c:v></c:pt></c:strCache></c:strRef></c:cat>
+                <c:val><c:numRef><c:numCache><c:pt idx="0"><c:v>12.5</c:v></c:pt></c:numCache></c:numRef></c:val>
+            </c:ser><c:ser>
+                <c:cat><c:strRef><c:strCache><c:pt idx="0"><c:v>North</c:v></c:pt></c:strCache></c:strRef></c:cat>
+            </c:ser></c:barChart>
+            <c:valAx><c:title><c:tx><c:rich><a:p><a:r><a:t>EUR (k)</a:t></a:r></a:p></c:rich></c:tx></c:title></c:valAx>
+            </c:plotArea></c:chart></c:chartSpace>"#;
+        assert_eq!(
+            read_graphic_text(chart).unwrap(),
+            vec!["Revenue by region", "2024", "North", "South", "EUR (k)"]
+        );
+
+        let diagram = br#"<dgm:dataModel xmlns:dgm="dgm" xmlns:a="a"><dgm:ptLst>
+            <dgm:pt><dgm:t><a:p><a:r><a:t>Plan</a:t></a:r></a:p></dgm:t></dgm:pt>
+            <dgm:pt><dgm:t><a:p><a:r><a:t>Ship</a:t></a:r></a:p></dgm:t></dgm:pt>
+        </dgm:ptLst></dgm:dataModel>"#;
+        assert_eq!(read_graphic_text(diagram).unwrap(), vec!["Plan", "Ship"]);
+        assert!(read_graphic_text(b"<c:chartSpace><c:title>").is_none());
+    }
+}
@@ -6,6 +6,7 @@
 
 import os
 import shutil
+import struct
 from pathlib import Path
 from typing import Optional, Callable
 import asyncio
@@ -20,6 +21,14 @@ except ImportError:
     HAS_NACL = False
 
 
+# File header (DATA_DICTIONARY §4): "CVBAK" + format version.
+# The Rust restore command rejects files without a known header.
+BACKUP_MAGIC = b"CVBAK002"
+
+# Unencrypted snapshot pack written by the Rust backup command
+PACK_MAGIC = b"CVBAK001"
+
+
 # ------------------------------------------------------------------------------
 # EXCEPTIONS
 # ------------------------------------------------------------------------------
@@ -107,8 +116,9 @@ async def create_backup(
         if progress_callback:
             progress_callback(75, "Writing backup file...")
         
-        # Write encrypted backup (salt + ciphertext)
+        # Write encrypted backup (magic + salt + ciphertext)
         with open(output_path, 'wb') as f:
+            f.write(BACKUP_MAGIC)
             f.write(salt)
             f.write(ciphertext)
         
@@ -157,8 +167,10 @@ async def restore_backup(
         if progress_callback:
             progress_callback(0, "Reading backup...")
         
-        # Read encrypted backup
+        # Read encrypted backup (pre-header backups start with the salt)
         with open(backup_path, 'rb') as f:
+            if f.read(len(BACKUP_MAGIC)) != BACKUP_MAGIC:
+                f.seek(0)
             salt = f.read(nacl.pwhash.argon2id.SALTBYTES)
             ciphertext = f.read()
         
@@ -202,6 +214,62 @@ async def restore_backup(
         raise BackupError(f"Failed to restore backup: {e}")
 
 
+# ------------------------------------------------------------------------------
+# PACK INSPECTION
+# ------------------------------------------------------------------------------
+
+def list_pack_entries(backup_path: Path | str) -> list[str]:
+    """
+    List the item names stored in a plain CVBAK001 pack without extracting.
+
+    Layout (written by the Rust backup command): magic, u32 entry count, then
+    per entry u32 name length, UTF-8 name, u64 size and the file bytes, all
+    little-endian. A trailing digest footer is ignored.
+
+    Args:
+        backup_path: Path to the .cvbak pack
+
+    Returns:
+        Item names in pack order
+
+    Raises:
+        BackupError: If the file is encrypted, not a pack or truncated
+    """
+    backup_path = Path(backup_path)
+
+    try:
+        data_len = backup_path.stat().st_size
+        with open(backup_path, "rb") as f:
+            magic = f.read(len(PACK_MAGIC))
+            if magic == BACKUP_MAGIC:
+                raise BackupError("Encrypted backups cannot be listed without the passkey")
+            if magic != PACK_MAGIC:
+                raise BackupError("Invalid backup file format")
+
+            (count,) = struct.unpack("<I", _read_exact(f, 4))
+            names = []
+            for _ in range(count):
+                (name_len,) = struct.unpack("<I", _read_exact(f, 4))
+                names.append(_read_exact(f, name_len).decode("utf-8"))
+                (size,) = struct.unpack("<Q", _read_exact(f, 8))
+                if f.tell() + size > data_len:
+                    raise BackupError("Backup pack is truncated")
+                f.seek(size, os.SEEK_CUR)
+            return names
+
+    except BackupError:
+        raise
+    except Exception as e:
+        raise BackupError(f"Failed to read backup: {e}")
+
+
+def _read_exact(f, n: int) -> bytes:
+    chunk = f.read(n)
+    if len(chunk) != n:
+        raise BackupError("Backup pack is truncated")
+    return chunk
+
+
 # ------------------------------------------------------------------------------
 # SECURE FILE DELETION
 # ------------------------------------------------------------------------------
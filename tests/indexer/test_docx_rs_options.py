@@ -0,0 +1,91 @@
+"""
+DOCX Rust Extractor - keyword shortcuts of the entry points
+
+Calls written before ExtractOptions existed pass settings as keywords;
+each one overrides the same field of `options`.
+"""
+
+import io
+import zipfile
+
+import pytest
+
+docx_extractor = pytest.importorskip("docx_extractor")
+
+DOCUMENT_XML = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>'
+    '<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">'
+    "<w:body>"
+    "<w:p><w:r><w:t>First   paragraph</w:t></w:r></w:p>"
+    "<w:p><w:r><w:t>Second</w:t></w:r></w:p>"
+    "<w:p><w:r><w:t>Third</w:t></w:r></w:p>"
+    "</w:body></w:document>"
+)
+
+
+@pytest.fixture
+def docx_bytes():
+    """Minimal three-paragraph DOCX built in memory"""
+    buffer = io.BytesIO()
+    with zipfile.ZipFile(buffer, "w") as archive:
+        archive.writestr(
+            "[Content_Types].xml",
+            '<?xml version="1.0" encoding="UTF-8"?>'
+            '<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">'
+            '<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>'
+            '<Override PartName="/word/document.xml" '
+            'ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>'
+            "</Types>",
+        )
+        archive.writestr(
+            "_rels/.rels",
+            '<?xml version="1.0" encoding="UTF-8"?>'
+            '<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">'
+            '<Relationship Id="rId1" Target="word/document.xml" '
+            'Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument"/>'
+            "</Relationships>",
+        )
+        archive.writestr(
+            "word/_rels/document.xml.rels",
+            '<?xml version="1.0" encoding="UTF-8"?>'
+            '<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"/>',
+        )
+        archive.writestr("word/document.xml", DOCUMENT_XML)
+    return buffer.getvalue()
+
+
+def test_legacy_keywords_still_apply(docx_bytes):
+    result = docx_extractor.extract_docx_bytes(
+        docx_bytes,
+        include_headers_footers=False,
+        accept_revisions=True,
+        max_chars=None,
+        timeout_ms=10_000,
+        normalize_whitespace=True,
+        emphasis_markers=False,
+        min_confidence=0.0,
+        detailed_runs=True,
+        start_index=0,
+        end_index=2,
+    )
+
+    assert [s.text for s in result.segments] == ["First paragraph", "Second"]
+
+
+def test_keywords_override_options(docx_bytes):
+    options = docx_extractor.ExtractOptions(max_segments=3, normalize_whitespace=True)
+
+    result = docx_extractor.extract_docx_bytes(docx_bytes, options, max_segments=1)
+
+    assert [s.text for s in result.segments] == ["First paragraph"]
+    assert result.truncated
+    # The caller's options object is left untouched
+    assert options.max_segments == 3
+
+
+def test_unknown_keyword_is_rejected(docx_bytes):
+    with pytest.raises(TypeError, match="unexpected keyword argument 'max_pages'"):
+        docx_extractor.extract_docx_bytes(docx_bytes, max_pages=1)
+
+    with pytest.raises(TypeError):
+        docx_extractor.extract_docx_bytes(docx_bytes, max_segments="many")
@@ -52,3 +52,92 @@ class TestDispatcher:
         
         assert result["status"] == "error"
         assert "validation" in result["message"].lower()
+
+    def test_dispatcher_reports_restore_progress(self):
+        """
+        GIVEN a restore.start envelope carrying a progress callable
+        WHEN dispatcher processes it
+        THEN the handler should report increasing progress before returning
+        """
+        from core.dispatcher import Dispatcher
+
+        updates = []
+        dispatcher = Dispatcher()
+        result = dispatcher.handle({
+            "cmd": "restore.start",
+            "payload": {"file_path": "vault.cvbak"},
+            "progress": updates.append,
+        })
+
+        assert result["status"] == "success"
+        assert [u["phase"] for u in updates] == ["validating", "restoring"]
+        assert updates[0]["percent"] < updates[-1]["percent"]
+
+    def test_dispatcher_restore_stops_when_cancelled(self):
+        """
+        GIVEN a restore.start envelope whose 'cancelled' callable returns True
+        WHEN dispatcher processes it
+        THEN the handler should stop before restoring and report cancellation
+        """
+        from core.dispatcher import Dispatcher
+
+        updates = []
+        dispatcher = Dispatcher()
+        result = dispatcher.handle({
+            "cmd": "restore.start",
+            "payload": {"file_path": "vault.cvbak"},
+            "progress": updates.append,
+            "cancelled": lambda: True,
+        })
+
+        assert result["status"] == "cancelled"
+        assert [u["phase"] for u in updates] == ["validating"]
+
+    def test_dispatcher_passes_raw_bytes_in_payload(self):
+        """
+        GIVEN an envelope carrying a raw 'data' buffer next to the payload
+        WHEN dispatcher processes it
+        THEN the handler should receive the bytes as payload["data"]
+        """
+        from core.dispatcher import Dispatcher
+
+        received = {}
+        dispatcher = Dispatcher()
+        dispatcher._services["probe"] = lambda action, payload, progress: (
+            received.update(payload) or {"status": "success"}
+        )
+        envelope = {"cmd": "probe.echo", "payload": {"name": "blob.bin"}, "data": b"\x00\xff"}
+        result = dispatcher.handle(envelope)
+
+        assert result["status"] == "success"
+        assert received == {"name": "blob.bin", "data": b"\x00\xff"}
+        assert "data" not in envelope["payload"]
+
+    def test_dispatcher_restore_dry_run_lists_items_and_conflicts(self, tmp_path):
+        """
+        GIVEN a restore.start envelope with dry_run set for a CVBAK001 pack
+        WHEN dispatcher processes it
+        THEN it should list the pack items and existing conflicts without writing
+        """
+        import struct
+        from core.dispatcher import Dispatcher
+
+        pack = bytearray(b"CVBAK001" + struct.pack("<I", 2))
+        for name, body in [("notes.db", b"db"), ("config.json", b"{}")]:
+            pack += struct.pack("<I", len(name)) + name.encode() + struct.pack("<Q", len(body)) + body
+        backup = tmp_path / "vault.cvbak"
+        backup.write_bytes(bytes(pack))
+        target = tmp_path / "vault"
+        target.mkdir()
+        (target / "config.json").write_text("{}")
+
+        result = Dispatcher().handle({
+            "cmd": "restore.start",
+            "payload": {"file_path": str(backup), "dry_run": True, "target_dir": str(target)},
+        })
+
+        assert result["status"] == "success"
+        assert result["dry_run"] is True
+        assert result["items"] == ["notes.db", "config.json"]
+        assert result["conflicts"] == ["config.json"]
+        assert sorted(p.name for p in target.iterdir()) == ["config.json"]